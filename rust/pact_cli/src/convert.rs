@@ -0,0 +1,56 @@
+use clap::ArgMatches;
+use std::fs;
+use std::path::Path;
+use pact_matching::models::Pact;
+use pact_matching::conversion::{convert_pact, parse_spec_version};
+
+fn convert_file(file: &str, matches: &ArgMatches) -> Result<usize, String> {
+    let spec_version = parse_spec_version(matches.value_of("to").unwrap())
+        .ok_or_else(|| format!("'{}' is not a valid specification version", matches.value_of("to").unwrap()))?;
+    let pact = Pact::read_pact_strict(Path::new(file))
+        .map_err(|err| format!("Failed to load pact file '{}': {}", file, err))?;
+    let conversion = convert_pact(&pact, &spec_version)?;
+
+    for warning in &conversion.warnings {
+        eprintln!("WARNING: {}: {}", file, warning);
+    }
+
+    match matches.value_of("out-dir") {
+        Some(dir) => {
+            let dir = Path::new(dir);
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create directory '{}': {}", dir.display(), err))?;
+            let path = dir.join(Path::new(file).file_name().unwrap());
+            fs::write(&path, conversion.to_string())
+                .map_err(|err| format!("Failed to write pact file '{}': {}", path.display(), err))?;
+        },
+        None => println!("{}", conversion.to_string())
+    }
+
+    Ok(conversion.warnings.len())
+}
+
+pub fn convert_pact_files(matches: &ArgMatches) -> Result<(), i32> {
+    let files: Vec<&str> = matches.values_of("file").unwrap().collect();
+    if files.len() > 1 && !matches.is_present("out-dir") {
+        eprintln!("ERROR: --out-dir is required when converting more than one pact file");
+        return Err(2);
+    }
+
+    let mut warnings = 0;
+    for file in files {
+        match convert_file(file, matches) {
+            Ok(count) => warnings += count,
+            Err(err) => {
+                eprintln!("ERROR: {}", err);
+                return Err(2);
+            }
+        }
+    }
+
+    if warnings > 0 && matches.is_present("fail-on-warnings") {
+        Err(1)
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use clap::ArgMatches;
+use std::path::Path;
+use serde_json::Value;
+use pact_matching::models::Pact;
+use pact_matching::pact_diff::{diff_pacts, has_breaking_changes, PactChange};
+
+fn display_text(changes: &Vec<PactChange>) {
+    if changes.is_empty() {
+        println!("No changes found");
+    } else {
+        for change in changes {
+            println!("{}", change);
+        }
+        let breaking = changes.iter().filter(|change| change.is_breaking()).count();
+        println!("\n{} change(s), {} breaking", changes.len(), breaking);
+    }
+}
+
+fn display_json(changes: &Vec<PactChange>) {
+    let json = json!({
+        s!("breaking"): has_breaking_changes(changes),
+        s!("changes"): Value::Array(changes.iter().map(|change| change.to_json()).collect())
+    });
+    println!("{}", json);
+}
+
+pub fn diff_pact_files(matches: &ArgMatches) -> Result<(), i32> {
+    let old_file = matches.value_of("old").unwrap();
+    let new_file = matches.value_of("new").unwrap();
+    let old = Pact::read_pact_strict(Path::new(old_file)).map_err(|err| {
+        eprintln!("ERROR: Failed to load pact file '{}': {}", old_file, err);
+        2
+    })?;
+    let new = Pact::read_pact_strict(Path::new(new_file)).map_err(|err| {
+        eprintln!("ERROR: Failed to load pact file '{}': {}", new_file, err);
+        2
+    })?;
+
+    let changes = diff_pacts(&old, &new);
+    match matches.value_of("output") {
+        Some("json") => display_json(&changes),
+        _ => display_text(&changes)
+    }
+
+    if has_breaking_changes(&changes) {
+        Err(1)
+    } else {
+        Ok(())
+    }
+}
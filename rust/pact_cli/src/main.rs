@@ -0,0 +1,232 @@
+//! # Pact Command Line Tools
+//!
+//! This project provides command line tools for working with pact files. It is a single executable
+//! binary, `pact_cli`, with a sub-command for each tool.
+//!
+//! ## Validating pact files
+//!
+//! The `validate` sub-command checks pact files for problems, and is intended to be run as part of
+//! a CI build. It reports problems with the structure of the pact file (like a missing request or a
+//! status code that is not a number), as well as semantic problems:
+//!
+//! | Check | Severity | Description |
+//! |-------|----------|-------------|
+//! | `invalid-pact` | Error | The pact file does not have the correct structure |
+//! | `duplicate-interaction` | Error | Two interactions have the same description and provider states |
+//! | `regex-does-not-match-example` | Error | A regex matching rule does not match the example value |
+//! | `min-type-not-satisfied` | Error | The example for a minimum type matcher has fewer items than the minimum |
+//! | `max-type-exceeded` | Error | The example for a maximum type matcher has more items than the maximum |
+//! | `generator-path-not-found` | Warning | A generator has been defined for a value that does not exist |
+//! | `unknown-spec-version` | Warning | The specification version of the pact file is unknown |
+//! | `missing-content-type` | Warning | A body is present, but there is no content type for it |
+//!
+//! ```console,ignore
+//! $ pact_cli validate -f pacts/consumer-provider.json
+//! pacts/consumer-provider.json:
+//!   ERROR [regex-does-not-match-example] $.interactions[0].response.matchingRules.body['$.id']: Example value 'x' at $.id does not match the regex '\d+'
+//!
+//! 1 error(s), 0 warning(s)
+//! ```
+//!
+//! The exit code is 0 if there are no findings at or above the `--fail-on` severity (defaults to
+//! error), 1 if there are, and 2 if any of the pact files could not be loaded. Use `--output json`
+//! to get the findings in a machine readable format.
+//!
+//! ## Comparing pact files
+//!
+//! The `diff` sub-command compares two versions of a pact file, and lists all the changes along with
+//! whether they are breaking for the provider (a provider verified against the old version may fail
+//! verification against the new one).
+//!
+//! ```console,ignore
+//! $ pact_cli diff pacts/v1/consumer-provider.json pacts/v2/consumer-provider.json
+//! [BREAKING] 'get user' response.body.email: Field 'email' has been added
+//! [compatible] 'get user' response.matchingRules.body['$.id']: Matching rule [Type] has been added
+//!
+//! 2 change(s), 1 breaking
+//! ```
+//!
+//! The exit code is 0 if there are no breaking changes, 1 if there are, and 2 if either of the pact
+//! files could not be loaded.
+//!
+//! ## Converting pact files
+//!
+//! The `convert` sub-command converts pact files to another version of the pact specification. Any
+//! features that can not be represented in the target version (like generators or multiple provider
+//! states in a V2 pact) are removed, and a warning is printed for each one.
+//!
+//! ```console,ignore
+//! $ pact_cli convert --to v2 pacts/consumer-provider.json > v2/consumer-provider.json
+//! WARNING: pacts/consumer-provider.json: 'get user' request.generators: Generators are not supported by V2 pacts and have been removed
+//! ```
+//!
+//! Converted pacts are written to standard output, or to the directory given with `--out-dir`
+//! (which is required when converting more than one file). The exit code is 0 if all the pact files
+//! were converted, 1 if there were warnings and `--fail-on-warnings` was given, and 2 if any of the
+//! pact files could not be loaded or converted.
+
+#![warn(missing_docs)]
+
+#[macro_use] extern crate clap;
+#[macro_use] extern crate pact_matching;
+#[macro_use] extern crate serde_json;
+extern crate simplelog;
+extern crate log;
+
+#[cfg(test)]
+#[macro_use(expect)]
+extern crate expectest;
+
+use clap::{Arg, App, SubCommand, AppSettings, ErrorKind};
+use std::env;
+use std::str::FromStr;
+use log::LevelFilter;
+use simplelog::{TermLogger, Config, TerminalMode};
+use pact_matching::models::PactSpecification;
+
+mod validate;
+mod diff;
+mod convert;
+
+fn print_version() {
+    println!("\npact cli version          : v{}", crate_version!());
+    println!("pact specification version: v{}", PactSpecification::V3.version_str());
+}
+
+fn main() {
+    match handle_command_args() {
+        Ok(_) => (),
+        Err(err) => std::process::exit(err)
+    }
+}
+
+fn handle_command_args() -> Result<(), i32> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let version = format!("v{}", crate_version!());
+    let app = App::new(program)
+        .version(version.as_str())
+        .about("Command line tools for working with pact files")
+        .version_short("v")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .setting(AppSettings::SubcommandRequired)
+        .setting(AppSettings::GlobalVersion)
+        .setting(AppSettings::VersionlessSubcommands)
+        .setting(AppSettings::ColoredHelp)
+        .arg(Arg::with_name("loglevel")
+            .short("l")
+            .long("loglevel")
+            .takes_value(true)
+            .use_delimiter(false)
+            .global(true)
+            .possible_values(&["error", "warn", "info", "debug", "trace", "none"])
+            .help("Log level (defaults to warn)"))
+        .subcommand(SubCommand::with_name("validate")
+            .about("Validates pact files, reporting any problems found")
+            .arg(Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .required_unless("dir")
+                .takes_value(true)
+                .use_delimiter(false)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Pact file to validate (can be repeated)"))
+            .arg(Arg::with_name("dir")
+                .short("d")
+                .long("dir")
+                .required_unless("file")
+                .takes_value(true)
+                .use_delimiter(false)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Directory of pact files to validate (can be repeated)"))
+            .arg(Arg::with_name("fail-on")
+                .long("fail-on")
+                .takes_value(true)
+                .use_delimiter(false)
+                .possible_values(&["error", "warning", "info"])
+                .help("Minimum severity of the findings that will result in a failure (defaults to error)"))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .use_delimiter(false)
+                .possible_values(&["text", "json"])
+                .help("Output format for the findings (defaults to text)"))
+            .setting(AppSettings::ColoredHelp))
+        .subcommand(SubCommand::with_name("diff")
+            .about("Compares two versions of a pact file, reporting the changes and if they are breaking")
+            .arg(Arg::with_name("old")
+                .required(true)
+                .index(1)
+                .help("The previous version of the pact file"))
+            .arg(Arg::with_name("new")
+                .required(true)
+                .index(2)
+                .help("The new version of the pact file"))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .use_delimiter(false)
+                .possible_values(&["text", "json"])
+                .help("Output format for the changes (defaults to text)"))
+            .setting(AppSettings::ColoredHelp))
+        .subcommand(SubCommand::with_name("convert")
+            .about("Converts pact files to another version of the pact specification")
+            .arg(Arg::with_name("file")
+                .required(true)
+                .multiple(true)
+                .help("Pact file to convert (can be repeated)"))
+            .arg(Arg::with_name("to")
+                .short("t")
+                .long("to")
+                .required(true)
+                .takes_value(true)
+                .use_delimiter(false)
+                .help("Specification version to convert to (v1, v1.1, v2 or v3)"))
+            .arg(Arg::with_name("out-dir")
+                .long("out-dir")
+                .takes_value(true)
+                .use_delimiter(false)
+                .help("Directory to write the converted pact files to (defaults to standard output)"))
+            .arg(Arg::with_name("fail-on-warnings")
+                .long("fail-on-warnings")
+                .help("Exit with an error if any features could not be converted"))
+            .setting(AppSettings::ColoredHelp))
+    ;
+
+    let matches = app.get_matches_safe();
+    match matches {
+        Ok(ref matches) => {
+            let level = matches.value_of("loglevel").unwrap_or("warn");
+            let log_level = match level {
+                "none" => LevelFilter::Off,
+                _ => LevelFilter::from_str(level).unwrap()
+            };
+            TermLogger::init(log_level, Config::default(), TerminalMode::Stderr).unwrap_or_default();
+            match matches.subcommand() {
+                ("validate", Some(sub_matches)) => validate::validate_pacts(sub_matches),
+                ("diff", Some(sub_matches)) => diff::diff_pact_files(sub_matches),
+                ("convert", Some(sub_matches)) => convert::convert_pact_files(sub_matches),
+                _ => Err(3)
+            }
+        },
+        Err(ref err) => {
+            match err.kind {
+                ErrorKind::HelpDisplayed => {
+                    println!("{}", err.message);
+                    Ok(())
+                },
+                ErrorKind::VersionDisplayed => {
+                    print_version();
+                    println!();
+                    Ok(())
+                },
+                _ => err.exit()
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use clap::ArgMatches;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use pact_matching::validation::{validate_pact_file, ValidationFinding, Severity, count_by_severity};
+
+fn pact_files_in_dir(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(pact_files_in_dir(&path)?);
+        } else if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn fail_on_severity(value: Option<&str>) -> Severity {
+    match value {
+        Some("warning") => Severity::Warning,
+        Some("info") => Severity::Info,
+        _ => Severity::Error
+    }
+}
+
+fn exit_code(results: &Vec<(PathBuf, Result<Vec<ValidationFinding>, String>)>, fail_on: Severity) -> i32 {
+    if results.iter().any(|&(_, ref result)| result.is_err()) {
+        2
+    } else if results.iter().any(|&(_, ref result)| result.as_ref()
+        .map(|findings| findings.iter().any(|finding| finding.severity >= fail_on))
+        .unwrap_or(false)) {
+        1
+    } else {
+        0
+    }
+}
+
+fn display_text(results: &Vec<(PathBuf, Result<Vec<ValidationFinding>, String>)>) {
+    let mut all_findings = vec![];
+    for &(ref file, ref result) in results {
+        match *result {
+            Ok(ref findings) => if !findings.is_empty() {
+                println!("{}:", file.display());
+                for finding in findings {
+                    println!("  {}", finding);
+                }
+                all_findings.extend(findings.iter().cloned());
+            },
+            Err(ref err) => println!("{}:\n  Could not load the pact file - {}", file.display(), err)
+        }
+    }
+    let counts = count_by_severity(&all_findings);
+    println!("\n{} error(s), {} warning(s)", counts.get(&Severity::Error).unwrap_or(&0),
+        counts.get(&Severity::Warning).unwrap_or(&0));
+}
+
+fn display_json(results: &Vec<(PathBuf, Result<Vec<ValidationFinding>, String>)>) {
+    let json = Value::Array(results.iter().map(|&(ref file, ref result)| match *result {
+        Ok(ref findings) => json!({
+            s!("file"): file.display().to_string(),
+            s!("findings"): Value::Array(findings.iter().map(|finding| finding.to_json()).collect())
+        }),
+        Err(ref err) => json!({
+            s!("file"): file.display().to_string(),
+            s!("error"): err.clone()
+        })
+    }).collect());
+    println!("{}", json);
+}
+
+pub fn validate_pacts(matches: &ArgMatches) -> Result<(), i32> {
+    let mut files: Vec<PathBuf> = matches.values_of("file")
+        .map(|values| values.map(PathBuf::from).collect())
+        .unwrap_or_default();
+    for dir in matches.values_of("dir").map(|values| values.collect::<Vec<&str>>()).unwrap_or_default() {
+        match pact_files_in_dir(Path::new(dir)) {
+            Ok(dir_files) => files.extend(dir_files),
+            Err(err) => {
+                eprintln!("ERROR: Could not load pacts from directory '{}' - {}", dir, err);
+                return Err(2)
+            }
+        }
+    }
+
+    let results: Vec<(PathBuf, Result<Vec<ValidationFinding>, String>)> = files.iter()
+        .map(|file| (file.clone(), validate_pact_file(file).map_err(|err| err.to_string())))
+        .collect();
+    match matches.value_of("output") {
+        Some("json") => display_json(&results),
+        _ => display_text(&results)
+    }
+
+    match exit_code(&results, fail_on_severity(matches.value_of("fail-on"))) {
+        0 => Ok(()),
+        code => Err(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+
+    fn finding(severity: Severity) -> ValidationFinding {
+        ValidationFinding {
+            severity,
+            check: pact_matching::validation::ValidationCheck::MissingContentType,
+            path: s!("$.interactions[0].response.headers"),
+            message: s!("missing content type")
+        }
+    }
+
+    #[test]
+    fn exit_code_is_based_on_the_fail_on_severity() {
+        let results = vec![(PathBuf::from("a.json"), Ok(vec![finding(Severity::Warning)])),
+            (PathBuf::from("b.json"), Ok(vec![]))];
+        expect!(exit_code(&results, Severity::Error)).to(be_equal_to(0));
+        expect!(exit_code(&results, Severity::Warning)).to(be_equal_to(1));
+        expect!(exit_code(&results, Severity::Info)).to(be_equal_to(1));
+    }
+
+    #[test]
+    fn exit_code_is_two_if_a_pact_could_not_be_loaded() {
+        let results = vec![(PathBuf::from("a.json"), Ok(vec![finding(Severity::Error)])),
+            (PathBuf::from("b.json"), Err(s!("No such file or directory")))];
+        expect!(exit_code(&results, Severity::Error)).to(be_equal_to(2));
+    }
+}
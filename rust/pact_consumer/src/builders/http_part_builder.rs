@@ -110,7 +110,7 @@ pub trait HttpPartBuilder {
         let body = body.into();
         {
             let (body_ref, _) = self.body_and_matching_rules_mut();
-            *body_ref = OptionalBody::Present(body.into());
+            *body_ref = OptionalBody::Present(body.into(), None);
         }
         self
     }
@@ -135,7 +135,30 @@ pub trait HttpPartBuilder {
         let body = body.into();
         {
             let (body_ref, rules) = self.body_and_matching_rules_mut();
-            *body_ref = OptionalBody::Present(body.to_example().to_string().into());
+            *body_ref = OptionalBody::Present(body.to_example().to_string().into(), None);
+            body.extract_matching_rules("$", rules.add_category("body"));
+        }
+        self
+    }
+
+    /// Specify a body using an `XmlElement` pattern for the root element. The
+    /// example body is generated from the pattern, and any matching rules are
+    /// extracted from it.
+    ///
+    /// ```
+    /// use pact_consumer::prelude::*;
+    /// use pact_consumer::builders::RequestBuilder;
+    ///
+    /// RequestBuilder::default()
+    ///     .content_type("application/xml")
+    ///     .xml_body(XmlElement::new("items")
+    ///         .child(EachLikeElement::new(XmlElement::new("item").text("apple"))));
+    /// ```
+    fn xml_body(&mut self, body: XmlElement) -> &mut Self {
+        {
+            let (body_ref, rules) = self.body_and_matching_rules_mut();
+            *body_ref = OptionalBody::Present(
+                format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}", body.to_example()).into(), None);
             body.extract_matching_rules("$", rules.add_category("body"));
         }
         self
@@ -202,3 +225,27 @@ fn json_body_pattern() {
     assert_requests_match!(good, pattern);
     assert_requests_do_not_match!(bad, pattern);
 }
+
+#[test]
+fn xml_body_pattern() {
+    let pattern = PactBuilder::new("C", "P")
+        .interaction("I", |i| {
+            i.request.content_type("application/xml").xml_body(XmlElement::new("items")
+                .child(EachLikeElement::new(XmlElement::new("item").text("apple")).with_max_len(3)));
+        })
+        .build();
+    let good = PactBuilder::new("C", "P")
+        .interaction("I", |i| {
+            i.request.content_type("application/xml")
+                .body("<items><item>pear</item><item>plum</item></items>");
+        })
+        .build();
+    let bad = PactBuilder::new("C", "P")
+        .interaction("I", |i| {
+            i.request.content_type("application/xml")
+                .body("<items><item/><item/><item/><item/></items>");
+        })
+        .build();
+    assert_requests_match!(good, pattern);
+    assert_requests_do_not_match!(bad, pattern);
+}
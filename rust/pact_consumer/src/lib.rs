@@ -215,6 +215,8 @@
 extern crate env_logger;
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate log;
 #[allow(unused_imports)] #[macro_use]
 extern crate maplit;
 #[allow(unused_imports)] #[macro_use]
@@ -254,6 +256,8 @@ pub mod prelude {
     pub use builders::{HttpPartBuilder, PactBuilder};
     pub use patterns::{Pattern, JsonPattern, StringPattern};
     pub use patterns::{EachLike, Like, Term};
+    pub use patterns::{XmlElement, EachLikeElement};
     pub use mock_server::{StartMockServer, ValidatingMockServer};
+    pub use pact_matching::models::MergeStrategy;
     pub use util::strip_null_fields;
 }
@@ -86,8 +86,14 @@ impl ValidatingMockServer {
     fn with_mode_and_future_consumer<F>(pact: Pact, mode: Mode, future_consumer: F) -> ValidatingMockServer
         where F: FnOnce(Box<dyn futures::Future<Item = (), Error = ()> + 'static + Send>)
     {
-        let (mock_server, future) = mock_server::MockServer::new("".into(), pact, ([0, 0, 0, 0], 0 as u16).into())
+        let (mut mock_server, future) = mock_server::MockServer::new("".into(), pact, ([0, 0, 0, 0], 0 as u16).into())
             .expect("error starting mock server");
+        if let Ok(strategy) = env::var("PACT_MERGE_STRATEGY") {
+            match strategy.parse() {
+                Ok(strategy) => mock_server.merge_strategy = strategy,
+                Err(err) => warn!("Ignoring the PACT_MERGE_STRATEGY environment variable, using the default merge strategy - {}", err)
+            }
+        }
 
         future_consumer(Box::new(future));
 
@@ -101,6 +107,13 @@ impl ValidatingMockServer {
         }
     }
 
+    /// Sets the strategy used to resolve conflicts with the interactions of an existing pact file
+    /// when the pact is written out. This overrides the `PACT_MERGE_STRATEGY` environment variable.
+    pub fn with_merge_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.mock_server.merge_strategy = strategy;
+        self
+    }
+
     /// The URL of our mock server. You can make normal HTTP requests using this
     /// as the base URL.
     pub fn url(&self) -> &Url {
@@ -10,10 +10,12 @@ mod json_pattern;
 #[macro_use]
 mod special_rules;
 mod string_pattern;
+mod xml_pattern;
 
 pub use self::json_pattern::*;
 pub use self::special_rules::*;
 pub use self::string_pattern::*;
+pub use self::xml_pattern::*;
 
 /// Abstract interface to types which can:
 ///
@@ -24,9 +26,8 @@ pub use self::string_pattern::*;
 /// For an overview of how the matching rules work, and what kinds of special
 /// matching rules exist, see the [`pact_matching` documentation][spec].
 ///
-/// The current version of this API will only work for `JsonPattern` and
-/// `serde_json::Value`. Extending this scheme to work for XML would require
-/// parameterizing the input and output types, and possibly other changes.
+/// This API is implemented for `JsonPattern` (matching `serde_json::Value`),
+/// `StringPattern` and `XmlElement` (both matching `String`).
 ///
 /// [spec]: https://docs.rs/pact_matching/0.2.2/pact_matching/
 pub trait Pattern: Debug {
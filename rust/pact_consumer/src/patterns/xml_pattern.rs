@@ -0,0 +1,230 @@
+//! Patterns for building XML bodies.
+
+use pact_matching::models::matchingrules::{Category, MatchingRule, RuleLogic};
+use std::iter::repeat;
+
+use super::Pattern;
+use super::string_pattern::StringPattern;
+use util::obj_key_for_path;
+
+/// A pattern for an XML element, which can be used to generate an example XML
+/// document and the matching rules for it.
+///
+/// ```
+/// use pact_consumer::prelude::*;
+///
+/// let body = XmlElement::new("items")
+///     .attribute("type", "fruit")
+///     .child(EachLikeElement::new(XmlElement::new("item").text("apple")).with_min_len(2));
+/// assert_eq!(body.to_example(),
+///     "<items type=\"fruit\"><item>apple</item><item>apple</item></items>");
+/// ```
+#[derive(Debug)]
+pub struct XmlElement {
+    name: String,
+    attributes: Vec<(String, StringPattern)>,
+    children: Vec<XmlNode>,
+}
+
+/// A node which can be nested inside an `XmlElement`.
+#[derive(Debug)]
+pub enum XmlNode {
+    /// A child element.
+    Element(XmlElement),
+    /// Repeated child elements that all look like an example element.
+    EachLike(EachLikeElement),
+    /// Text content.
+    Text(StringPattern),
+}
+
+impl XmlElement {
+    /// Create an element with the given name. Namespaced elements can be
+    /// created by using the qualified name (i.e. `ns:name`) and declaring the
+    /// namespace with an `xmlns:ns` attribute.
+    pub fn new<N: Into<String>>(name: N) -> XmlElement {
+        XmlElement {
+            name: name.into(),
+            attributes: vec![],
+            children: vec![],
+        }
+    }
+
+    /// Add an attribute to this element.
+    pub fn attribute<N, V>(mut self, name: N, value: V) -> XmlElement
+    where
+        N: Into<String>,
+        V: Into<StringPattern>,
+    {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
+    /// Add a child node to this element.
+    pub fn child<C: Into<XmlNode>>(mut self, child: C) -> XmlElement {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Add text content to this element.
+    pub fn text<T: Into<StringPattern>>(self, text: T) -> XmlElement {
+        self.child(XmlNode::Text(text.into()))
+    }
+}
+
+impl Pattern for XmlElement {
+    type Matches = String;
+
+    fn to_example(&self) -> String {
+        let mut xml = format!("<{}", self.name);
+        for &(ref name, ref value) in &self.attributes {
+            xml.push_str(&format!(" {}=\"{}\"", name, escape(&value.to_example())));
+        }
+        if self.children.is_empty() {
+            xml.push_str("/>");
+        } else {
+            xml.push('>');
+            for child in &self.children {
+                xml.push_str(&child.to_example());
+            }
+            xml.push_str(&format!("</{}>", self.name));
+        }
+        xml
+    }
+
+    /// Extracts the matching rules for this element, where `path` is the path
+    /// to the element (i.e. `$` for the root element, or `$.parent[0]` for the
+    /// first child of `parent`).
+    fn extract_matching_rules(&self, path: &str, rules_out: &mut Category) {
+        let element_path = format!("{}{}", path, obj_key_for_path(&self.name));
+        for &(ref name, ref value) in &self.attributes {
+            let attribute_path = format!("{}{}", element_path, obj_key_for_path(&format!("@{}", name)));
+            value.extract_matching_rules(&attribute_path, rules_out);
+        }
+        let mut index = 0;
+        for child in &self.children {
+            match *child {
+                XmlNode::Element(ref element) => {
+                    element.extract_matching_rules(&format!("{}[{}]", element_path, index), rules_out);
+                    index += 1;
+                },
+                XmlNode::EachLike(ref each_like) => {
+                    each_like.extract_matching_rules(&element_path, rules_out);
+                    index += each_like.example_len();
+                },
+                XmlNode::Text(ref text) => {
+                    text.extract_matching_rules(&format!("{}['#text']", element_path), rules_out);
+                }
+            }
+        }
+    }
+}
+
+impl XmlNode {
+    fn to_example(&self) -> String {
+        match *self {
+            XmlNode::Element(ref element) => element.to_example(),
+            XmlNode::EachLike(ref each_like) => each_like.to_example(),
+            XmlNode::Text(ref text) => escape(&text.to_example()),
+        }
+    }
+}
+
+impl From<XmlElement> for XmlNode {
+    fn from(element: XmlElement) -> Self {
+        XmlNode::Element(element)
+    }
+}
+
+impl From<EachLikeElement> for XmlNode {
+    fn from(each_like: EachLikeElement) -> Self {
+        XmlNode::EachLike(each_like)
+    }
+}
+
+/// Match repeated sibling elements with the same name which all look like the
+/// example element. This is the XML equivalent of `EachLike`.
+#[derive(Debug)]
+pub struct EachLikeElement {
+    example_element: XmlElement,
+    min_len: usize,
+    max_len: Option<usize>,
+}
+
+impl EachLikeElement {
+    /// Match sibling elements like `example_element`.
+    pub fn new(example_element: XmlElement) -> EachLikeElement {
+        EachLikeElement {
+            example_element,
+            min_len: 1,
+            max_len: None,
+        }
+    }
+
+    /// Use this after `new` to set a minimum number of matching elements.
+    pub fn with_min_len(mut self, min_len: usize) -> EachLikeElement {
+        self.min_len = min_len;
+        self
+    }
+
+    /// Use this after `new` to set a maximum number of matching elements.
+    pub fn with_max_len(mut self, max_len: usize) -> EachLikeElement {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// The number of elements in the generated example.
+    fn example_len(&self) -> usize {
+        self.min_len.max(1)
+    }
+
+    fn to_example(&self) -> String {
+        repeat(self.example_element.to_example()).take(self.example_len()).collect()
+    }
+
+    /// Extracts the matching rules, where `parent_path` is the path to the
+    /// parent element.
+    fn extract_matching_rules(&self, parent_path: &str, rules_out: &mut Category) {
+        let name_key = obj_key_for_path(&self.example_element.name);
+        let rule = match self.max_len {
+            Some(max_len) => MatchingRule::MinMaxType(self.min_len, max_len),
+            None => MatchingRule::MinType(self.min_len)
+        };
+        rules_out.add_rule(&format!("{}{}", parent_path, name_key), rule, &RuleLogic::And);
+        rules_out.add_rule(&format!("{}[*]{}.*", parent_path, name_key), MatchingRule::Type,
+            &RuleLogic::And);
+        self.example_element.extract_matching_rules(&format!("{}[*]", parent_path), rules_out);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[test]
+fn xml_element_is_pattern() {
+    use super::special_rules::Term;
+    use regex::Regex;
+
+    let pattern = XmlElement::new("ns:items")
+        .attribute("xmlns:ns", "urn:items")
+        .attribute("id", Term::new(Regex::new("^[0-9]+$").unwrap(), "10"))
+        .child(XmlElement::new("ns:count").text("2 & more"))
+        .child(EachLikeElement::new(XmlElement::new("ns:item")
+            .attribute("name", Term::new(Regex::new("^[a-z]+$").unwrap(), "apple")))
+            .with_min_len(2)
+            .with_max_len(5));
+
+    assert_eq!(pattern.to_example(), "<ns:items xmlns:ns=\"urn:items\" id=\"10\">\
+        <ns:count>2 &amp; more</ns:count>\
+        <ns:item name=\"apple\"/><ns:item name=\"apple\"/></ns:items>");
+
+    let mut rules = Category::default("body");
+    pattern.extract_matching_rules("$", &mut rules);
+    let expected_rules = hashmap!(
+        s!("$.body['ns:items']['@id']") => json!({ "match": "regex", "regex": "^[0-9]+$" }),
+        s!("$.body['ns:items']['ns:item']") => json!({ "match": "type", "min": 2, "max": 5 }),
+        s!("$.body['ns:items'][*]['ns:item'].*") => json!({ "match": "type" }),
+        s!("$.body['ns:items'][*]['ns:item']['@name']") => json!({ "match": "regex", "regex": "^[a-z]+$" })
+    );
+    assert_eq!(rules.to_v2_json(), expected_rules);
+}
@@ -0,0 +1,324 @@
+//! The `conversion` module provides functions to convert a pact between the versions of the pact
+//! specification (i.e. to upgrade V2 pacts to V3, or to downgrade V3 pacts for a verifier that
+//! only supports V2).
+//!
+//! Pacts are always loaded into the same model regardless of their specification version, so
+//! upgrading a pact does not lose any information. When downgrading, any features that can not be
+//! represented in the target version are dropped, and a warning is returned for each one:
+//!
+//! * Generators are only supported by V3 pacts.
+//! * V2 pacts only support a single provider state per interaction, without any parameters.
+//! * V2 pacts only support a single matcher per item, and only define the `regex` and `type`
+//!   matchers (other matchers are written, but older verifiers may not support them).
+//! * V1 and V1.1 pacts do not support matching rules.
+
+use std::fmt::{Display, Formatter};
+use serde_json::Value;
+use models::{Pact, Interaction, PactSpecification};
+use models::matchingrules::{MatchingRules, MatchingRule};
+use models::generators::Generators;
+use models::parse_errors::child_path;
+
+/// A feature of the pact that could not be represented in the target specification version
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionWarning {
+    /// Description of the interaction the feature was found in
+    pub interaction: String,
+    /// Path to the feature in the interaction (i.e. `request.generators`)
+    pub path: String,
+    /// Description of what was changed or dropped
+    pub message: String
+}
+
+impl ConversionWarning {
+    fn new(interaction: &Interaction, path: String, message: String) -> ConversionWarning {
+        ConversionWarning {
+            interaction: interaction.description.clone(),
+            path,
+            message
+        }
+    }
+
+    /// Converts this warning to a `Value` struct
+    pub fn to_json(&self) -> Value {
+        json!({
+            s!("interaction"): self.interaction,
+            s!("path"): self.path,
+            s!("message"): self.message
+        })
+    }
+}
+
+impl Display for ConversionWarning {
+    fn fmt(&self, f: &mut Formatter) -> ::std::fmt::Result {
+        write!(f, "'{}' {}: {}", self.interaction, self.path, self.message)
+    }
+}
+
+/// Result of converting a pact to a specification version
+#[derive(Debug, Clone)]
+pub struct PactConversion {
+    /// The converted pact, with any unsupported features removed
+    pub pact: Pact,
+    /// Specification version the pact was converted to
+    pub spec_version: PactSpecification,
+    /// Warnings for all the features that could not be represented in the specification version
+    pub warnings: Vec<ConversionWarning>
+}
+
+impl PactConversion {
+    /// Returns the converted pact in the JSON format of the target specification version
+    pub fn to_json(&self) -> Value {
+        self.pact.to_canonical_json(self.spec_version.clone())
+    }
+
+    /// Returns the converted pact as a pretty-printed JSON string
+    pub fn to_string(&self) -> String {
+        self.pact.to_canonical_string(self.spec_version.clone())
+    }
+}
+
+fn check_matching_rules(interaction: &Interaction, part: &str, rules: &MatchingRules,
+    spec_version: &PactSpecification, warnings: &mut Vec<ConversionWarning>) {
+    let path = format!("{}.matchingRules", part);
+    match spec_version {
+        &PactSpecification::V1 | &PactSpecification::V1_1 => if rules.is_not_empty() {
+            warnings.push(ConversionWarning::new(interaction, path, format!(
+                "Matching rules are not supported by {} pacts and have been removed", spec_version.to_string())));
+        },
+        _ => {
+            let mut categories = rules.categories().into_iter().collect::<Vec<String>>();
+            categories.sort();
+            for category in categories {
+                let rules = rules.rules_for_category(&category).unwrap();
+                let mut keys = rules.rules.keys().cloned().collect::<Vec<String>>();
+                keys.sort();
+                for key in keys {
+                    let rulelist = &rules.rules[&key];
+                    let category_path = child_path(&path, &category);
+                    let rule_path = if key.is_empty() { category_path } else { child_path(&category_path, &key) };
+                    if rulelist.rules.len() > 1 {
+                        warnings.push(ConversionWarning::new(interaction, rule_path.clone(), format!(
+                            "{} pacts only support one matcher per item, only {:?} has been kept",
+                            spec_version.to_string(), rulelist.rules[0])));
+                    }
+                    match rulelist.rules.first() {
+                        Some(&MatchingRule::Regex(_)) | Some(&MatchingRule::Type) | Some(&MatchingRule::MinType(_)) |
+                        Some(&MatchingRule::MaxType(_)) | Some(&MatchingRule::MinMaxType(_, _)) | None => (),
+                        Some(rule) => warnings.push(ConversionWarning::new(interaction, rule_path, format!(
+                            "{:?} matcher is not defined by the {} specification, and may not be supported by {} verifiers",
+                            rule, spec_version.to_string(), spec_version.to_string())))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_generators(interaction: &Interaction, part: &str, generators: &Generators,
+    spec_version: &PactSpecification, warnings: &mut Vec<ConversionWarning>) {
+    if generators.is_not_empty() {
+        warnings.push(ConversionWarning::new(interaction, format!("{}.generators", part), format!(
+            "Generators are not supported by {} pacts and have been removed", spec_version.to_string())));
+    }
+}
+
+fn convert_interaction(interaction: &Interaction, spec_version: &PactSpecification,
+    warnings: &mut Vec<ConversionWarning>) -> Interaction {
+    let mut converted = interaction.clone();
+    if *spec_version == PactSpecification::V3 {
+        return converted;
+    }
+
+    if interaction.provider_states.len() > 1 {
+        warnings.push(ConversionWarning::new(interaction, s!("providerStates"), format!(
+            "{} pacts only support a single provider state, only '{}' has been kept",
+            spec_version.to_string(), interaction.provider_states[0].name)));
+        converted.provider_states.truncate(1);
+    }
+    if interaction.provider_states.iter().take(1).any(|state| !state.params.is_empty()) {
+        warnings.push(ConversionWarning::new(interaction, s!("providerStates[0].params"), format!(
+            "Provider state parameters are not supported by {} pacts and have been removed", spec_version.to_string())));
+        for state in converted.provider_states.iter_mut() {
+            state.params.clear();
+        }
+    }
+
+    check_matching_rules(interaction, "request", &interaction.request.matching_rules, spec_version, warnings);
+    check_matching_rules(interaction, "response", &interaction.response.matching_rules, spec_version, warnings);
+    check_generators(interaction, "request", &interaction.request.generators, spec_version, warnings);
+    check_generators(interaction, "response", &interaction.response.generators, spec_version, warnings);
+    if *spec_version != PactSpecification::V2 {
+        converted.request.matching_rules = MatchingRules::default();
+        converted.response.matching_rules = MatchingRules::default();
+    }
+    converted.request.generators = Generators::default();
+    converted.response.generators = Generators::default();
+
+    converted
+}
+
+/// Converts the pact to the given specification version. Returns an error if the pact can not be
+/// written in the specification version.
+pub fn convert_pact(pact: &Pact, spec_version: &PactSpecification) -> Result<PactConversion, String> {
+    match spec_version {
+        &PactSpecification::V1 | &PactSpecification::V1_1 | &PactSpecification::V2 |
+        &PactSpecification::V3 => {
+            let mut warnings = vec![];
+            let interactions = pact.interactions.iter()
+                .map(|interaction| convert_interaction(interaction, spec_version, &mut warnings))
+                .collect();
+            Ok(PactConversion {
+                pact: Pact {
+                    interactions,
+                    specification_version: spec_version.clone(),
+                    .. pact.clone()
+                },
+                spec_version: spec_version.clone(),
+                warnings
+            })
+        },
+        _ => Err(format!("Converting pacts to the {} specification is not supported", spec_version.to_string()))
+    }
+}
+
+/// Parses a specification version, as used on the command line (i.e. `v2`, `3`, `1.1` or `3.0.0`).
+pub fn parse_spec_version(version: &str) -> Option<PactSpecification> {
+    let version = version.trim().trim_start_matches(|ch| ch == 'v' || ch == 'V');
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    match (parts.next(), parts.next()) {
+        (Some(Some(1)), None) | (Some(Some(1)), Some(Some(0))) => Some(PactSpecification::V1),
+        (Some(Some(1)), Some(Some(1))) => Some(PactSpecification::V1_1),
+        (Some(Some(2)), _) => Some(PactSpecification::V2),
+        (Some(Some(3)), _) => Some(PactSpecification::V3),
+        (Some(Some(4)), _) => Some(PactSpecification::V4),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use models::{Request, Response};
+    use models::provider_states::ProviderState;
+    use models::matchingrules::*;
+    use models::generators::Generator;
+
+    fn v3_pact() -> Pact {
+        Pact {
+            interactions: vec![
+                Interaction {
+                    description: s!("get user"),
+                    provider_states: vec![
+                        ProviderState { name: s!("user exists"), params: hashmap!{ s!("id") => json!(100) } },
+                        ProviderState { name: s!("user is active"), params: hashmap!{} }
+                    ],
+                    request: Request {
+                        path: s!("/users/100"),
+                        matching_rules: matchingrules!{
+                            "path" => { "" => [ MatchingRule::Regex(s!("/users/\\d+")) ] }
+                        },
+                        generators: generators!{
+                            "PATH" => { "" => Generator::RandomInt(1, 100) }
+                        },
+                        .. Request::default()
+                    },
+                    response: Response {
+                        matching_rules: matchingrules!{
+                            "body" => {
+                                "$.id" => [ MatchingRule::Integer ],
+                                "$.name" => [ MatchingRule::Type, MatchingRule::Regex(s!("\\w+")) ]
+                            }
+                        },
+                        .. Response::default()
+                    },
+                    .. Interaction::default()
+                }
+            ],
+            .. Pact::default()
+        }
+    }
+
+    #[test]
+    fn converting_to_v3_keeps_everything() {
+        let pact = v3_pact();
+        let result = convert_pact(&pact, &PactSpecification::V3).unwrap();
+        expect!(result.warnings.iter()).to(be_empty());
+        expect!(result.pact.interactions.clone()).to(be_equal_to(pact.interactions.clone()));
+        expect!(result.to_json()["metadata"]["pactSpecification"]["version"].clone()).to(be_equal_to(json!("3.0.0")));
+    }
+
+    #[test]
+    fn converting_to_v2_drops_the_features_that_are_not_supported() {
+        let result = convert_pact(&v3_pact(), &PactSpecification::V2).unwrap();
+        let messages = result.warnings.iter().map(|w| w.to_string()).collect::<Vec<String>>();
+        expect!(messages).to(be_equal_to(vec![
+            s!("'get user' providerStates: V2 pacts only support a single provider state, only 'user exists' has been kept"),
+            s!("'get user' providerStates[0].params: Provider state parameters are not supported by V2 pacts and have been removed"),
+            s!("'get user' response.matchingRules.body['$.id']: Integer matcher is not defined by the V2 specification, and may not be supported by V2 verifiers"),
+            s!("'get user' response.matchingRules.body['$.name']: V2 pacts only support one matcher per item, only Type has been kept"),
+            s!("'get user' request.generators: Generators are not supported by V2 pacts and have been removed")
+        ]));
+
+        let json = result.to_json();
+        let interaction = &json["interactions"][0];
+        expect!(interaction["providerState"].clone()).to(be_equal_to(json!("user exists")));
+        expect!(interaction["request"].get("generators")).to(be_none());
+        expect!(interaction["request"]["matchingRules"].clone()).to(be_equal_to(json!({
+            "$.path": { "match": "regex", "regex": "/users/\\d+" }
+        })));
+        expect!(json["metadata"]["pactSpecification"]["version"].clone()).to(be_equal_to(json!("2.0.0")));
+    }
+
+    #[test]
+    fn converting_a_v2_pact_to_v3_and_back_is_lossless() {
+        let pact = Pact {
+            interactions: vec![
+                Interaction {
+                    response: Response {
+                        matching_rules: matchingrules!{
+                            "body" => { "$.id" => [ MatchingRule::Regex(s!("\\d+")) ] },
+                            "header" => { "Content-Type" => [ MatchingRule::Regex(s!("application/json.*")) ] }
+                        },
+                        .. Response::default()
+                    },
+                    .. Interaction::default()
+                }
+            ],
+            specification_version: PactSpecification::V2,
+            .. Pact::default()
+        };
+        let v3 = convert_pact(&pact, &PactSpecification::V3).unwrap();
+        let reloaded = Pact::from_json(&s!("v3"), &v3.to_json());
+        expect!(reloaded.specification_version.clone()).to(be_equal_to(PactSpecification::V3));
+        let v2 = convert_pact(&reloaded, &PactSpecification::V2).unwrap();
+        expect!(v2.warnings.iter()).to(be_empty());
+        expect!(Pact::from_json(&s!("v2"), &v2.to_json()).interactions).to(be_equal_to(pact.interactions));
+    }
+
+    #[test]
+    fn converting_to_v1_removes_the_matching_rules() {
+        let result = convert_pact(&v3_pact(), &PactSpecification::V1_1).unwrap();
+        expect!(result.warnings.iter().any(|w| w.path == "request.matchingRules")).to(be_true());
+        expect!(result.warnings.iter().any(|w| w.path == "response.matchingRules")).to(be_true());
+        expect!(result.to_json()["interactions"][0]["response"].get("matchingRules")).to(be_none());
+    }
+
+    #[test]
+    fn converting_to_an_unsupported_version_is_an_error() {
+        expect!(convert_pact(&v3_pact(), &PactSpecification::V4)).to(be_err());
+        expect!(convert_pact(&v3_pact(), &PactSpecification::Unknown)).to(be_err());
+    }
+
+    #[test]
+    fn parse_spec_version_test() {
+        expect!(parse_spec_version("v1")).to(be_some().value(PactSpecification::V1));
+        expect!(parse_spec_version("1.1")).to(be_some().value(PactSpecification::V1_1));
+        expect!(parse_spec_version("V2")).to(be_some().value(PactSpecification::V2));
+        expect!(parse_spec_version("3.0.0")).to(be_some().value(PactSpecification::V3));
+        expect!(parse_spec_version("v4")).to(be_some().value(PactSpecification::V4));
+        expect!(parse_spec_version("five")).to(be_none());
+        expect!(parse_spec_version("1.2")).to(be_none());
+    }
+}
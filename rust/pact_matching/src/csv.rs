@@ -0,0 +1,357 @@
+//! The `csv` module provides functions to compare CSV bodies. The first row of a CSV body is
+//! treated as the header row, and the remaining rows are compared by column name.
+
+use super::{Mismatch, MismatchDetails, MismatchKind};
+use super::{DiffConfig, MatchingConfig};
+use csv_crate::ReaderBuilder;
+use itertools::Itertools;
+use models::matchingrules::*;
+use matchers::*;
+use serde_json::Value;
+use json::{parse_error_details, rule_mismatch_details};
+
+/// A CSV document split into its header row and the data rows
+#[derive(Debug, Clone, PartialEq)]
+struct CsvBody {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>
+}
+
+fn parse_csv(body: &Vec<u8>) -> Result<CsvBody, String> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(body.as_slice());
+    let mut records = vec![];
+    for record in reader.records() {
+        match record {
+            Ok(record) => records.push(record.iter().map(|field| s!(field)).collect::<Vec<String>>()),
+            Err(err) => return Err(format!("{}", err))
+        }
+    }
+    let mut records = records.into_iter();
+    Ok(CsvBody {
+        headers: records.next().unwrap_or_default(),
+        rows: records.collect()
+    })
+}
+
+/// Matches the expected CSV to the actual, and populates the mismatches vector with any differences
+pub fn match_csv(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    match_csv_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the expected CSV to the actual, and populates the mismatches vector with any differences.
+/// Stops comparing the bodies once the mismatch limit of the matching configuration is reached.
+pub fn match_csv_with_config(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let expected_result = parse_csv(expected);
+    let actual_result = parse_csv(actual);
+
+    if expected_result.is_err() || actual_result.is_err() {
+        match expected_result {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
+                    actual: Some(actual.clone().into()),
+                    mismatch: format!("Failed to parse the expected body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+        match actual_result {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
+                    actual: Some(actual.clone().into()),
+                    mismatch: format!("Failed to parse the actual body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+    } else {
+        let expected_body = expected_result.unwrap();
+        let actual_body = actual_result.unwrap();
+        compare_headers(&expected_body, &actual_body, config.clone(), mismatches);
+        compare_rows(&expected_body, &actual_body, config, matching_config, mismatches, matchers);
+    }
+}
+
+impl Matches<Vec<Vec<String>>> for Vec<Vec<String>> {
+    fn matches(&self, actual: &Vec<Vec<String>>, matcher: &MatchingRule) -> Result<(), String> {
+        let result = match *matcher {
+          MatchingRule::MinType(min) => if actual.len() < min {
+             Err(format!("Expected at least {} row(s) but received {}", min, actual.len()))
+          } else {
+             Ok(())
+          },
+          MatchingRule::MaxType(max) => if actual.len() > max {
+             Err(format!("Expected at most {} row(s) but received {}", max, actual.len()))
+          } else {
+             Ok(())
+          },
+          MatchingRule::MinMaxType(min, max) => if actual.len() < min {
+            Err(format!("Expected at least {} row(s) but received {}", min, actual.len()))
+          } else if actual.len() > max {
+            Err(format!("Expected at most {} row(s) but received {}", max, actual.len()))
+          } else {
+            Ok(())
+          },
+          // only the type rules constrain the number of rows
+          _ => Ok(())
+        };
+        debug!("Comparing '{:?}' to '{:?}' using {:?} -> {:?}", self, actual, matcher, result);
+        result
+    }
+}
+
+fn compare_headers(expected: &CsvBody, actual: &CsvBody, config: DiffConfig, mismatches: &mut Vec<super::Mismatch>) {
+    let missing = expected.headers.iter().filter(|column| !actual.headers.contains(column)).collect::<Vec<&String>>();
+    let unexpected = actual.headers.iter().filter(|column| !expected.headers.contains(column)).collect::<Vec<&String>>();
+    for column in missing {
+        mismatches.push(Mismatch::BodyMismatch { path: s!("$"),
+            expected: Some(expected.headers.join(",").into()),
+            actual: Some(actual.headers.join(",").into()),
+            mismatch: format!("Expected column '{}' but was missing", column),
+            details: Some(MismatchDetails::new(MismatchKind::MissingValue, &[], json!(column), Value::Null))});
+    }
+    if config == DiffConfig::NoUnexpectedKeys {
+        for column in unexpected {
+            mismatches.push(Mismatch::BodyMismatch { path: s!("$"),
+                expected: Some(expected.headers.join(",").into()),
+                actual: Some(actual.headers.join(",").into()),
+                mismatch: format!("Unexpected column '{}' received", column),
+                details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, &[], Value::Null, json!(column)))});
+        }
+    }
+}
+
+/// If there is a type rule defined on the root path, the first expected row is used as a template
+/// for all the actual rows
+fn is_rows_like_rule(rules: &RuleList) -> bool {
+    rules.rules.iter().any(|rule| match *rule {
+        MatchingRule::Type | MatchingRule::MinType(_) | MatchingRule::MaxType(_) |
+        MatchingRule::MinMaxType(_, _) => true,
+        _ => false
+    })
+}
+
+fn compare_rows(expected: &CsvBody, actual: &CsvBody, config: DiffConfig, matching_config: &MatchingConfig,
+    mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let path = vec![s!("$")];
+    let rows_like = matchers.resolve_body_matchers_for_exact_path(&path)
+        .map(|rules| is_rows_like_rule(&rules)).unwrap_or(false);
+    if rows_like && !expected.rows.is_empty() {
+        let template = expected.rows[0].clone();
+        let matcher_result = match_values_with_rules("body", &path, matchers, &expected.rows, &actual.rows);
+        debug!("Comparing {} row(s) to {} row(s) at path '$' -> {:?}", expected.rows.len(), actual.rows.len(), matcher_result);
+        match matcher_result {
+            Err(failures) => {
+              for (rule, message) in failures {
+                mismatches.push(Mismatch::BodyMismatch {
+                  path: s!("$"),
+                  expected: Some(format!("{} row(s)", expected.rows.len()).into()),
+                  actual: Some(format!("{} row(s)", actual.rows.len()).into()),
+                  mismatch: message.clone(),
+                  details: Some(rule_mismatch_details(rule, &path, &json!(expected.rows), &json!(actual.rows)))
+                })
+              }
+            },
+            Ok(_) => ()
+        }
+        for (i, row) in actual.rows.iter().enumerate() {
+            if matching_config.limit_reached(mismatches.len()) {
+                break;
+            }
+            compare_row(i, &expected.headers, &template, &actual.headers, row, mismatches, matchers);
+        }
+    } else {
+        if expected.rows.len() > actual.rows.len() ||
+            (config == DiffConfig::NoUnexpectedKeys && expected.rows.len() != actual.rows.len()) {
+            mismatches.push(Mismatch::BodyMismatch { path: s!("$"),
+                expected: Some(format!("{} row(s)", expected.rows.len()).into()),
+                actual: Some(format!("{} row(s)", actual.rows.len()).into()),
+                mismatch: format!("Expected {} row(s) but received {} row(s)", expected.rows.len(), actual.rows.len()),
+                details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, &[], json!(expected.rows),
+                  json!(actual.rows)))});
+        }
+        for (i, (expected_row, actual_row)) in expected.rows.iter().zip(actual.rows.iter()).enumerate() {
+            if matching_config.limit_reached(mismatches.len()) {
+                break;
+            }
+            compare_row(i, &expected.headers, expected_row, &actual.headers, actual_row, mismatches, matchers);
+        }
+    }
+}
+
+/// Compares the values of a row by column name. Matching rules can address a column by either its
+/// name (`$[*].name`) or its index in the expected header row (`$[*][2]`).
+fn compare_row(row: usize, expected_headers: &Vec<String>, expected: &Vec<String>, actual_headers: &Vec<String>,
+    actual: &Vec<String>, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    for (index, column) in expected_headers.iter().enumerate() {
+        let actual_index = match actual_headers.iter().position(|header| header == column) {
+            Some(actual_index) => actual_index,
+            // missing columns are reported when comparing the headers
+            None => continue
+        };
+        let expected_value = expected.get(index).cloned().unwrap_or_default();
+        let path = vec![s!("$"), row.to_string(), column.clone()];
+        let index_path = vec![s!("$"), row.to_string(), index.to_string()];
+        match actual.get(actual_index) {
+            Some(actual_value) => {
+                let matcher_result = if matchers.matcher_is_defined("body", &path) {
+                  match_values_with_rules("body", &path, matchers, &expected_value, actual_value)
+                } else if matchers.matcher_is_defined("body", &index_path) {
+                  match_values_with_rules("body", &index_path, matchers, &expected_value, actual_value)
+                } else {
+                  expected_value.matches(actual_value, &MatchingRule::Equality)
+                    .map_err(|err| vec![(Some(MatchingRule::Equality), err)])
+                };
+                debug!("Comparing '{}' to '{}' at path '{}' -> {:?}", expected_value, actual_value,
+                    path.iter().join("."), matcher_result);
+                match matcher_result {
+                    Err(failures) => {
+                      for (rule, message) in failures {
+                        mismatches.push(Mismatch::BodyMismatch {
+                          path: path.iter().join("."),
+                          expected: Some(expected_value.clone().into()),
+                          actual: Some(actual_value.clone().into()),
+                          mismatch: format!("Row {}, column '{}': {}", row, column, message),
+                          details: Some(rule_mismatch_details(rule, &path, &json!(expected_value), &json!(actual_value)))
+                        })
+                      }
+                    },
+                    Ok(_) => ()
+                }
+            },
+            None => mismatches.push(Mismatch::BodyMismatch {
+                path: path.iter().join("."),
+                expected: Some(expected_value.clone().into()),
+                actual: None,
+                mismatch: format!("Row {}, column '{}': Expected '{}' but was missing", row, column, expected_value),
+                details: Some(MismatchDetails::new(MismatchKind::MissingValue, &path, json!(expected_value), Value::Null))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use Mismatch;
+    use DiffConfig;
+
+    fn mismatch_message(mismatch: &Mismatch) -> String {
+        match mismatch {
+            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m, .. } => m.clone(),
+            _ => s!("")
+        }
+    }
+
+    fn mismatch_path(mismatch: &Mismatch) -> String {
+        match mismatch {
+            &Mismatch::BodyMismatch{ path: ref p, expected: _, actual: _, mismatch: _, .. } => p.clone(),
+            _ => s!("")
+        }
+    }
+
+    #[test]
+    fn match_csv_handles_invalid_csv() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,\"Fred\n";
+        let actual = "id,name\n1,Fred\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_path(&mismatches[0])).to(be_equal_to(s!("$")));
+    }
+
+    #[test]
+    fn match_csv_with_equal_bodies() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n2,Mary\n";
+        let actual = "id,name\r\n1,Fred\r\n2,\"Mary\"\r\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn match_csv_compares_columns_by_name() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n";
+        let actual = "name,id,age\nFred,1,42\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+
+        mismatches.clear();
+        match_csv(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Unexpected column 'age' received")));
+    }
+
+    #[test]
+    fn match_csv_with_missing_column() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n";
+        let actual = "id\n1\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected column 'name' but was missing")));
+    }
+
+    #[test]
+    fn match_csv_reports_the_row_and_column_of_a_mismatch() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n2,Mary\n";
+        let actual = "id,name\n1,Fred\n2,Maria\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(&mismatches[0]).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.1.name"),
+            expected: Some("Mary".into()), actual: Some("Maria".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(
+            s!("Row 1, column 'name': Expected 'Mary' to be equal to 'Maria'")));
+    }
+
+    #[test]
+    fn match_csv_with_different_number_of_rows() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n2,Mary\n";
+        let actual = "id,name\n1,Fred\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected 2 row(s) but received 1 row(s)")));
+    }
+
+    #[test]
+    fn match_csv_with_matching_rules_by_column_name_and_index() {
+        let mut mismatches = vec![];
+        let expected = "id,name,created\n1,Fred,2019-01-01\n";
+        let actual = "id,name,created\n100,Frederick,2019-07-23\n";
+        match_csv(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$[*].id" => [ MatchingRule::Integer ],
+                "$[0].name" => [ MatchingRule::Regex(s!("^F[a-z]+$")) ],
+                "$[*][2]" => [ MatchingRule::Date(s!("yyyy-MM-dd")) ]
+            }
+        });
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn match_csv_with_rows_like_rule() {
+        let mut mismatches = vec![];
+        let expected = "id,name\n1,Fred\n";
+        let actual = "id,name\n10,Mary\n20,John\n30,X\n";
+        let matchers = matchingrules!{
+            "body" => {
+                "$" => [ MatchingRule::MinMaxType(1, 2) ],
+                "$[*].id" => [ MatchingRule::Integer ],
+                "$[*].name" => [ MatchingRule::Regex(s!("[A-Z][a-z]+")) ]
+            }
+        };
+        match_csv(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchers);
+        expect!(mismatches.iter()).to(have_count(2));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected at most 2 row(s) but received 3")));
+        expect!(mismatch_path(&mismatches[1])).to(be_equal_to(s!("$.2.name")));
+        expect!(mismatch_message(&mismatches[1])).to(be_equal_to(s!("Row 2, column 'name': Expected 'X' to match '[A-Z][a-z]+'")));
+    }
+}
@@ -0,0 +1,174 @@
+//! The `diff` module provides functions to display the differences between XML and text bodies
+//! as unified diffs. XML bodies are canonicalized first (pretty-printed, with the attributes of
+//! each element in a stable order), so only the differences that matter to the matching show up.
+//! Each diff is trimmed to the lines around the changes.
+
+use difference::{Changeset, Difference};
+use ansi_term::Colour::*;
+use models::xml_utils::parse_bytes;
+use xml::canonicalize_xml;
+
+/// Number of unchanged lines displayed before and after each change
+pub const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineChange {
+    Same,
+    Added,
+    Removed
+}
+
+fn diff_lines(expected: &str, actual: &str) -> Vec<(LineChange, String)> {
+    let changeset = Changeset::new(expected.trim_end_matches('\n'), actual.trim_end_matches('\n'), "\n");
+    changeset.diffs.iter().flat_map(|diff| {
+        let (change, text) = match diff {
+            &Difference::Same(ref text) => (LineChange::Same, text),
+            &Difference::Add(ref text) => (LineChange::Added, text),
+            &Difference::Rem(ref text) => (LineChange::Removed, text)
+        };
+        text.split('\n').map(|line| (change, line.to_string())).collect::<Vec<(LineChange, String)>>()
+    }).collect()
+}
+
+/// Start of the range for a hunk header. An empty range starts at the line before the hunk.
+fn range_start(lines_before: usize, count: usize) -> usize {
+    if count > 0 { lines_before + 1 } else { lines_before }
+}
+
+/// Returns a colorized unified diff of the expected and actual text. Lines only in the expected
+/// text are prefixed with `-` (in red), and lines only in the actual text with `+` (in green).
+/// Changes are grouped into hunks with `context` unchanged lines before and after them, so the
+/// diff is trimmed to the regions around the changes. Returns an empty string if there are no
+/// differences.
+pub fn unified_diff(expected: &str, actual: &str, context: usize) -> String {
+    let lines = diff_lines(expected, actual);
+    let changes = lines.iter().enumerate()
+        .filter(|&(_, &(change, _))| change != LineChange::Same)
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for index in changes {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end))
+        }
+    }
+
+    let mut output = String::new();
+    for (start, end) in hunks {
+        let before = &lines[..start];
+        let expected_before = before.iter().filter(|&&(change, _)| change != LineChange::Added).count();
+        let actual_before = before.iter().filter(|&&(change, _)| change != LineChange::Removed).count();
+        let hunk = &lines[start..end];
+        let expected_count = hunk.iter().filter(|&&(change, _)| change != LineChange::Added).count();
+        let actual_count = hunk.iter().filter(|&&(change, _)| change != LineChange::Removed).count();
+        output.push_str(&Cyan.paint(format!("@@ -{},{} +{},{} @@", range_start(expected_before, expected_count),
+            expected_count, range_start(actual_before, actual_count), actual_count)).to_string());
+        output.push('\n');
+        for &(change, ref line) in hunk {
+            match change {
+                LineChange::Same => output.push_str(&format!(" {}", line)),
+                LineChange::Added => output.push_str(&Green.paint(format!("+{}", line)).to_string()),
+                LineChange::Removed => output.push_str(&Red.paint(format!("-{}", line)).to_string())
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Returns a unified diff of the expected versus the actual XML bodies. Both bodies are
+/// canonicalized before they are compared. Bodies that are not valid XML are compared as text.
+pub fn display_xml_diff(expected: &String, actual: &String) -> String {
+    let expected_xml = canonicalize_xml(&expected.as_bytes().to_vec()).unwrap_or_else(|_| expected.clone());
+    let actual_xml = canonicalize_xml(&actual.as_bytes().to_vec()).unwrap_or_else(|_| actual.clone());
+    unified_diff(&expected_xml, &actual_xml, CONTEXT_LINES)
+}
+
+/// Returns a unified diff of the expected versus the actual text bodies
+pub fn display_text_diff(expected: &String, actual: &String) -> String {
+    unified_diff(expected, actual, CONTEXT_LINES)
+}
+
+/// Returns a diff of the expected and actual values of a body mismatch, if they can be usefully
+/// displayed as one. Values that are both XML documents are diffed as XML, and values that span
+/// multiple lines are diffed as text. Returns `None` for single line values, which are already
+/// shown in the description of the mismatch.
+pub fn body_mismatch_diff(expected: &Option<Vec<u8>>, actual: &Option<Vec<u8>>) -> Option<String> {
+    match (expected, actual) {
+        (&Some(ref expected), &Some(ref actual)) => {
+            let diff = if is_xml_document(expected) && is_xml_document(actual) {
+                display_xml_diff(&String::from_utf8_lossy(expected).to_string(),
+                    &String::from_utf8_lossy(actual).to_string())
+            } else if expected.contains(&b'\n') || actual.contains(&b'\n') {
+                display_text_diff(&String::from_utf8_lossy(expected).to_string(),
+                    &String::from_utf8_lossy(actual).to_string())
+            } else {
+                s!("")
+            };
+            if diff.is_empty() { None } else { Some(diff) }
+        },
+        _ => None
+    }
+}
+
+fn is_xml_document(body: &Vec<u8>) -> bool {
+    String::from_utf8_lossy(body).trim_start().starts_with('<') && parse_bytes(body).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+
+    fn header(text: &str) -> String {
+        format!("{}\n", Cyan.paint(text))
+    }
+
+    #[test]
+    fn unified_diff_returns_nothing_for_the_same_text() {
+        expect!(unified_diff("a\nb\nc", "a\nb\nc", 3)).to(be_equal_to(s!("")));
+    }
+
+    #[test]
+    fn unified_diff_trims_the_diff_to_the_lines_around_the_changes() {
+        let expected = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12";
+        let actual = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13";
+        expect!(unified_diff(expected, actual, 1)).to(be_equal_to(format!("{} 2\n{}\n{}\n 4\n{} 12\n{}\n",
+            header("@@ -2,3 +2,3 @@"), Red.paint("-3"), Green.paint("+three"),
+            header("@@ -12,1 +12,2 @@"), Green.paint("+13"))));
+    }
+
+    #[test]
+    fn unified_diff_merges_changes_that_are_close_together() {
+        let expected = "1\n2\n3\n4\n5";
+        let actual = "one\n2\n3\nfour\n5";
+        expect!(unified_diff(expected, actual, 1)).to(be_equal_to(format!("{}{}\n{}\n 2\n 3\n{}\n{}\n 5\n",
+            header("@@ -1,5 +1,5 @@"), Red.paint("-1"), Green.paint("+one"), Red.paint("-4"),
+            Green.paint("+four"))));
+    }
+
+    #[test]
+    fn display_xml_diff_ignores_formatting_and_attribute_order() {
+        let expected = s!(r#"<?xml version="1.0"?><order id="1" status="new"><item sku="A"/><item sku="B"/></order>"#);
+        let actual = s!(r#"<?xml version="1.0"?>
+        <order status="new"   id="1">
+          <item sku="A"/>
+          <item sku="C"/>
+        </order>"#);
+        expect!(display_xml_diff(&expected, &actual)).to(be_equal_to(format!(
+            "{} <order id=\"1\" status=\"new\">\n   <item sku=\"A\"/>\n{}\n{}\n </order>\n",
+            header("@@ -1,4 +1,4 @@"), Red.paint("-  <item sku=\"B\"/>"), Green.paint("+  <item sku=\"C\"/>"))));
+    }
+
+    #[test]
+    fn body_mismatch_diff_only_diffs_multiline_or_xml_values() {
+        expect!(body_mismatch_diff(&Some("a".into()), &Some("b".into()))).to(be_none());
+        expect!(body_mismatch_diff(&Some("a".into()), &None)).to(be_none());
+        expect!(body_mismatch_diff(&Some("a\nb".into()), &Some("a\nc".into()))).to(be_some());
+        expect!(body_mismatch_diff(&Some("<a x=\"1\"/>".into()), &Some("<a x=\"2\"/>".into()))).to(be_some());
+    }
+}
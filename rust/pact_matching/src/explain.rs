@@ -0,0 +1,325 @@
+//! The `explain` module provides a trace of how the matching rules were resolved when comparing
+//! two JSON bodies. For each path that is visited during the comparison, it records all the
+//! matching rules whose path expression matched along with their weights, the rule that was
+//! selected (the one with the highest weight) and the outcome of the comparison at that path.
+//!
+//! The entries are recorded by the JSON body matching itself while it compares the bodies, so they
+//! can be used to find out why a body matched when it was not expected to, or which rule caused a
+//! mismatch.
+
+use std::fmt::{Display, Formatter};
+use serde_json::{self, Value};
+use itertools::Itertools;
+use models::HttpPart;
+use models::matchingrules::{RuleResolver, RuleList, RuleLogic};
+use json::compare_with_trace;
+use {DiffConfig, MatchingConfig, Mismatch};
+
+/// A matching rule whose path expression matched a path
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateRule {
+    /// Path expression the rules were defined with
+    pub path_expression: String,
+    /// Weight of the path expression for the path. The rule with the highest weight is selected.
+    pub weight: usize,
+    /// Matching rules defined for the path expression
+    pub rules: RuleList
+}
+
+impl CandidateRule {
+    fn matchers_to_string(&self) -> String {
+        let matchers = self.rules.rules.iter().map(|rule| rule.to_json().to_string()).join(", ");
+        if self.rules.rules.len() > 1 {
+            let logic = match self.rules.rule_logic {
+                RuleLogic::And => "AND",
+                RuleLogic::Or => "OR"
+            };
+            format!("{} {}", logic, matchers)
+        } else {
+            matchers
+        }
+    }
+
+    /// Converts this candidate to a JSON structure
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pathExpression": self.path_expression,
+            "weight": self.weight,
+            "matchers": self.rules.rules.iter().map(|rule| rule.to_json()).collect::<Vec<Value>>()
+        })
+    }
+}
+
+/// Outcome of the comparison at a path
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExplainOutcome {
+    /// The actual value matched
+    Matched,
+    /// The actual value did not match, with the mismatch messages
+    Mismatched(Vec<String>)
+}
+
+/// Explanation of the comparison at a single path in the body
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainEntry {
+    /// Path in the body (in the same form as the path of body mismatches, i.e. `$.items.0.id`)
+    pub path: String,
+    /// All the rules that apply to the path, ordered from the selected rule to the least specific
+    pub candidates: Vec<CandidateRule>,
+    /// The rule that was used. If there is none, the values are compared with equality, or
+    /// for maps and lists, by their structure.
+    pub selected: Option<CandidateRule>,
+    /// Outcome of the comparison
+    pub outcome: ExplainOutcome
+}
+
+impl ExplainEntry {
+    /// Converts this entry to a JSON structure
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "path": self.path,
+            "candidates": self.candidates.iter().map(|candidate| candidate.to_json()).collect::<Vec<Value>>(),
+            "selected": self.selected.as_ref().map(|candidate| json!(candidate.path_expression))
+                .unwrap_or(Value::Null)
+        });
+        match self.outcome {
+            ExplainOutcome::Matched => json["outcome"] = json!("matched"),
+            ExplainOutcome::Mismatched(ref messages) => {
+                json["outcome"] = json!("mismatched");
+                json["mismatches"] = json!(messages);
+            }
+        }
+        json
+    }
+}
+
+impl Display for ExplainEntry {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", self.path)?;
+        if !self.candidates.is_empty() {
+            writeln!(f, "  candidates:")?;
+            for candidate in &self.candidates {
+                writeln!(f, "    {} (weight {}): {}", candidate.path_expression, candidate.weight,
+                    candidate.matchers_to_string())?;
+            }
+        }
+        match self.selected {
+            Some(ref candidate) => writeln!(f, "  selected: {}", candidate.path_expression)?,
+            None => writeln!(f, "  selected: none (default comparison)")?
+        }
+        match self.outcome {
+            ExplainOutcome::Matched => write!(f, "  outcome: matched"),
+            ExplainOutcome::Mismatched(ref messages) => {
+                write!(f, "  outcome: mismatched")?;
+                for message in messages {
+                    write!(f, "\n    - {}", message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Explanation of a body comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// Entries for each path visited, in the order they were visited
+    pub entries: Vec<ExplainEntry>
+}
+
+impl Explanation {
+    /// If the body matched
+    pub fn matched(&self) -> bool {
+        self.entries.iter().all(|entry| entry.outcome == ExplainOutcome::Matched)
+    }
+
+    /// Converts the explanation to a JSON structure
+    pub fn to_json(&self) -> Value {
+        json!({
+            "matched": self.matched(),
+            "entries": self.entries.iter().map(|entry| entry.to_json()).collect::<Vec<Value>>()
+        })
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.entries.iter().map(|entry| entry.to_string()).join("\n"))
+    }
+}
+
+fn outcome(messages: Vec<String>) -> ExplainOutcome {
+    if messages.is_empty() {
+        ExplainOutcome::Matched
+    } else {
+        ExplainOutcome::Mismatched(messages)
+    }
+}
+
+fn candidates(path: &Vec<String>, matchers: &dyn RuleResolver) -> Vec<CandidateRule> {
+    matchers.resolve_body_matchers_with_weights(path).into_iter()
+        .map(|(path_expression, weight, rules)| CandidateRule { path_expression, weight, rules })
+        .collect()
+}
+
+/// Adds an entry for the path to the trace before the path is compared, and returns its index
+pub(crate) fn start_entry(path: &Vec<String>, matchers: &dyn RuleResolver, entries: &mut Vec<ExplainEntry>) -> usize {
+    let candidates = candidates(path, matchers);
+    entries.push(ExplainEntry {
+        path: path.join("."),
+        selected: candidates.first().cloned(),
+        candidates,
+        outcome: ExplainOutcome::Matched
+    });
+    entries.len() - 1
+}
+
+/// Sets the outcome of the entry from the mismatches that were raised while comparing its path.
+/// Mismatches for the children of the path have their own entries, so are not included.
+pub(crate) fn finish_entry(entry: &mut ExplainEntry, mismatches: &[Mismatch]) {
+    let messages = mismatches.iter().filter_map(|mismatch| match *mismatch {
+        Mismatch::BodyMismatch { ref path, ref mismatch, .. } if *path == entry.path => Some(mismatch.clone()),
+        _ => None
+    }).collect();
+    entry.outcome = outcome(messages);
+}
+
+/// Explains the comparison of the actual body to the expected one, using the matching rules of the
+/// expected part. Returns an error if the bodies are not JSON or can not be parsed. Parts without a
+/// body return an empty explanation.
+pub fn explain_body(expected: &dyn HttpPart, actual: &dyn HttpPart, config: DiffConfig) -> Result<Explanation, String> {
+    if !expected.body().is_present() || !actual.body().is_present() {
+        return Ok(Explanation { entries: vec![] });
+    }
+    let content_type = expected.content_type();
+    if !content_type.starts_with("application/json") {
+        return Err(format!("Only JSON bodies can be explained, the expected body has a content type of '{}'",
+            content_type));
+    }
+    let expected_json: Value = serde_json::from_slice(&expected.body().value())
+        .map_err(|err| format!("Failed to parse the expected body - {}", err))?;
+    let actual_json: Value = serde_json::from_slice(&actual.body().value())
+        .map_err(|err| format!("Failed to parse the actual body - {}", err))?;
+    let mut entries = vec![];
+    let mut mismatches = vec![];
+    compare_with_trace(&vec![s!("$")], &expected_json, &actual_json, &config, &MatchingConfig::default(),
+        &mut mismatches, expected.matching_rules(), &mut Some(&mut entries));
+    Ok(Explanation { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use models::{Response, OptionalBody};
+    use models::matchingrules::{MatchingRules, MatchingRule};
+    use json::match_json;
+
+    fn response(body: Value, matching_rules: MatchingRules) -> Response {
+        Response {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+            body: OptionalBody::Present(body.to_string().into_bytes(), None),
+            matching_rules,
+            .. Response::default()
+        }
+    }
+
+    #[test]
+    fn explains_the_rules_selected_for_each_path() {
+        let expected = response(json!({ "items": [ { "id": 1, "name": "a" } ] }), matchingrules!{
+            "body" => {
+                "$.items" => [ MatchingRule::MinType(1) ],
+                "$.items[*].id" => [ MatchingRule::Integer ],
+                "$..id" => [ MatchingRule::Regex(s!("\\d+")) ]
+            }
+        });
+        let actual = response(json!({ "items": [ { "id": 100, "name": "a" }, { "id": "x", "name": "b" } ] }),
+            MatchingRules::default());
+
+        let explanation = explain_body(&expected, &actual, DiffConfig::AllowUnexpectedKeys).unwrap();
+
+        let paths = explanation.entries.iter().map(|entry| entry.path.clone()).collect::<Vec<String>>();
+        expect!(paths).to(be_equal_to(vec![s!("$"), s!("$.items"), s!("$.items.0"), s!("$.items.0.id"),
+            s!("$.items.0.name"), s!("$.items.1"), s!("$.items.1.id"), s!("$.items.1.name")]));
+
+        let root = &explanation.entries[0];
+        expect!(root.candidates.iter()).to(be_empty());
+        expect!(root.selected.clone()).to(be_none());
+        expect!(root.outcome.clone()).to(be_equal_to(ExplainOutcome::Matched));
+
+        let id = &explanation.entries[3];
+        expect!(id.candidates.iter().map(|c| (c.path_expression.clone(), c.weight)).collect::<Vec<_>>()).to(be_equal_to(vec![
+            (s!("$.items[*].id"), 8), (s!("$..id"), 4), (s!("$.items"), 4)
+        ]));
+        expect!(id.selected.clone().map(|c| c.path_expression)).to(be_some().value(s!("$.items[*].id")));
+        expect!(id.outcome.clone()).to(be_equal_to(ExplainOutcome::Matched));
+
+        let invalid_id = &explanation.entries[6];
+        expect!(invalid_id.outcome.clone()).to(be_equal_to(ExplainOutcome::Mismatched(vec![
+            s!("Expected 'x' to be an integer value")
+        ])));
+        expect!(explanation.matched()).to(be_false());
+    }
+
+    #[test]
+    fn explains_default_comparisons() {
+        let expected = response(json!({ "a": 1, "b": [1, 2] }), MatchingRules::default());
+        let actual = response(json!({ "a": 2, "b": [1] }), MatchingRules::default());
+
+        let explanation = explain_body(&expected, &actual, DiffConfig::AllowUnexpectedKeys).unwrap();
+
+        expect!(explanation.to_json()).to(be_equal_to(json!({
+            "matched": false,
+            "entries": [
+                { "path": "$", "candidates": [], "selected": null, "outcome": "matched" },
+                { "path": "$.a", "candidates": [], "selected": null, "outcome": "mismatched",
+                  "mismatches": ["Expected '1' to be equal to '2'"] },
+                { "path": "$.b", "candidates": [], "selected": null, "outcome": "mismatched",
+                  "mismatches": ["Expected 2 but was missing", "Expected a List with 2 elements but received 1 elements"] },
+                { "path": "$.b.0", "candidates": [], "selected": null, "outcome": "matched" }
+            ]
+        })));
+        expect!(explanation.to_string()).to(be_equal_to(s!("$\n  selected: none (default comparison)\n  outcome: matched\n\
+            $.a\n  selected: none (default comparison)\n  outcome: mismatched\n    - Expected '1' to be equal to '2'\n\
+            $.b\n  selected: none (default comparison)\n  outcome: mismatched\n    - Expected 2 but was missing\n    - Expected a List with 2 elements but received 1 elements\n\
+            $.b.0\n  selected: none (default comparison)\n  outcome: matched")));
+    }
+
+    #[test]
+    fn explains_the_mismatches_of_the_json_body_matching() {
+        let expected = response(json!({ "id": 1, "tags": ["a"], "owner": { "name": "x", "age": 10 } }), matchingrules!{
+            "body" => {
+                "$.tags" => [ MatchingRule::MinType(2) ],
+                "$.owner.*" => [ MatchingRule::Type ]
+            }
+        });
+        let actual = response(json!({ "id": 2, "tags": ["b"], "owner": { "name": 100, "age": 11 } }),
+            MatchingRules::default());
+
+        let explanation = explain_body(&expected, &actual, DiffConfig::AllowUnexpectedKeys).unwrap();
+
+        let mut mismatches = vec![];
+        match_json(&expected.body.value(), &actual.body.value(), DiffConfig::AllowUnexpectedKeys,
+            &mut mismatches, &expected.matching_rules);
+        let explained = explanation.entries.iter().flat_map(|entry| match entry.outcome {
+            ExplainOutcome::Matched => vec![],
+            ExplainOutcome::Mismatched(ref messages) => messages.iter()
+                .map(|message| format!("{} -> {}", entry.path, message)).collect()
+        }).collect::<Vec<String>>();
+        expect!(explained.len()).to(be_equal_to(3));
+        expect!(explained).to(be_equal_to(mismatches.iter().map(|mismatch| mismatch.description())
+            .collect::<Vec<String>>()));
+    }
+
+    #[test]
+    fn only_json_bodies_can_be_explained() {
+        let expected = Response {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/plain")] }),
+            body: OptionalBody::Present("text".into(), None),
+            .. Response::default()
+        };
+        expect!(explain_body(&expected, &expected.clone(), DiffConfig::AllowUnexpectedKeys)).to(be_err());
+        expect!(explain_body(&Response::default(), &Response::default(), DiffConfig::AllowUnexpectedKeys))
+            .to(be_ok().value(Explanation { entries: vec![] }));
+    }
+}
@@ -91,20 +91,20 @@ fn apply_generator_to_empty_body_test() {
 #[test]
 fn do_not_apply_generators_if_there_are_no_body_generators() {
   let generators = Generators::default();
-  let body = OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into());
+  let body = OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into(), None);
   expect!(generators.apply_body_generators(&body, DetectedContentType::Json, &hashmap!{})).to(be_equal_to(body));
 }
 
 #[test]
 fn apply_generator_to_text_body_test() {
   let generators = Generators::default();
-  let body = OptionalBody::Present("some text".into());
+  let body = OptionalBody::Present("some text".into(), None);
   expect!(generators.apply_body_generators(&body, DetectedContentType::Text, &hashmap!{})).to(be_equal_to(body));
 }
 
 #[test]
 fn applies_body_generator_to_the_copy_of_the_request() {
-  let request = Request { body: OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into()),
+  let request = Request { body: OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into(), None),
     generators: generators! {
       "BODY" => {
         "$.a" => Generator::RandomInt(1, 10)
@@ -119,7 +119,7 @@ fn applies_body_generator_to_the_copy_of_the_request() {
 
 #[test]
 fn applies_body_generator_to_the_copy_of_the_response() {
-  let response = Response { body: OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into()),
+  let response = Response { body: OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into(), None),
     generators: generators! {
       "BODY" => {
         "$.a" => Generator::RandomInt(1, 10)
@@ -133,13 +133,27 @@ fn applies_body_generator_to_the_copy_of_the_response() {
 
 #[test]
 fn does_not_change_body_if_there_are_no_generators() {
-  let body = OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into());
+  let body = OptionalBody::Present("{\"a\": 100, \"b\": \"B\"}".into(), None);
   let generators = generators!{};
   let processed = generators.apply_body_generators(&body, DetectedContentType::Json,
     &hashmap!{});
   expect!(processed).to(be_equal_to(body));
 }
 
+#[test]
+fn applies_body_generators_to_a_yaml_body() {
+  let body = OptionalBody::Present("a: 100\nb: B\n".into(), None);
+  let generators = generators! {
+    "BODY" => {
+      "$.a" => Generator::RandomInt(1, 10)
+    }
+  };
+  let processed = generators.apply_body_generators(&body, DetectedContentType::Yaml, &hashmap!{});
+  let yaml: Value = serde_yaml::from_slice(&processed.value()).unwrap();
+  expect!(&yaml["a"]).to_not(be_equal_to(&json!(100)));
+  expect!(&yaml["b"]).to(be_equal_to(&json!("B")));
+}
+
 #[test]
 fn applies_the_generator_to_a_json_map_entry() {
   let map = json!({"a": 100, "b": "B", "c": "C"});
@@ -333,3 +347,84 @@ fn regex_generator_test() {
   let generated = Generator::Regex(r"\d{4}\w{1,4}".into()).generate_value(&"".to_string(), &hashmap!{});
   assert_that!(generated.unwrap(), matches_regex(r"^\d{4}\w{1,4}$"));
 }
+
+#[test]
+fn applies_provider_state_generators_to_the_copy_of_the_request() {
+  let request = Request {
+    path: s!("/orders/1/items/2"),
+    query: Some(hashmap!{ s!("order") => vec![s!("1")] }),
+    headers: Some(hashmap!{
+      s!("Content-Type") => vec![s!("application/json")],
+      s!("X-Order") => vec![s!("1")]
+    }),
+    body: OptionalBody::Present("{\"orderId\": 1, \"description\": \"\", \"express\": false}".into(), None),
+    generators: generators! {
+      "PATH" => Generator::ProviderStateGenerator(s!("/orders/${orderId}/items/${itemId}")),
+      "QUERY" => {
+        "order" => Generator::ProviderStateGenerator(s!("orderId"))
+      },
+      "HEADER" => {
+        "X-Order" => Generator::ProviderStateGenerator(s!("Order ${orderId}"))
+      },
+      "BODY" => {
+        "$.orderId" => Generator::ProviderStateGenerator(s!("${orderId}")),
+        "$.description" => Generator::ProviderStateGenerator(s!("Order ${orderId} item ${itemId}")),
+        "$.express" => Generator::ProviderStateGenerator(s!("express"))
+      }
+    },
+    .. Request::default()
+  };
+  let context = hashmap!{
+    s!("orderId") => json!("1234"),
+    s!("itemId") => json!(5678),
+    s!("express") => json!("true")
+  };
+
+  let generated = generate_request(&request, &context);
+
+  expect!(generated.path).to(be_equal_to(s!("/orders/1234/items/5678")));
+  expect!(generated.query.unwrap().get("order").unwrap().clone()).to(be_equal_to(vec![s!("1234")]));
+  expect!(generated.headers.unwrap().get("X-Order").unwrap().clone()).to(be_equal_to(vec![s!("Order 1234")]));
+  let body: Value = serde_json::from_str(generated.body.str_value()).unwrap();
+  expect!(body).to(be_equal_to(json!({
+    "orderId": 1234,
+    "description": "Order 1234 item 5678",
+    "express": true
+  })));
+}
+
+#[test]
+fn provider_state_generator_leaves_the_value_if_the_expression_can_not_be_evaluated() {
+  let request = Request { path: s!("/orders/1"), generators: generators! {
+    "PATH" => Generator::ProviderStateGenerator(s!("/orders/${orderId}"))
+  }, .. Request::default() };
+  expect!(generate_request(&request, &hashmap!{}).path).to(be_equal_to(s!("/orders/1")));
+}
+
+#[test]
+fn applies_provider_state_generator_to_the_response_status() {
+  let response = Response { status: 200, generators: generators! {
+    "STATUS" => Generator::ProviderStateGenerator(s!("status"))
+  }, .. Response::default() };
+  expect!(generate_response(&response, &hashmap!{ s!("status") => json!("201") }).status).to(be_equal_to(201));
+}
+
+#[test]
+fn applies_the_generator_to_all_matching_values_with_recursive_descent() {
+  let value = json!({
+    "id": 1,
+    "data": {
+      "items": [ { "id": 2, "name": "a" }, { "id": 3, "a/b": { "id": 4 } } ]
+    },
+    "name": "b"
+  });
+  let mut json_handler = JsonHandler { value };
+
+  json_handler.apply_key(&s!("$.data..id"), &Generator::RandomInt(100, 200), &hashmap!{});
+
+  expect!(&json_handler.value["id"]).to(be_equal_to(&json!(1)));
+  expect!(json_handler.value["data"]["items"][0]["id"].as_i64().unwrap()).to(be_greater_or_equal_to(100));
+  expect!(json_handler.value["data"]["items"][1]["id"].as_i64().unwrap()).to(be_greater_or_equal_to(100));
+  expect!(json_handler.value["data"]["items"][1]["a/b"]["id"].as_i64().unwrap()).to(be_greater_or_equal_to(100));
+  expect!(&json_handler.value["data"]["items"][0]["name"]).to(be_equal_to(&json!("a")));
+}
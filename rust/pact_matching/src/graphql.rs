@@ -0,0 +1,614 @@
+//! The `graphql` module provides functions to compare GraphQL requests. The query documents are
+//! parsed and normalised before being compared, so differences in whitespace, commas, comments and the
+//! order of fields and arguments are ignored. The other attributes of the request (`operationName`,
+//! `variables`, etc.) are compared using the JSON matching rules.
+
+use serde_json::{self, Value};
+use super::{Mismatch, MismatchDetails, MismatchKind};
+use super::{DiffConfig, MatchingConfig};
+use models::matchingrules::*;
+use matchers::*;
+use json::{compare, parse_error_details, rule_mismatch_details};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punctuator(String),
+    Name(String),
+    Value(String)
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match *self {
+            Token::Punctuator(ref p) => format!("'{}'", p),
+            Token::Name(ref n) => format!("'{}'", n),
+            Token::Value(ref v) => format!("value {}", v)
+        }
+    }
+}
+
+fn tokenise(query: &str) -> Result<Vec<Token>, String> {
+    let chars = query.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        match ch {
+            _ if ch.is_whitespace() || ch == ',' || ch == '\u{feff}' => index += 1,
+            '#' => {
+                while index < chars.len() && chars[index] != '\n' && chars[index] != '\r' {
+                    index += 1;
+                }
+            },
+            '!' | '$' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}' => {
+                tokens.push(Token::Punctuator(ch.to_string()));
+                index += 1;
+            },
+            '.' => if chars[index..].starts_with(&['.', '.', '.']) {
+                tokens.push(Token::Punctuator(s!("...")));
+                index += 3;
+            } else {
+                return Err(format!("Unexpected character '.' at offset {}", index));
+            },
+            '"' => {
+                let start = index;
+                if chars[index..].starts_with(&['"', '"', '"']) {
+                    index += 3;
+                    loop {
+                        if index >= chars.len() {
+                            return Err(format!("Unterminated block string at offset {}", start));
+                        } else if chars[index] == '\\' && chars[index + 1..].starts_with(&['"', '"', '"']) {
+                            index += 4;
+                        } else if chars[index..].starts_with(&['"', '"', '"']) {
+                            index += 3;
+                            break;
+                        } else {
+                            index += 1;
+                        }
+                    }
+                } else {
+                    index += 1;
+                    loop {
+                        if index >= chars.len() || chars[index] == '\n' || chars[index] == '\r' {
+                            return Err(format!("Unterminated string at offset {}", start));
+                        } else if chars[index] == '\\' {
+                            index += 2;
+                        } else if chars[index] == '"' {
+                            index += 1;
+                            break;
+                        } else {
+                            index += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Value(chars[start..index].iter().collect()));
+            },
+            _ if ch == '-' || ch.is_ascii_digit() => {
+                let start = index;
+                index += 1;
+                while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '.' ||
+                    ((chars[index] == '-' || chars[index] == '+') && (chars[index - 1] == 'e' || chars[index - 1] == 'E'))) {
+                    index += 1;
+                }
+                tokens.push(Token::Value(chars[start..index].iter().collect()));
+            },
+            _ if ch == '_' || ch.is_ascii_alphabetic() => {
+                let start = index;
+                while index < chars.len() && (chars[index] == '_' || chars[index].is_ascii_alphanumeric()) {
+                    index += 1;
+                }
+                tokens.push(Token::Name(chars[start..index].iter().collect()));
+            },
+            _ => return Err(format!("Unexpected character '{}' at offset {}", ch, index))
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parser that writes out a GraphQL document in a normalised form. Selection sets, arguments,
+/// variable definitions and input object fields are sorted, and the query shorthand (`{ ... }`)
+/// is written out as a `query` operation.
+struct Normaliser {
+    tokens: Vec<Token>,
+    index: usize
+}
+
+impl Normaliser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn peek_is(&self, punctuator: &str) -> bool {
+        self.peek() == Some(&Token::Punctuator(s!(punctuator)))
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        match self.tokens.get(self.index).cloned() {
+            Some(token) => {
+                self.index += 1;
+                Ok(token)
+            },
+            None => Err(s!("Unexpected end of the query"))
+        }
+    }
+
+    fn expect(&mut self, punctuator: &str) -> Result<(), String> {
+        match self.next()? {
+            Token::Punctuator(ref p) if p == punctuator => Ok(()),
+            token => Err(format!("Expected '{}' but found {}", punctuator, token.describe()))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Name(name) => Ok(name),
+            token => Err(format!("Expected a name but found {}", token.describe()))
+        }
+    }
+
+    fn document(&mut self) -> Result<String, String> {
+        let mut definitions = vec![];
+        while self.peek().is_some() {
+            definitions.push(self.definition()?);
+        }
+        if definitions.is_empty() {
+            Err(s!("The query document is empty"))
+        } else {
+            Ok(definitions.join(" "))
+        }
+    }
+
+    fn definition(&mut self) -> Result<String, String> {
+        if self.peek_is("{") {
+            return Ok(format!("query {}", self.selection_set()?));
+        }
+        let keyword = self.name()?;
+        match keyword.as_str() {
+            "query" | "mutation" | "subscription" => {
+                let mut definition = keyword.clone();
+                if let Some(&Token::Name(_)) = self.peek() {
+                    definition.push(' ');
+                    definition.push_str(&self.name()?);
+                }
+                if self.peek_is("(") {
+                    definition.push_str(&self.list(Normaliser::variable_definition)?);
+                }
+                definition.push_str(&self.directives()?);
+                Ok(format!("{} {}", definition, self.selection_set()?))
+            },
+            "fragment" => {
+                let name = self.name()?;
+                let on = self.name()?;
+                if on != "on" {
+                    return Err(format!("Expected 'on' but found '{}'", on));
+                }
+                let type_condition = self.name()?;
+                let directives = self.directives()?;
+                Ok(format!("fragment {} on {}{} {}", name, type_condition, directives, self.selection_set()?))
+            },
+            _ => Err(format!("Expected a query, mutation, subscription or fragment but found '{}'", keyword))
+        }
+    }
+
+    fn selection_set(&mut self) -> Result<String, String> {
+        self.expect("{")?;
+        let mut selections = vec![];
+        while !self.peek_is("}") {
+            selections.push(self.selection()?);
+        }
+        self.expect("}")?;
+        if selections.is_empty() {
+            return Err(s!("Expected a selection set with at least one field"));
+        }
+        selections.sort();
+        Ok(format!("{{ {} }}", selections.join(" ")))
+    }
+
+    fn selection(&mut self) -> Result<String, String> {
+        if self.peek_is("...") {
+            self.next()?;
+            match self.peek().cloned() {
+                Some(Token::Name(ref name)) if name == "on" => {
+                    self.next()?;
+                    let type_condition = self.name()?;
+                    let directives = self.directives()?;
+                    Ok(format!("... on {}{} {}", type_condition, directives, self.selection_set()?))
+                },
+                Some(Token::Name(_)) => {
+                    let name = self.name()?;
+                    Ok(format!("...{}{}", name, self.directives()?))
+                },
+                _ => {
+                    let directives = self.directives()?;
+                    Ok(format!("...{} {}", directives, self.selection_set()?))
+                }
+            }
+        } else {
+            let mut field = self.name()?;
+            if self.peek_is(":") {
+                self.next()?;
+                field = format!("{}: {}", field, self.name()?);
+            }
+            if self.peek_is("(") {
+                field.push_str(&self.list(Normaliser::argument)?);
+            }
+            field.push_str(&self.directives()?);
+            if self.peek_is("{") {
+                field = format!("{} {}", field, self.selection_set()?);
+            }
+            Ok(field)
+        }
+    }
+
+    /// Parenthesised list of arguments or variable definitions, which are sorted
+    fn list(&mut self, item: fn(&mut Normaliser) -> Result<String, String>) -> Result<String, String> {
+        self.expect("(")?;
+        let mut items = vec![];
+        while !self.peek_is(")") {
+            items.push(item(self)?);
+        }
+        self.expect(")")?;
+        items.sort();
+        Ok(format!("({})", items.join(", ")))
+    }
+
+    fn argument(&mut self) -> Result<String, String> {
+        let name = self.name()?;
+        self.expect(":")?;
+        Ok(format!("{}: {}", name, self.value()?))
+    }
+
+    fn variable_definition(&mut self) -> Result<String, String> {
+        self.expect("$")?;
+        let name = self.name()?;
+        self.expect(":")?;
+        let mut definition = format!("${}: {}", name, self.type_reference()?);
+        if self.peek_is("=") {
+            self.next()?;
+            definition = format!("{} = {}", definition, self.value()?);
+        }
+        definition.push_str(&self.directives()?);
+        Ok(definition)
+    }
+
+    fn type_reference(&mut self) -> Result<String, String> {
+        let mut type_ref = if self.peek_is("[") {
+            self.next()?;
+            let inner = self.type_reference()?;
+            self.expect("]")?;
+            format!("[{}]", inner)
+        } else {
+            self.name()?
+        };
+        if self.peek_is("!") {
+            self.next()?;
+            type_ref.push('!');
+        }
+        Ok(type_ref)
+    }
+
+    fn directives(&mut self) -> Result<String, String> {
+        let mut directives = String::new();
+        while self.peek_is("@") {
+            self.next()?;
+            directives.push_str(&format!(" @{}", self.name()?));
+            if self.peek_is("(") {
+                directives.push_str(&self.list(Normaliser::argument)?);
+            }
+        }
+        Ok(directives)
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Punctuator(ref p) if p == "$" => Ok(format!("${}", self.name()?)),
+            Token::Punctuator(ref p) if p == "[" => {
+                let mut values = vec![];
+                while !self.peek_is("]") {
+                    values.push(self.value()?);
+                }
+                self.expect("]")?;
+                Ok(format!("[{}]", values.join(", ")))
+            },
+            Token::Punctuator(ref p) if p == "{" => {
+                let mut fields = vec![];
+                while !self.peek_is("}") {
+                    fields.push(self.argument()?);
+                }
+                self.expect("}")?;
+                fields.sort();
+                Ok(format!("{{{}}}", fields.join(", ")))
+            },
+            Token::Name(name) => Ok(name),
+            Token::Value(value) => Ok(value),
+            token => Err(format!("Expected a value but found {}", token.describe()))
+        }
+    }
+}
+
+/// Parses the GraphQL query document, and returns it in a normalised form where the insignificant
+/// whitespace, commas and comments have been removed, and the fields and arguments are sorted.
+pub fn normalise_query(query: &str) -> Result<String, String> {
+    let mut normaliser = Normaliser { tokens: tokenise(query)?, index: 0 };
+    normaliser.document()
+}
+
+/// Parses the body as a GraphQL request. JSON bodies are returned as is, and any other body is
+/// treated as the query document (i.e. for a content type of `application/graphql`).
+fn graphql_request(body: &Vec<u8>) -> Result<Value, String> {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(json) => if json.is_object() {
+            Ok(json)
+        } else {
+            Err(format!("Expected a JSON object but received {}", json))
+        },
+        Err(_) => match String::from_utf8(body.clone()) {
+            Ok(query) => Ok(json!({ "query": query })),
+            Err(err) => Err(format!("{}", err))
+        }
+    }
+}
+
+/// If the body should be compared as a GraphQL request. This is the case for a content type of
+/// `application/graphql`, or a JSON body with a `query` attribute that is a valid GraphQL document
+/// (which is how GraphQL clients normally send their requests). JSON bodies where the `query`
+/// attribute is not a string or not a GraphQL document are still compared as JSON.
+pub fn is_graphql_request(mimetype: &String, body: &Vec<u8>) -> bool {
+    if mimetype == "application/graphql" {
+        true
+    } else if mimetype.starts_with("application/") && mimetype.contains("json") {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(ref json) => match json.get("query") {
+                Some(&Value::String(ref query)) => normalise_query(query).is_ok(),
+                _ => false
+            },
+            Err(_) => false
+        }
+    } else {
+        false
+    }
+}
+
+/// Matches the expected GraphQL request to the actual, and populates the mismatches vector with any differences
+pub fn match_graphql(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    match_graphql_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the expected GraphQL request to the actual, and populates the mismatches vector with any differences.
+/// Stops comparing the requests once the mismatch limit of the matching configuration is reached.
+pub fn match_graphql_with_config(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let expected_request = graphql_request(expected);
+    let actual_request = graphql_request(actual);
+
+    if expected_request.is_err() || actual_request.is_err() {
+        match expected_request {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone()),
+                    actual: Some(actual.clone()),
+                    mismatch: format!("Failed to parse the expected body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+        match actual_request {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone()),
+                    actual: Some(actual.clone()),
+                    mismatch: format!("Failed to parse the actual body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+    } else {
+        let expected_request = expected_request.unwrap();
+        let actual_request = actual_request.unwrap();
+        compare_query(&expected_request, &actual_request, mismatches, matchers);
+
+        let expected_map = expected_request.as_object().unwrap();
+        let actual_map = actual_request.as_object().unwrap();
+        for (key, value) in expected_map.iter().filter(|&(key, _)| key != "query") {
+            if matching_config.limit_reached(mismatches.len()) {
+                return;
+            }
+            let path = vec![s!("$"), key.clone()];
+            match actual_map.get(key) {
+                Some(actual_value) => compare(&path, value, actual_value, &config, matching_config,
+                    mismatches, matchers),
+                None => if !value.is_null() {
+                    mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
+                        expected: Some(value.to_string().into()),
+                        actual: None,
+                        mismatch: format!("Expected {}={} but was missing", key, value),
+                        details: Some(MismatchDetails::new(MismatchKind::MissingValue, &path, value.clone(), Value::Null))});
+                }
+            }
+        }
+        if config == DiffConfig::NoUnexpectedKeys {
+            for (key, value) in actual_map.iter().filter(|&(key, _)| key != "query" && !expected_map.contains_key(key)) {
+                if !value.is_null() && *value != json!({}) {
+                    mismatches.push(Mismatch::BodyMismatch { path: format!("$.{}", key),
+                        expected: None,
+                        actual: Some(value.to_string().into()),
+                        mismatch: format!("Unexpected {}={} received", key, value),
+                        details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, &[s!("$"), key.clone()],
+                          Value::Null, value.clone()))});
+                }
+            }
+        }
+    }
+}
+
+fn compare_query(expected: &Value, actual: &Value, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let path = vec![s!("$"), s!("query")];
+    let expected_query = expected.get("query").and_then(|query| query.as_str()).map(|query| s!(query)).unwrap_or_default();
+    let actual_query = match actual.get("query") {
+        Some(&Value::String(ref query)) => query.clone(),
+        _ => {
+            mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
+                expected: Some(expected_query.clone().into()),
+                actual: None,
+                mismatch: s!("Expected a GraphQL query but was missing"),
+                details: Some(MismatchDetails::new(MismatchKind::MissingValue, &path, json!(expected_query),
+                  actual.get("query").cloned().unwrap_or(Value::Null)))});
+            return;
+        }
+    };
+
+    let expected_json = json!(expected_query);
+    let actual_json = json!(actual_query);
+    let matcher_result: Result<(), Vec<(MismatchDetails, String)>> = if matchers.matcher_is_defined("body", &path) {
+        match_values_with_rules("body", &path, matchers, &expected_query, &actual_query)
+          .map_err(|failures| failures.into_iter()
+            .map(|(rule, message)| (rule_mismatch_details(rule, &path, &expected_json, &actual_json), message))
+            .collect())
+    } else {
+        let details = |kind: MismatchKind| MismatchDetails::new(kind, &path, expected_json.clone(), actual_json.clone());
+        match (normalise_query(&expected_query), normalise_query(&actual_query)) {
+            (Ok(ref e), Ok(ref a)) if e == a => Ok(()),
+            (Ok(e), Ok(a)) => Err(vec![(details(MismatchKind::RuleMismatch(MatchingRule::Equality)),
+              format!("Expected GraphQL query '{}' but received '{}'", e, a))]),
+            (Err(err), _) => Err(vec![(details(MismatchKind::ParseError),
+              format!("Failed to parse the expected GraphQL query: {}", err))]),
+            (_, Err(err)) => Err(vec![(details(MismatchKind::ParseError),
+              format!("Failed to parse the actual GraphQL query: {}", err))])
+        }
+    };
+    debug!("Comparing GraphQL query '{}' to '{}' -> {:?}", expected_query, actual_query, matcher_result);
+    match matcher_result {
+        Err(failures) => {
+          for (details, message) in failures {
+            mismatches.push(Mismatch::BodyMismatch {
+              path: path.join("."),
+              expected: Some(expected_query.clone().into()),
+              actual: Some(actual_query.clone().into()),
+              mismatch: message.clone(),
+              details: Some(details)
+            })
+          }
+        },
+        Ok(_) => ()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use Mismatch;
+    use DiffConfig;
+
+    fn mismatch_message(mismatch: &Mismatch) -> String {
+        match mismatch {
+            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m, .. } => m.clone(),
+            _ => s!("")
+        }
+    }
+
+    fn mismatch_path(mismatch: &Mismatch) -> String {
+        match mismatch {
+            &Mismatch::BodyMismatch{ path: ref p, expected: _, actual: _, mismatch: _, .. } => p.clone(),
+            _ => s!("")
+        }
+    }
+
+    #[test]
+    fn normalise_query_removes_insignificant_characters_and_sorts_fields() {
+        expect!(normalise_query("query GetUser($id: ID!) {\n  user(id: $id) {\n    name, # the name\n    email\n  }\n}"))
+            .to(be_ok().value(s!("query GetUser($id: ID!) { user(id: $id) { email name } }")));
+        expect!(normalise_query("{ user(id: 1, active: true) { ...UserFields friends(first: 10) @include(if: $all) { name } } }"))
+            .to(be_ok().value(s!("query { user(active: true, id: 1) { ...UserFields friends(first: 10) @include(if: $all) { name } } }")));
+        expect!(normalise_query("mutation { add(input: {name: \"Fred\", age: 42, tags: [\"a\" \"b\"]}) { id } }"))
+            .to(be_ok().value(s!("mutation { add(input: {age: 42, name: \"Fred\", tags: [\"a\", \"b\"]}) { id } }")));
+        expect!(normalise_query("fragment UserFields on User { name ... on Admin { level } }"))
+            .to(be_ok().value(s!("fragment UserFields on User { ... on Admin { level } name }")));
+    }
+
+    #[test]
+    fn normalise_query_returns_an_error_for_invalid_queries() {
+        expect!(normalise_query("")).to(be_err().value(s!("The query document is empty")));
+        expect!(normalise_query("{ user { name }")).to(be_err().value(s!("Unexpected end of the query")));
+        expect!(normalise_query("query { user(id: ) { name } }")).to(be_err().value(s!("Expected a value but found ')'")));
+        expect!(normalise_query("update { user }")).to(be_err()
+            .value(s!("Expected a query, mutation, subscription or fragment but found 'update'")));
+    }
+
+    #[test]
+    fn is_graphql_request_test() {
+        expect!(is_graphql_request(&s!("application/graphql"), &"{ user { name } }".into())).to(be_true());
+        expect!(is_graphql_request(&s!("application/json"), &r#"{"query": "{ user { name } }"}"#.into())).to(be_true());
+        expect!(is_graphql_request(&s!("application/json"), &r#"{"query": "not a query"}"#.into())).to(be_false());
+        expect!(is_graphql_request(&s!("application/json"), &r#"{"query": 100}"#.into())).to(be_false());
+        expect!(is_graphql_request(&s!("application/json"), &r#"{"name": "{ user { name } }"}"#.into())).to(be_false());
+        expect!(is_graphql_request(&s!("text/plain"), &r#"{"query": "{ user { name } }"}"#.into())).to(be_false());
+    }
+
+    #[test]
+    fn match_graphql_ignores_formatting_and_field_order() {
+        let mut mismatches = vec![];
+        let expected = r#"{"query": "query GetUser($id: ID!) { user(id: $id) { name email } }", "operationName": "GetUser", "variables": {"id": "100"}}"#;
+        let actual = r#"{"operationName": "GetUser", "variables": {"id": "100"}, "query": "query GetUser($id: ID!) {\n  user(id: $id) {\n    email\n    name\n  }\n}\n"}"#;
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn match_graphql_with_different_queries() {
+        let mut mismatches = vec![];
+        let expected = r#"{"query": "{ user { name email } }"}"#;
+        let actual = r#"{"query": "{ user { name } }"}"#;
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_path(&mismatches[0])).to(be_equal_to(s!("$.query")));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(
+            s!("Expected GraphQL query 'query { user { email name } }' but received 'query { user { name } }'")));
+    }
+
+    #[test]
+    fn match_graphql_with_invalid_actual_query() {
+        let mut mismatches = vec![];
+        let expected = r#"{"query": "{ user { name } }"}"#;
+        let actual = r#"{"query": "{ user { name }"}"#;
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(
+            s!("Failed to parse the actual GraphQL query: Unexpected end of the query")));
+    }
+
+    #[test]
+    fn match_graphql_compares_the_operation_name_and_variables() {
+        let mut mismatches = vec![];
+        let expected = r#"{"query": "query GetUser($id: ID!) { user(id: $id) { name } }", "operationName": "GetUser", "variables": {"id": "100", "full": true}}"#;
+        let actual = r#"{"query": "query GetUser($id: ID!) { user(id: $id) { name } }", "operationName": "GetUsers", "variables": {"id": "200", "full": true}}"#;
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$.variables.id" => [ MatchingRule::Regex(s!("\\d+")) ]
+            }
+        });
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_path(&mismatches[0])).to(be_equal_to(s!("$.operationName")));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected 'GetUser' to be equal to 'GetUsers'")));
+    }
+
+    #[test]
+    fn match_graphql_with_unexpected_variables() {
+        let mut mismatches = vec![];
+        let expected = r#"{"query": "{ user { name } }"}"#;
+        let actual = r#"{"query": "{ user { name } }", "variables": {"id": "1"}, "operationName": null}"#;
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Unexpected variables={\"id\":\"1\"} received")));
+    }
+
+    #[test]
+    fn match_graphql_with_a_raw_query_body() {
+        let mut mismatches = vec![];
+        let expected = "query { user { name email } }";
+        let actual = "{\n  user {\n    email\n    name\n  }\n}";
+        match_graphql(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+    }
+}
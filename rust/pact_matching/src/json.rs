@@ -2,16 +2,16 @@
 
 use serde_json;
 use serde_json::value::Value;
-use super::Mismatch;
-use super::DiffConfig;
+use super::{Mismatch, MismatchDetails, MismatchKind};
+use super::{DiffConfig, MatchingConfig};
 use difference::*;
 use ansi_term::Colour::*;
 use std::str::FromStr;
 use models::matchingrules::*;
 use matchers::*;
-use onig::Regex;
+use explain::{self, ExplainEntry};
 
-fn type_of(json: &Value) -> String {
+pub(crate) fn type_of(json: &Value) -> String {
     match json {
         &Value::Object(_) => s!("Map"),
         &Value::Array(_) => s!("List"),
@@ -30,7 +30,7 @@ impl Matches<Value> for Value {
     fn matches(&self, actual: &Value, matcher: &MatchingRule) -> Result<(), String> {
         let result = match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 let actual_str = match actual {
                   &Value::String(ref s) => s.clone(),
@@ -151,7 +151,7 @@ impl Matches<Vec<Value>> for Vec<Value> {
     fn matches(&self, actual: &Vec<Value>, matcher: &MatchingRule) -> Result<(), String> {
         let result = match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&Value::Array(actual.clone()).to_string()) {
                   Ok(())
@@ -204,6 +204,13 @@ impl Matches<Vec<Value>> for Vec<Value> {
 /// Matches the expected JSON to the actual, and populates the mismatches vector with any differences
 pub fn match_json(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
     mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    match_json_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the expected JSON to the actual, and populates the mismatches vector with any differences.
+/// Stops comparing the bodies once the mismatch limit of the matching configuration is reached.
+pub fn match_json_with_config(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
     let expected_json = serde_json::from_slice(expected);
     let actual_json = serde_json::from_slice(actual);
 
@@ -212,7 +219,8 @@ pub fn match_json(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
             Err(e) => {
                 mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
                     actual: Some(actual.clone().into()),
-                    mismatch: format!("Failed to parse the expected body: '{}'", e)});
+                    mismatch: format!("Failed to parse the expected body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
             },
             _ => ()
         }
@@ -220,17 +228,25 @@ pub fn match_json(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
             Err(e) => {
                 mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
                     actual: Some(actual.clone().into()),
-                    mismatch: format!("Failed to parse the actual body: '{}'", e)});
+                    mismatch: format!("Failed to parse the actual body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
             },
             _ => ()
         }
     } else {
         compare(&vec![s!("$")], &expected_json.unwrap(), &actual_json.unwrap(), &config,
-            mismatches, matchers);
+            matching_config, mismatches, matchers);
     }
 }
 
-fn walk_json(json: &Value, path: &mut dyn Iterator<Item=&str>) -> Option<Value> {
+/// Details for a body that could not be parsed, with the bodies as strings
+pub(crate) fn parse_error_details(expected: &[u8], actual: &[u8]) -> MismatchDetails {
+    MismatchDetails::new(MismatchKind::ParseError, &[],
+        Value::String(String::from_utf8_lossy(expected).to_string()),
+        Value::String(String::from_utf8_lossy(actual).to_string()))
+}
+
+pub(crate) fn walk_json(json: &Value, path: &mut dyn Iterator<Item=&str>) -> Option<Value> {
     match path.next() {
         Some(p) => match json {
             &Value::Object(_) => json.get(p).map(|json| json.clone()),
@@ -269,37 +285,81 @@ pub fn display_diff(expected: &String, actual: &String, path: &String) -> String
     output
 }
 
-fn compare(path: &Vec<String>, expected: &Value, actual: &Value, config: &DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+fn child_path(path: &Vec<String>, key: &str) -> Vec<String> {
+    let mut p = path.clone();
+    p.push(key.to_string());
+    p
+}
+
+/// Details for a failed matching rule. If no matcher was found for the path, the kind of the
+/// mismatch is `NoMatcher`.
+pub(crate) fn rule_mismatch_details(rule: Option<MatchingRule>, path: &[String], expected: &Value,
+    actual: &Value) -> MismatchDetails {
+    let kind = match rule {
+        Some(rule) => MismatchKind::RuleMismatch(rule),
+        None => MismatchKind::NoMatcher
+    };
+    MismatchDetails::new(kind, path, expected.clone(), actual.clone())
+}
+
+pub(crate) fn compare(path: &Vec<String>, expected: &Value, actual: &Value, config: &DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    compare_with_trace(path, expected, actual, config, matching_config, mismatches, matchers, &mut None)
+}
+
+/// Compares the expected JSON to the actual. If a trace is given, an entry is added to it for each
+/// path that is compared, with the rules that applied to the path and the mismatches raised for it.
+pub(crate) fn compare_with_trace(path: &Vec<String>, expected: &Value, actual: &Value, config: &DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver,
+    trace: &mut Option<&mut Vec<ExplainEntry>>) {
+    if matching_config.limit_reached(mismatches.len()) {
+        return;
+    }
     debug!("Comparing path {}", path.join("."));
+    let entry = match *trace {
+        Some(ref mut entries) => Some(explain::start_entry(path, matchers, entries)),
+        None => None
+    };
+    let start = mismatches.len();
     match (expected, actual) {
-        (&Value::Object(ref emap), &Value::Object(ref amap)) => compare_maps(path, emap, amap, config, mismatches, matchers),
+        (&Value::Object(ref emap), &Value::Object(ref amap)) => compare_maps(path, emap, amap, config,
+            matching_config, mismatches, matchers, trace),
         (&Value::Object(_), _) => {
             mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
                 expected: Some(value_of(expected).into()),
                 actual: Some(value_of(actual).into()),
                 mismatch: format!("Type mismatch: Expected {} {} but received {} {}",
-                    type_of(expected), expected, type_of(actual), actual)});
+                    type_of(expected), expected, type_of(actual), actual),
+                details: Some(MismatchDetails::new(MismatchKind::TypeMismatch, path, expected.clone(), actual.clone()))});
         },
-        (&Value::Array(ref elist), &Value::Array(ref alist)) => compare_lists(path, elist, alist, config, mismatches, matchers),
+        (&Value::Array(ref elist), &Value::Array(ref alist)) => compare_lists(path, elist, alist, config,
+            matching_config, mismatches, matchers, trace),
         (&Value::Array(_), _) => {
             mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
                 expected: Some(value_of(expected).into()),
                 actual: Some(value_of(actual).into()),
                 mismatch: format!("Type mismatch: Expected {} {} but received {} {}",
-                    type_of(expected), value_of(expected), type_of(actual), value_of(actual))});
+                    type_of(expected), value_of(expected), type_of(actual), value_of(actual)),
+                details: Some(MismatchDetails::new(MismatchKind::TypeMismatch, path, expected.clone(), actual.clone()))});
         },
         (_, _) => compare_values(path, expected, actual, mismatches, matchers)
     }
+    if let Some(index) = entry {
+        if let Some(ref mut entries) = *trace {
+            explain::finish_entry(&mut entries[index], &mismatches[start..]);
+        }
+    }
 }
 
 fn compare_maps(path: &Vec<String>, expected: &serde_json::Map<String, Value>, actual: &serde_json::Map<String, Value>,
-    config: &DiffConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    config: &DiffConfig, matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver,
+    trace: &mut Option<&mut Vec<ExplainEntry>>) {
     if expected.is_empty() && !actual.is_empty() {
       mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
           expected: Some(value_of(&json!(expected)).into()),
           actual: Some(value_of(&json!(actual)).into()),
-          mismatch: format!("Expected an empty Map but received {}", value_of(&json!(actual)))});
+          mismatch: format!("Expected an empty Map but received {}", value_of(&json!(actual))),
+          details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, path, json!(expected), json!(actual)))});
     } else {
         match config {
             &DiffConfig::AllowUnexpectedKeys if expected.len() > actual.len() => {
@@ -307,14 +367,16 @@ fn compare_maps(path: &Vec<String>, expected: &serde_json::Map<String, Value>, a
                     expected: Some(value_of(&json!(expected)).into()),
                     actual: Some(value_of(&json!(&actual)).into()),
                     mismatch: format!("Expected a Map with at least {} elements but received {} elements",
-                    expected.len(), actual.len())});
+                    expected.len(), actual.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path, json!(expected), json!(actual)))});
             },
             &DiffConfig::NoUnexpectedKeys if expected.len() != actual.len() => {
                 mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
                     expected: Some(value_of(&json!(expected)).into()),
                     actual: Some(value_of(&json!(&actual)).into()),
                     mismatch: format!("Expected a Map with {} elements but received {} elements",
-                    expected.len(), actual.len())});
+                    expected.len(), actual.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path, json!(expected), json!(actual)))});
             },
             _ => ()
         }
@@ -323,25 +385,34 @@ fn compare_maps(path: &Vec<String>, expected: &serde_json::Map<String, Value>, a
         p.push(s!("any"));
         if matchers.wildcard_matcher_is_defined("body", &p) {
             for (key, value) in actual.iter() {
+                if matching_config.limit_reached(mismatches.len()) {
+                    break;
+                }
                 let mut p = path.to_vec();
                 p.push(key.clone());
                 if expected.contains_key(key) {
-                    compare(&p, &expected[key], value, config, mismatches, matchers);
+                    compare_with_trace(&p, &expected[key], value, config, matching_config, mismatches, matchers, trace);
                 } else if !expected.is_empty() {
-                    compare(&p, &expected.values().next().unwrap(), value, config, mismatches, matchers);
+                    compare_with_trace(&p, &expected.values().next().unwrap(), value, config, matching_config, mismatches,
+                        matchers, trace);
                 }
             }
         } else {
             for (key, value) in expected.iter() {
+                if matching_config.limit_reached(mismatches.len()) {
+                    break;
+                }
                 if actual.contains_key(key) {
                     let mut p = path.to_vec();
                     p.push(key.clone());
-                    compare(&p, value, &actual[key], config, mismatches, matchers);
+                    compare_with_trace(&p, value, &actual[key], config, matching_config, mismatches, matchers, trace);
                 } else {
                     mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
                         expected: Some(value_of(&json!(expected)).into()),
                         actual: Some(value_of(&json!(&actual)).into()),
-                        mismatch: format!("Expected entry {}={} but was missing", key, value_of(value))});
+                        mismatch: format!("Expected entry {}={} but was missing", key, value_of(value)),
+                        details: Some(MismatchDetails::new(MismatchKind::MissingValue, &child_path(path, key),
+                            value.clone(), Value::Null))});
                 }
             }
         }
@@ -349,20 +420,22 @@ fn compare_maps(path: &Vec<String>, expected: &serde_json::Map<String, Value>, a
 }
 
 fn compare_lists(path: &Vec<String>, expected: &Vec<Value>, actual: &Vec<Value>, config: &DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver,
+    trace: &mut Option<&mut Vec<ExplainEntry>>) {
     let spath = path.join(".");
     if matchers.matcher_is_defined("body", &path) {
         debug!("compare_lists: matcher defined for path '{}'", spath);
         let expected_json = Value::Array(expected.clone());
         let actual_json = Value::Array(actual.clone());
-        match match_values("body", path, matchers.clone(), &expected_json, &actual_json) {
-            Err(messages) => {
-              for message in messages {
+        match match_values_with_rules("body", path, matchers, &expected_json, &actual_json) {
+            Err(failures) => {
+              for (rule, message) in failures {
                 mismatches.push(Mismatch::BodyMismatch {
                   path: path.join("."),
                   expected: Some(expected_json.to_string().into()),
                   actual: Some(actual_json.to_string().into()),
-                  mismatch: message.clone()
+                  mismatch: message.clone(),
+                  details: Some(rule_mismatch_details(rule, path, &expected_json, &actual_json))
                 })
               }
             },
@@ -371,60 +444,68 @@ fn compare_lists(path: &Vec<String>, expected: &Vec<Value>, actual: &Vec<Value>,
         let expected_example = expected.first().unwrap().clone();
         let mut expected_list = Vec::new();
         expected_list.resize(actual.len(), expected_example);
-        compare_list_content(path, &expected_list, actual, config, mismatches, matchers);
+        compare_list_content(path, &expected_list, actual, config, matching_config, mismatches, matchers, trace);
     } else {
         if expected.is_empty() && !actual.is_empty() {
             mismatches.push(Mismatch::BodyMismatch { path: spath,
                 expected: Some(value_of(&json!(expected)).into()),
                 actual: Some(value_of(&json!(actual)).into()),
-                mismatch: format!("Expected an empty List but received {}", value_of(&json!(actual)))});
+                mismatch: format!("Expected an empty List but received {}", value_of(&json!(actual))),
+                details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, path, json!(expected), json!(actual)))});
         } else {
-            compare_list_content(path, expected, actual, config, mismatches, matchers);
+            compare_list_content(path, expected, actual, config, matching_config, mismatches, matchers, trace);
             if expected.len() != actual.len() {
                 mismatches.push(Mismatch::BodyMismatch { path: spath,
                     expected: Some(value_of(&json!(expected)).into()),
                     actual: Some(value_of(&json!(actual)).into()),
                     mismatch: format!("Expected a List with {} elements but received {} elements",
-                        expected.len(), actual.len())});
+                        expected.len(), actual.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path, json!(expected), json!(actual)))});
             }
         }
     }
 }
 
 fn compare_list_content(path: &Vec<String>, expected: &Vec<Value>, actual: &Vec<Value>, config: &DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver,
+    trace: &mut Option<&mut Vec<ExplainEntry>>) {
     for (index, value) in expected.iter().enumerate() {
+      if matching_config.limit_reached(mismatches.len()) {
+          break;
+      }
       let ps = index.to_string();
       debug!("Comparing list item {} with value '{:?}' to '{:?}'", index, actual.get(index), value);
       let mut p = path.to_vec();
       p.push(ps);
       if index < actual.len() {
-          compare(&p, value, &actual[index], config, mismatches, matchers);
+          compare_with_trace(&p, value, &actual[index], config, matching_config, mismatches, matchers, trace);
       } else if !matchers.matcher_is_defined("body", &p) {
           mismatches.push(Mismatch::BodyMismatch { path: path.join("."),
               expected: Some(value_of(&json!(expected)).into()),
               actual: Some(value_of(&json!(actual)).into()),
-              mismatch: format!("Expected {} but was missing", value_of(value))});
+              mismatch: format!("Expected {} but was missing", value_of(value)),
+              details: Some(MismatchDetails::new(MismatchKind::MissingValue, &p, value.clone(), Value::Null))});
       }
     }
 }
 
 fn compare_values(path: &Vec<String>, expected: &Value, actual: &Value, mismatches: &mut Vec<super::Mismatch>,
-    matchers: &MatchingRules) {
+    matchers: &dyn RuleResolver) {
     let matcher_result = if matchers.matcher_is_defined("body", &path) {
-        match_values("body", path, matchers.clone(), expected, actual)
+        match_values_with_rules("body", path, matchers, expected, actual)
     } else {
-        expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![err])
+        expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![(Some(MatchingRule::Equality), err)])
     };
     debug!("Comparing '{:?}' to '{:?}' at path '{}' -> {:?}", expected, actual, path.join("."), matcher_result);
     match matcher_result {
-        Err(messages) => {
-          for message in messages {
+        Err(failures) => {
+          for (rule, message) in failures {
             mismatches.push(Mismatch::BodyMismatch {
               path: path.join("."),
               expected: Some(format!("{}", expected).into()),
               actual: Some(format!("{}", actual).into()),
-              mismatch: message.clone()
+              mismatch: message.clone(),
+              details: Some(rule_mismatch_details(rule, path, expected, actual))
             })
           }
         },
@@ -438,6 +519,7 @@ mod tests {
     use expectest::prelude::*;
     use Mismatch;
     use DiffConfig;
+    use {MismatchDetails, MismatchKind};
 
     #[test]
     fn match_json_handles_invalid_expected_json() {
@@ -448,7 +530,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("")}));
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
     }
 
     #[test]
@@ -460,12 +542,12 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("Type mismatch: Expected List [{}] but received Map {}")}));
+            actual: Some(actual.into()), mismatch: s!("Type mismatch: Expected List [{}] but received Map {}"), details: None}));
     }
 
     fn mismatch_message(mismatch: &Mismatch) -> String {
         match mismatch {
-            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m } => m.clone(),
+            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m, .. } => m.clone(),
             _ => s!("")
         }
     }
@@ -479,7 +561,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("")}));
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Type mismatch: Expected Map {} but received List []")));
     }
 
@@ -492,7 +574,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("")}));
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Type mismatch: Expected List [{}] but received Map {}")));
     }
 
@@ -507,7 +589,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(val1.into()),
-            actual: Some(val2.into()), mismatch: s!("")}));
+            actual: Some(val2.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'string value' to be equal to 'other value'")));
     }
 
@@ -522,7 +604,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(val1.into()),
-            actual: Some(val2.into()), mismatch: s!("")}));
+            actual: Some(val2.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '100' to be equal to '200'")));
     }
 
@@ -537,7 +619,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(val1.into()),
-            actual: Some(val2.into()), mismatch: s!("")}));
+            actual: Some(val2.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '100.01' to be equal to '100.02'")));
     }
 
@@ -552,7 +634,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(val1.into()),
-            actual: Some(val2.into()), mismatch: s!("")}));
+            actual: Some(val2.into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'true' to be equal to 'false'")));
     }
 
@@ -567,7 +649,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(val1.clone().into()),
-            actual: Some(val2.clone().into()), mismatch: s!("")}));
+            actual: Some(val2.clone().into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'null' to be equal to '33'")));
     }
 
@@ -601,7 +683,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.1"),
-            expected: Some("22".into()), actual: Some("44".into()), mismatch: s!("")}));
+            expected: Some("22".into()), actual: Some("44".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '22' to be equal to '44'")));
         mismatches.clear();
 
@@ -610,7 +692,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("[11,44,33]".into()),
-            actual: Some("[11,44,33,66]".into()), mismatch: s!("")}));
+            actual: Some("[11,44,33,66]".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a List with 3 elements but received 4 elements")));
         mismatches.clear();
 
@@ -619,12 +701,12 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.1"),
             expected: Some("22".into()),
-            actual: Some("44".into()), mismatch: s!("")}));
+            actual: Some("44".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '22' to be equal to '44'")));
         let mismatch = mismatches[1].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("[11,22,33]".into()),
-            actual: Some("[11,44,33,66]".into()), mismatch: s!("")}));
+            actual: Some("[11,44,33,66]".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a List with 3 elements but received 4 elements")));
         mismatches.clear();
 
@@ -672,7 +754,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.b"),
-            expected: Some("2".into()), actual: Some("3".into()), mismatch: s!("")}));
+            expected: Some("2".into()), actual: Some("3".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '2' to be equal to '3'")));
         mismatches.clear();
 
@@ -683,7 +765,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("{\"a\":1,\"b\":2}".into()),
-            actual: Some("{\"a\":1,\"b\":2,\"c\":3}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\":1,\"b\":2,\"c\":3}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a Map with 2 elements but received 3 elements")));
         mismatches.clear();
 
@@ -692,7 +774,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.b"),
             expected: Some("3".into()),
-            actual: Some("2".into()), mismatch: s!("")}));
+            actual: Some("2".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '3' to be equal to '2'")));
         mismatches.clear();
 
@@ -701,12 +783,12 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("{\"a\":1,\"b\":3}".into()),
-            actual: Some("{\"a\":1,\"b\":2,\"c\":3}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\":1,\"b\":2,\"c\":3}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a Map with 2 elements but received 3 elements")));
         let mismatch = mismatches[1].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.b"),
             expected: Some("3".into()),
-            actual: Some("2".into()), mismatch: s!("")}));
+            actual: Some("2".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '3' to be equal to '2'")));
         mismatches.clear();
 
@@ -715,12 +797,12 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("{\"a\":1,\"b\":2,\"c\":3}".into()),
-            actual: Some("{\"a\":1,\"b\":2}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\":1,\"b\":2}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a Map with at least 3 elements but received 2 elements")));
         let mismatch = mismatches[1].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"),
             expected: Some("{\"a\":1,\"b\":2,\"c\":3}".into()),
-            actual: Some("{\"a\":1,\"b\":2}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\":1,\"b\":2}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected entry c=3 but was missing")));
         mismatches.clear();
 
@@ -887,4 +969,57 @@ mod tests {
         mismatches.clear();
     }
 
+    fn mismatch_details(mismatch: &Mismatch) -> Option<MismatchDetails> {
+        match mismatch {
+            &Mismatch::BodyMismatch { ref details, .. } => details.clone(),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn match_json_records_the_details_of_each_mismatch() {
+        let mut mismatches = vec![];
+        let expected = s!(r#"{"id": 100, "name": "Fred", "tags": ["a"], "a/b": {"c": 1}}"#);
+        let actual = s!(r#"{"id": "abc", "tags": "a", "a/b": {"c": 2}, "other": true}"#);
+        match_json(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$.id" => [ MatchingRule::Integer ]
+            }
+        });
+        let details = mismatches.iter().filter_map(mismatch_details).collect::<Vec<MismatchDetails>>();
+        expect!(details).to(be_equal_to(vec![
+            MismatchDetails { kind: MismatchKind::RuleMismatch(MatchingRule::Equality), pointer: s!("/a~1b/c"),
+                expected: json!(1), actual: json!(2) },
+            MismatchDetails { kind: MismatchKind::RuleMismatch(MatchingRule::Integer), pointer: s!("/id"),
+                expected: json!(100), actual: json!("abc") },
+            MismatchDetails { kind: MismatchKind::MissingValue, pointer: s!("/name"),
+                expected: json!("Fred"), actual: Value::Null },
+            MismatchDetails { kind: MismatchKind::TypeMismatch, pointer: s!("/tags"),
+                expected: json!(["a"]), actual: json!("a") }
+        ]));
+    }
+
+    #[test]
+    fn rule_mismatch_details_test() {
+        let path = vec![s!("$"), s!("id")];
+        expect!(rule_mismatch_details(Some(MatchingRule::Integer), &path, &json!(100), &json!("abc"))).to(be_equal_to(
+            MismatchDetails { kind: MismatchKind::RuleMismatch(MatchingRule::Integer), pointer: s!("/id"),
+                expected: json!(100), actual: json!("abc") }));
+        expect!(rule_mismatch_details(None, &path, &json!(100), &json!("abc")).kind).to(be_equal_to(MismatchKind::NoMatcher));
+        expect!(MismatchKind::NoMatcher.name()).to(be_equal_to(s!("NoMatcher")));
+    }
+
+    #[test]
+    fn match_json_records_parse_errors_and_list_lengths() {
+        let mut mismatches = vec![];
+        match_json(&"[1, 2]".into(), &"[1".into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatch_details(&mismatches[0]).map(|details| details.kind)).to(be_some().value(MismatchKind::ParseError));
+
+        mismatches.clear();
+        match_json(&"[1, 2]".into(), &"[1, 2, 3]".into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_details(&mismatches[0])).to(be_some().value(MismatchDetails {
+            kind: MismatchKind::LengthMismatch, pointer: s!(""), expected: json!([1, 2]), actual: json!([1, 2, 3]) }));
+    }
+
 }
@@ -93,6 +93,25 @@
 //! matcher
 //! 2. Otherwise compare the values using equality.
 //!
+//! #### YAML body matching rules
+//!
+//! YAML bodies (with a content type of `application/yaml`, `application/x-yaml`, `text/yaml`, etc.) are parsed into the
+//! same structure as JSON bodies, and then compared using the JSON body matching rules above. Matching rule paths
+//! are the same as for JSON bodies, and any differences are displayed in YAML form.
+//!
+//! #### GraphQL body matching rules
+//!
+//! JSON bodies where the expected body has a `query` attribute containing a valid GraphQL document (i.e.
+//! `{"query": "query GetUser($id: ID!) { user(id: $id) { name } }", "variables": {"id": "100"}}`), as well as bodies
+//! with a content type of `application/graphql`, are compared as GraphQL requests. JSON bodies where the `query`
+//! attribute is not a GraphQL document are compared as JSON.
+//!
+//! 1. The query documents are parsed and normalised, so that whitespace, commas, comments and the order of the fields,
+//! arguments and variable definitions are ignored. The normalised queries must then be equal. If there is a matcher
+//! defined for the path `$.query`, default to that matcher instead.
+//! 2. The remaining attributes (`operationName`, `variables`, etc.) are compared using the JSON body matching rules
+//! above, with paths relative to the request (i.e. `$.variables.id`).
+//!
 //! #### XML body matching rules
 //!
 //! Bodies consist of a root element, Elements (Lists with children), Attributes (Maps) and values (Strings).
@@ -115,6 +134,13 @@
 //! to that matcher on the elements name or children.
 //! 2. Otherwise the elements match if they have the same name.
 //!
+//! Element and attribute names are namespace aware: two names are the same if they have the same
+//! namespace URI and local name, regardless of the prefixes used to bind the namespace. In matching
+//! paths and mismatch messages, namespaced nodes are referred to by their qualified name using the
+//! prefix declared in the expected body. As `:` is not allowed in path identifiers, qualified names
+//! must use bracket notation in matching rules (i.e. `$['soap:Envelope']['soap:Body']` or
+//! `$['ns:item']['@ns:id']`).
+//!
 //! Then, if there are no mismatches:
 //!
 //! 1. compare the attributes of the element
@@ -151,6 +177,12 @@
 //!
 //! Then, for each expected and actual element pair, compare them using the rules for comparing elements.
 //!
+//! If there is a type matcher (`type`, `min`, `max`) defined for the path to a child element name (i.e. `$.items.item`),
+//! the first expected child with that name is used as a template for all the actual children with the same name, in
+//! the same way as a type matcher on a JSON array. The number of actual children with that name must then satisfy any
+//! `min` or `max` values, and each of them is compared to the template. The remaining child elements are compared as
+//! above.
+//!
 //! ##### For comparing text nodes
 //!
 //! Text nodes are combined into a single string and then compared as values.
@@ -166,6 +198,23 @@
 //! matcher
 //! 2. Otherwise compare the values using equality.
 //!
+//! #### CSV body matching rules
+//!
+//! CSV bodies (with a content type of `text/csv` or `application/csv`) are compared row by row. The first row of the
+//! body is treated as the header row, and values in the remaining rows are compared by column name, so the order of the
+//! columns does not matter.
+//!
+//! 1. Each expected column must be present in the actual header row. If we don't allow unexpected keys, the actual header
+//! row must not have any additional columns.
+//! 2. If there is a type matcher (`type`, `min`, `max`) defined for the root path `$`, the first expected row is used as a
+//! template for all the actual rows, and the number of actual rows must satisfy any `min` or `max` values.
+//! 3. Otherwise, if we allow unexpected keys the actual body must have at least as many rows as the expected body, and if
+//! we don't it must have the same number of rows. Each expected row is compared to the actual row at the same index.
+//!
+//! Values are compared using the rules for comparing values. Matching rule paths consist of the row index (excluding the
+//! header row) followed by either the column name or the index of the column in the expected header row (i.e. `$[*].name`
+//! or `$[0][2]`). Mismatches are reported with the path `$.<row>.<column name>`.
+//!
 //! ### Matching Paths
 //!
 //! Paths are matched by the following:
@@ -309,6 +358,10 @@
 //!
 //! So for the item with id 102, the matcher with path `$.item1.level[1].id` and weighting 32 will be selected.
 //!
+//! If a path with a recursive descent (like `$..id`) matches the whole path to the item, the paths that match the whole
+//! path are selected over paths that only match a parent of the item (like `$.item1.level`), so the recursive descent is
+//! not hidden by a matcher on a parent.
+//!
 //! ## Supported matchers
 //!
 //! The following matchers are supported:
@@ -346,6 +399,12 @@ extern crate chrono_tz;
 extern crate rand_regex;
 extern crate regex_syntax;
 extern crate reqwest;
+extern crate serde_yaml;
+extern crate csv as csv_crate;
+extern crate fs2;
+extern crate zip;
+extern crate tar;
+extern crate flate2;
 
 /// Simple macro to convert a string slice to a `String` struct.
 #[macro_export]
@@ -365,6 +424,16 @@ mod time_utils;
 mod matchers;
 pub mod json;
 mod xml;
+pub mod yaml;
+mod csv;
+pub mod graphql;
+pub mod validation;
+pub mod pact_diff;
+pub mod conversion;
+pub mod synthesis;
+pub mod explain;
+pub mod diff;
+pub mod plan;
 
 use models::HttpPart;
 use models::matchingrules::*;
@@ -378,15 +447,95 @@ fn strip_whitespace<'a, T: FromIterator<&'a str>>(val: &'a String, split_by: &'a
 
 lazy_static! {
     static ref BODY_MATCHERS: [(Regex, fn(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
-            mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules)); 3] = [
-        (Regex::new("application/.*json").unwrap(), json::match_json),
-        (Regex::new("application/json.*").unwrap(), json::match_json),
-        (Regex::new("application/.*xml").unwrap(), xml::match_xml)
+            matching_config: &MatchingConfig, mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver)); 5] = [
+        (Regex::new("application/.*json").unwrap(), json::match_json_with_config),
+        (Regex::new("application/json.*").unwrap(), json::match_json_with_config),
+        (Regex::new("application/.*xml").unwrap(), xml::match_xml_with_config),
+        (Regex::new("(application|text)/.*yaml").unwrap(), yaml::match_yaml_with_config),
+        (Regex::new("(application|text)/csv").unwrap(), csv::match_csv_with_config)
     ];
 }
 
 static PARAMETERISED_HEADER_TYPES: [&'static str; 2] = ["accept", "content-type"];
 
+/// Enum that defines the kind of body mismatch that occurred, so tools can act on a mismatch
+/// without having to parse the description.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MismatchKind {
+    /// A matching rule failed
+    RuleMismatch(MatchingRule),
+    /// A matcher was expected for the path, but none was found
+    NoMatcher,
+    /// The actual value was a different type of value (i.e. a List instead of a Map)
+    TypeMismatch,
+    /// An expected value (a map entry, list item, attribute, column or row) was missing
+    MissingValue,
+    /// A value was received that was not expected
+    UnexpectedValue,
+    /// The number of items (map entries, list items, attributes or rows) was different
+    LengthMismatch,
+    /// The body could not be parsed
+    ParseError
+}
+
+impl MismatchKind {
+    /// Returns the name of the mismatch kind
+    pub fn name(&self) -> String {
+        match self {
+            &MismatchKind::RuleMismatch(_) => s!("RuleMismatch"),
+            &MismatchKind::NoMatcher => s!("NoMatcher"),
+            &MismatchKind::TypeMismatch => s!("TypeMismatch"),
+            &MismatchKind::MissingValue => s!("MissingValue"),
+            &MismatchKind::UnexpectedValue => s!("UnexpectedValue"),
+            &MismatchKind::LengthMismatch => s!("LengthMismatch"),
+            &MismatchKind::ParseError => s!("ParseError")
+        }
+    }
+}
+
+/// Machine readable details of a body mismatch
+#[derive(Debug, Clone, PartialEq)]
+pub struct MismatchDetails {
+    /// Kind of mismatch
+    pub kind: MismatchKind,
+    /// JSON Pointer (RFC 6901) to the value in the body where the mismatch occurred
+    pub pointer: String,
+    /// Expected value
+    pub expected: Value,
+    /// Actual value
+    pub actual: Value
+}
+
+impl MismatchDetails {
+    /// Creates the details of a mismatch at the path (i.e. `["$", "items", "0"]`)
+    pub fn new(kind: MismatchKind, path: &[String], expected: Value, actual: Value) -> MismatchDetails {
+        MismatchDetails { kind, pointer: json_pointer(path), expected, actual }
+    }
+
+    /// Converts the details to a `Value` struct
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "kind": self.kind.name(),
+            "pointer": self.pointer,
+            "expected": self.expected,
+            "actual": self.actual
+        });
+        if let MismatchKind::RuleMismatch(ref rule) = self.kind {
+            json["rule"] = rule.to_json();
+        }
+        json
+    }
+}
+
+/// Converts a path (i.e. `["$", "items", "0"]`) to a JSON Pointer (i.e. `/items/0`). The root
+/// marker is dropped, and `~` and `/` in the path segments are escaped.
+pub fn json_pointer(path: &[String]) -> String {
+    path.iter()
+        .skip_while(|segment| segment.as_str() == "$")
+        .map(|segment| format!("/{}", segment.replace("~", "~0").replace("/", "~1")))
+        .collect()
+}
+
 /// Enum that defines the different types of mismatches that can occur.
 #[derive(Debug, Clone)]
 pub enum Mismatch {
@@ -451,7 +600,16 @@ pub enum Mismatch {
         /// actual value
         actual: Option<Vec<u8>>,
         /// description of the mismatch
-        mismatch: String
+        mismatch: String,
+        /// machine readable details of the mismatch, if they are known
+        details: Option<MismatchDetails>
+    },
+    /// Summary of the mismatches that were not reported because the mismatch limit was reached
+    TruncatedMismatches {
+        /// maximum number of mismatches that were reported
+        limit: usize,
+        /// number of mismatches found past the limit before matching stopped
+        count: usize
     }
 }
 
@@ -506,8 +664,8 @@ impl Mismatch {
                     s!("actual") : json!(a)
                 })
             },
-            &Mismatch::BodyMismatch { path: ref p, expected: ref e, actual: ref a, mismatch: ref m } => {
-                 json!({
+            &Mismatch::BodyMismatch { path: ref p, expected: ref e, actual: ref a, mismatch: ref m, ref details } => {
+                 let mut json = json!({
                     s!("type") : json!("BodyMismatch"),
                     s!("path") : json!(p),
                     s!("expected") : match e {
@@ -519,6 +677,18 @@ impl Mismatch {
                         &None => serde_json::Value::Null
                     },
                     s!("mismatch") : json!(m)
+                });
+                if let &Some(ref details) = details {
+                    json[s!("details")] = details.to_json();
+                }
+                json
+            },
+            &Mismatch::TruncatedMismatches { limit, count } => {
+                json!({
+                    s!("type") : json!("TruncatedMismatches"),
+                    s!("limit") : json!(limit),
+                    s!("count") : json!(count),
+                    s!("mismatch") : json!(self.description())
                 })
             }
         }
@@ -533,7 +703,8 @@ impl Mismatch {
             Mismatch::QueryMismatch { .. } => s!("QueryMismatch"),
             Mismatch::HeaderMismatch { .. } => s!("HeaderMismatch"),
             Mismatch::BodyTypeMismatch { .. } => s!("BodyTypeMismatch"),
-            Mismatch::BodyMismatch { .. } => s!("BodyMismatch")
+            Mismatch::BodyMismatch { .. } => s!("BodyMismatch"),
+            Mismatch::TruncatedMismatches { .. } => s!("TruncatedMismatches")
         }
     }
 
@@ -546,7 +717,8 @@ impl Mismatch {
             Mismatch::QueryMismatch { ref parameter, expected: ref e, .. } => format!("includes parameter '{}' with value '{}'", parameter, e),
             Mismatch::HeaderMismatch { ref key, expected: ref e, .. } => format!("includes header '{}' with value '{}'", key, e),
             Mismatch::BodyTypeMismatch { .. } => s!("has a matching body"),
-            Mismatch::BodyMismatch { .. } => s!("has a matching body")
+            Mismatch::BodyMismatch { .. } => s!("has a matching body"),
+            Mismatch::TruncatedMismatches { .. } => s!("has no further mismatches")
         }
     }
 
@@ -559,11 +731,15 @@ impl Mismatch {
             Mismatch::QueryMismatch { ref mismatch, .. } => mismatch.clone(),
             Mismatch::HeaderMismatch { ref mismatch, .. } => mismatch.clone(),
             Mismatch::BodyTypeMismatch {  expected: ref e, actual: ref a } => format!("expected '{}' body but was '{}'", e, a),
-            Mismatch::BodyMismatch { ref path, ref mismatch, .. } => format!("{} -> {}", path, mismatch)
+            Mismatch::BodyMismatch { ref path, ref mismatch, .. } => format!("{} -> {}", path, mismatch),
+            Mismatch::TruncatedMismatches { limit, count } => format!(
+                "Only the first {} mismatches were reported, at least {} more were not", limit, count)
         }
     }
 
-    /// Returns a formatted string with ansi escape codes for this mismatch
+    /// Returns a formatted string with ansi escape codes for this mismatch. Body mismatches where
+    /// the expected and actual values are XML documents or span multiple lines include a unified
+    /// diff of the values.
     pub fn ansi_description(&self) -> String {
         match *self {
             Mismatch::MethodMismatch { expected: ref e, actual: ref a } => format!("expected {} but was {}", Red.paint(e.clone()), Green.paint(a.clone())),
@@ -574,7 +750,16 @@ impl Mismatch {
             Mismatch::HeaderMismatch { expected: ref e, actual: ref a, key: ref k, .. } => format!("Expected header '{}' to have value '{}' but was '{}'",
                 Style::new().bold().paint(k.clone()), Red.paint(e.to_string()), Green.paint(a.to_string())),
             Mismatch::BodyTypeMismatch {  expected: ref e, actual: ref a } => format!("expected '{}' body but was '{}'", Red.paint(e.clone()), Green.paint(a.clone())),
-            Mismatch::BodyMismatch { ref path, ref mismatch, .. } => format!("{} -> {}", Style::new().bold().paint(path.clone()), mismatch)
+            Mismatch::BodyMismatch { ref path, ref mismatch, ref expected, ref actual, .. } => {
+                let description = format!("{} -> {}", Style::new().bold().paint(path.clone()), mismatch);
+                match diff::body_mismatch_diff(expected, actual) {
+                    Some(diff) => format!("{}\n{}", description, diff),
+                    None => description
+                }
+            },
+            Mismatch::TruncatedMismatches { limit, count } => format!(
+                "Only the first {} mismatches were reported, at least {} more were not",
+                Style::new().bold().paint(limit.to_string()), Red.paint(count.to_string()))
         }
     }
 }
@@ -606,10 +791,14 @@ impl PartialEq for Mismatch {
                 &Mismatch::HeaderMismatch{ key: ref p2, expected: ref e2, actual: ref a2, mismatch: _ }) => {
                 p1 == p2 && e1 == e2 && a1 == a2
             },
-            (&Mismatch::BodyMismatch{ path: ref p1, expected: ref e1, actual: ref a1, mismatch: _ },
-                &Mismatch::BodyMismatch{ path: ref p2, expected: ref e2, actual: ref a2, mismatch: _ }) => {
+            (&Mismatch::BodyMismatch{ path: ref p1, expected: ref e1, actual: ref a1, .. },
+                &Mismatch::BodyMismatch{ path: ref p2, expected: ref e2, actual: ref a2, .. }) => {
                 p1 == p2 && e1 == e2 && a1 == a2
             },
+            (&Mismatch::TruncatedMismatches{ limit: l1, count: c1 },
+                &Mismatch::TruncatedMismatches{ limit: l2, count: c2 }) => {
+                l1 == l2 && c1 == c2
+            },
             (_, _) => false
         }
     }
@@ -624,42 +813,113 @@ pub enum DiffConfig {
     NoUnexpectedKeys
 }
 
+/// Configuration that limits how many mismatches a match reports. Once the limit is reached,
+/// matching stops and the remaining mismatches are summarised with a `TruncatedMismatches` entry.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MatchingConfig {
+    /// Maximum number of mismatches to report. A limit of zero is treated as one.
+    pub max_mismatches: Option<usize>,
+    /// If matching should stop at the first mismatch
+    pub fail_fast: bool
+}
+
+impl MatchingConfig {
+    /// Configuration that reports at most `max_mismatches` mismatches
+    pub fn with_max_mismatches(max_mismatches: usize) -> MatchingConfig {
+        MatchingConfig { max_mismatches: Some(max_mismatches), .. MatchingConfig::default() }
+    }
+
+    /// Configuration that stops matching at the first mismatch
+    pub fn fail_fast() -> MatchingConfig {
+        MatchingConfig { fail_fast: true, .. MatchingConfig::default() }
+    }
+
+    /// The maximum number of mismatches that will be reported, if there is one
+    pub fn limit(&self) -> Option<usize> {
+        if self.fail_fast {
+            Some(1)
+        } else {
+            self.max_mismatches.map(|max| max.max(1))
+        }
+    }
+
+    /// If matching should stop, given the number of mismatches found so far. Unless failing fast,
+    /// matching continues one mismatch past the limit, so that there is something to summarise.
+    pub fn limit_reached(&self, mismatch_count: usize) -> bool {
+        match self.limit() {
+            Some(limit) if self.fail_fast => mismatch_count >= limit,
+            Some(limit) => mismatch_count > limit,
+            None => false
+        }
+    }
+
+    /// Truncates the mismatches to the limit, appending a `TruncatedMismatches` entry with the
+    /// number of mismatches that were dropped.
+    pub fn truncate(&self, mut mismatches: Vec<Mismatch>) -> Vec<Mismatch> {
+        match self.limit() {
+            Some(limit) if mismatches.len() > limit => {
+                let count = mismatches.len() - limit;
+                mismatches.truncate(limit);
+                mismatches.push(Mismatch::TruncatedMismatches { limit, count });
+                mismatches
+            },
+            _ => mismatches
+        }
+    }
+}
+
 /// Matches the actual text body to the expected one.
-pub fn match_text(expected: &Vec<u8>, actual: &Vec<u8>, mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+pub fn match_text(expected: &Vec<u8>, actual: &Vec<u8>, mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
   let path = vec![s!("$")];
   if matchers.matcher_is_defined("body", &path) {
-    if let Err(messages) = match_values("body", &path, matchers.clone(), expected, actual) {
-      for message in messages {
+    if let Err(failures) = match_values_with_rules("body", &path, matchers, expected, actual) {
+      for (rule, message) in failures {
         mismatches.push(Mismatch::BodyMismatch {
           path: s!("$"),
           expected: Some(expected.clone()),
           actual: Some(actual.clone()),
-          mismatch: message.clone()
+          mismatch: message.clone(),
+          details: Some(json::rule_mismatch_details(rule, &path, &text_value(expected), &text_value(actual)))
         })
       }
     }
   } else if expected != actual {
     mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone()),
       actual: Some(actual.clone()),
-      mismatch: format!("Expected text '{:?}' but received '{:?}'", expected, actual) });
+      mismatch: format!("Expected text '{:?}' but received '{:?}'", expected, actual),
+      details: Some(MismatchDetails::new(MismatchKind::RuleMismatch(MatchingRule::Equality), &path,
+        text_value(expected), text_value(actual))) });
   };
 }
 
+fn text_value(body: &[u8]) -> Value {
+  Value::String(String::from_utf8_lossy(body).to_string())
+}
+
 /// Matches the actual request method to the expected one.
 pub fn match_method(expected: String, actual: String, mismatches: &mut Vec<Mismatch>) {
+    compare_methods(&expected, &actual, mismatches)
+}
+
+fn compare_methods(expected: &String, actual: &String, mismatches: &mut Vec<Mismatch>) {
     if expected.to_lowercase() != actual.to_lowercase() {
-        mismatches.push(Mismatch::MethodMismatch { expected: expected, actual: actual });
+        mismatches.push(Mismatch::MethodMismatch { expected: expected.clone(), actual: actual.clone() });
     }
 }
 
 /// Matches the actual request path to the expected one.
 pub fn match_path(expected: String, actual: String, mismatches: &mut Vec<Mismatch>,
     matchers: &MatchingRules) {
+    compare_paths(&expected, &actual, mismatches, matchers)
+}
+
+fn compare_paths(expected: &String, actual: &String, mismatches: &mut Vec<Mismatch>,
+    matchers: &dyn RuleResolver) {
     let path = vec![];
     let matcher_result = if matchers.matcher_is_defined("path", &path) {
-      matchers::match_values("path", &path, matchers.clone(), &expected, &actual)
+      matchers::match_values("path", &path, matchers, expected, actual)
     } else {
-      expected.matches(&actual, &MatchingRule::Equality).map_err(|err| vec![err])
+      expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![err])
     };
     match matcher_result {
         Err(messages) => {
@@ -675,10 +935,10 @@ pub fn match_path(expected: String, actual: String, mismatches: &mut Vec<Mismatc
 }
 
 fn compare_query_parameter_value(key: &String, expected: &String, actual: &String, index: usize,
-    mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
     let path = vec![s!("$"), key.clone(), format!("{}", index)];
     let matcher_result = if matchers.matcher_is_defined("query", &path) {
-      matchers::match_values("query", &path, matchers.clone(), expected, actual)
+      matchers::match_values("query", &path, matchers, expected, actual)
     } else {
       expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![err])
     };
@@ -698,7 +958,7 @@ fn compare_query_parameter_value(key: &String, expected: &String, actual: &Strin
 }
 
 fn compare_query_parameter_values(key: &String, expected: &Vec<String>, actual: &Vec<String>,
-    mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
     for (index, val) in expected.iter().enumerate() {
         if index < actual.len() {
             compare_query_parameter_value(key, val, &actual[index], index, mismatches, matchers);
@@ -712,7 +972,7 @@ fn compare_query_parameter_values(key: &String, expected: &Vec<String>, actual:
 }
 
 fn match_query_values(key: &String, expected: &Vec<String>, actual: &Vec<String>,
-    mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
     if expected.is_empty() && !actual.is_empty() {
         mismatches.push(Mismatch::QueryMismatch { parameter: key.clone(),
             expected: format!("{:?}", expected),
@@ -731,9 +991,9 @@ fn match_query_values(key: &String, expected: &Vec<String>, actual: &Vec<String>
     }
 }
 
-fn match_query_maps(expected: HashMap<String, Vec<String>>, actual: HashMap<String, Vec<String>>,
-    mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
-    for (key, value) in &expected {
+fn match_query_maps(expected: &HashMap<String, Vec<String>>, actual: &HashMap<String, Vec<String>>,
+    mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
+    for (key, value) in expected {
         match actual.get(key) {
             Some(actual_value) => match_query_values(key, value, actual_value, mismatches, matchers),
             None => mismatches.push(Mismatch::QueryMismatch { parameter: key.clone(),
@@ -742,7 +1002,7 @@ fn match_query_maps(expected: HashMap<String, Vec<String>>, actual: HashMap<Stri
                 mismatch: format!("Expected query parameter '{}' but was missing", key) })
         }
     }
-    for (key, value) in &actual {
+    for (key, value) in actual {
         match expected.get(key) {
             Some(_) => (),
             None => mismatches.push(Mismatch::QueryMismatch { parameter: key.clone(),
@@ -757,21 +1017,27 @@ fn match_query_maps(expected: HashMap<String, Vec<String>>, actual: HashMap<Stri
 pub fn match_query(expected: Option<HashMap<String, Vec<String>>>,
     actual: Option<HashMap<String, Vec<String>>>, mismatches: &mut Vec<Mismatch>,
     matchers: &MatchingRules) {
+    compare_queries(&expected, &actual, mismatches, matchers)
+}
+
+fn compare_queries(expected: &Option<HashMap<String, Vec<String>>>,
+    actual: &Option<HashMap<String, Vec<String>>>, mismatches: &mut Vec<Mismatch>,
+    matchers: &dyn RuleResolver) {
     match (actual, expected) {
-        (Some(aqm), Some(eqm)) => match_query_maps(eqm, aqm, mismatches, matchers),
-        (Some(aqm), None) => for (key, value) in &aqm {
+        (&Some(ref aqm), &Some(ref eqm)) => match_query_maps(eqm, aqm, mismatches, matchers),
+        (&Some(ref aqm), &None) => for (key, value) in aqm {
             mismatches.push(Mismatch::QueryMismatch { parameter: key.clone(),
                 expected: "".to_string(),
                 actual: format!("{:?}", value),
                 mismatch: format!("Unexpected query parameter '{}' received", key) });
         },
-        (None, Some(eqm)) => for (key, value) in &eqm {
+        (&None, &Some(ref eqm)) => for (key, value) in eqm {
             mismatches.push(Mismatch::QueryMismatch { parameter: key.clone(),
                 expected: format!("{:?}", value),
                 actual: "".to_string(),
                 mismatch: format!("Expected query parameter '{}' but was missing", key) });
         },
-        (None, None) => (),
+        (&None, &None) => (),
     };
 }
 
@@ -812,13 +1078,13 @@ fn match_parameter_header(expected: &String, actual: &String, mismatches: &mut V
 }
 
 fn match_header_value(key: &String, expected: &String, actual: &String, mismatches: &mut Vec<Mismatch>,
-    matchers: &MatchingRules) {
+    matchers: &dyn RuleResolver) {
     let path = vec![s!("$"), key.clone()];
     let expected = strip_whitespace::<String>(expected, ",");
     let actual = strip_whitespace::<String>(actual, ",");
 
     let matcher_result = if matchers.matcher_is_defined("header", &path) {
-        matchers::match_values("header",&path, matchers.clone(), &expected, &actual)
+        matchers::match_values("header",&path, matchers, &expected, &actual)
     } else if PARAMETERISED_HEADER_TYPES.contains(&key.to_lowercase().as_str()) {
         match_parameter_header(&expected, &actual, mismatches, &key);
         Ok(())
@@ -847,10 +1113,10 @@ fn find_entry<T>(map: &HashMap<String, T>, key: &String) -> Option<(String, T)>
     }
 }
 
-fn match_header_maps(expected: HashMap<String, Vec<String>>, actual: HashMap<String, Vec<String>>,
-  mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
-  for (key, value) in &expected {
-    match find_entry(&actual, key) {
+fn match_header_maps(expected: &HashMap<String, Vec<String>>, actual: &HashMap<String, Vec<String>>,
+  mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
+  for (key, value) in expected {
+    match find_entry(actual, key) {
       Some((_, actual_value)) => for (index, val) in value.iter().enumerate() {
         match_header_value(key, val, actual_value.get(index).unwrap_or(&s!("")), mismatches, matchers)
       },
@@ -866,51 +1132,64 @@ fn match_header_maps(expected: HashMap<String, Vec<String>>, actual: HashMap<Str
 pub fn match_headers(expected: Option<HashMap<String, Vec<String>>>,
   actual: Option<HashMap<String, Vec<String>>>, mismatches: &mut Vec<Mismatch>,
   matchers: &MatchingRules) {
+  compare_headers(&expected, &actual, mismatches, matchers)
+}
+
+fn compare_headers(expected: &Option<HashMap<String, Vec<String>>>,
+  actual: &Option<HashMap<String, Vec<String>>>, mismatches: &mut Vec<Mismatch>,
+  matchers: &dyn RuleResolver) {
   match (actual, expected) {
-    (Some(aqm), Some(eqm)) => match_header_maps(eqm, aqm, mismatches, matchers),
-    (Some(_), None) => (),
-    (None, Some(eqm)) => for (key, value) in &eqm {
+    (&Some(ref aqm), &Some(ref eqm)) => match_header_maps(eqm, aqm, mismatches, matchers),
+    (&Some(_), &None) => (),
+    (&None, &Some(ref eqm)) => for (key, value) in eqm {
       mismatches.push(Mismatch::HeaderMismatch { key: key.clone(),
         expected: format!("{:?}", value.join(", ")),
         actual: "".to_string(),
         mismatch: format!("Expected header '{}' but was missing", key) });
     },
-    (None, None) => (),
+    (&None, &None) => (),
   };
 }
 
 fn compare_bodies(mimetype: String, expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
-    mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
-    match BODY_MATCHERS.iter().find(|mt| mt.0.is_match(&mimetype)) {
-        Some(ref match_fn) => match_fn.1(expected, actual, config, mismatches, matchers),
-        None => match_text(expected, actual, mismatches, matchers)
+    matching_config: &MatchingConfig, mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
+    if graphql::is_graphql_request(&mimetype, expected) {
+        graphql::match_graphql_with_config(expected, actual, config, matching_config, mismatches, matchers)
+    } else {
+        match BODY_MATCHERS.iter().find(|mt| mt.0.is_match(&mimetype)) {
+            Some(ref match_fn) => match_fn.1(expected, actual, config, matching_config, mismatches, matchers),
+            None => match_text(expected, actual, mismatches, matchers)
+        }
     }
 }
 
 fn match_body_content(content_type: String, expected: &models::OptionalBody, actual: &models::OptionalBody,
-    config: DiffConfig, mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    config: DiffConfig, matching_config: &MatchingConfig, mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
     match (expected, actual) {
         (&models::OptionalBody::Missing, _) => (),
-        (&models::OptionalBody::Null, &models::OptionalBody::Present(ref b)) => {
+        (&models::OptionalBody::Null, &models::OptionalBody::Present(ref b, _)) => {
             mismatches.push(Mismatch::BodyMismatch { expected: None, actual: Some(b.clone()),
                 mismatch: format!("Expected empty body but received '{:?}'", b.clone()),
-                path: s!("/")});
+                path: s!("/"),
+                details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, &[], Value::Null, text_value(b)))});
         },
-        (&models::OptionalBody::Empty, &models::OptionalBody::Present(ref b)) => {
+        (&models::OptionalBody::Empty, &models::OptionalBody::Present(ref b, _)) => {
             mismatches.push(Mismatch::BodyMismatch { expected: None, actual: Some(b.clone()),
                 mismatch: format!("Expected empty body but received '{:?}'", b.clone()),
-                path: s!("/")});
+                path: s!("/"),
+                details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, &[], Value::Null, text_value(b)))});
         },
         (&models::OptionalBody::Null, _) => (),
         (&models::OptionalBody::Empty, _) => (),
         (e, &models::OptionalBody::Missing) => {
             mismatches.push(Mismatch::BodyMismatch { expected: Some(e.value()), actual: None,
                 mismatch: format!("Expected body '{:?}' but was missing", e.value()),
-                path: s!("/")});
+                path: s!("/"),
+                details: Some(MismatchDetails::new(MismatchKind::MissingValue, &[], text_value(&e.value()), Value::Null))});
         },
         (_, _) => {
             compare_bodies(content_type, &expected.value(), &actual.value(),
-                config, mismatches, matchers);
+                config, matching_config, mismatches, matchers);
         }
     }
 }
@@ -918,10 +1197,18 @@ fn match_body_content(content_type: String, expected: &models::OptionalBody, act
 /// Matches the actual body to the expected one. This takes into account the content type of each.
 pub fn match_body(expected: &dyn models::HttpPart, actual: &dyn models::HttpPart, config: DiffConfig,
     mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    match_body_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the actual body to the expected one, stopping once the mismatch limit of the
+/// matching configuration is reached. The mismatches are not truncated.
+pub fn match_body_with_config(expected: &dyn models::HttpPart, actual: &dyn models::HttpPart, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<Mismatch>, matchers: &dyn RuleResolver) {
     debug!("expected content type = '{}', actual content type = '{}'", expected.content_type(),
            actual.content_type());
     if expected.content_type() == actual.content_type() {
-        match_body_content(expected.content_type(), expected.body(), actual.body(), config, mismatches, matchers)
+        match_body_content(expected.content_type(), expected.body(), actual.body(), config, matching_config,
+            mismatches, matchers)
     } else if expected.body().is_present() {
         mismatches.push(Mismatch::BodyTypeMismatch { expected: expected.content_type(),
             actual: actual.content_type() });
@@ -930,20 +1217,43 @@ pub fn match_body(expected: &dyn models::HttpPart, actual: &dyn models::HttpPart
 
 /// Matches the expected and actual requests.
 pub fn match_request(expected: models::Request, actual: models::Request) -> Vec<Mismatch> {
+    match_request_with_config(expected, actual, &MatchingConfig::default())
+}
+
+/// Matches the expected and actual requests, reporting no more mismatches than the matching
+/// configuration allows.
+pub fn match_request_with_config(expected: models::Request, actual: models::Request,
+    matching_config: &MatchingConfig) -> Vec<Mismatch> {
+    compare_requests(&expected, &actual, &expected.matching_rules, matching_config)
+}
+
+/// Matches the expected and actual requests, resolving the matching rules of the expected request
+/// with the given resolver
+pub(crate) fn compare_requests(expected: &models::Request, actual: &models::Request, matchers: &dyn RuleResolver,
+    matching_config: &MatchingConfig) -> Vec<Mismatch> {
     let mut mismatches = vec![];
 
     info!("comparing to expected {}", expected);
     debug!("     body: '{}'", expected.body.str_value());
     debug!("     matching_rules: {:?}", expected.matching_rules);
     debug!("     generators: {:?}", expected.generators);
-    match_method(expected.method.clone(), actual.method.clone(), &mut mismatches);
-    match_path(expected.path.clone(), actual.path.clone(), &mut mismatches, &expected.matching_rules);
-    match_body(&expected, &actual, DiffConfig::NoUnexpectedKeys, &mut mismatches, &expected.matching_rules);
-    match_query(expected.query, actual.query, &mut mismatches, &expected.matching_rules);
-    match_headers(expected.headers, actual.headers, &mut mismatches, &expected.matching_rules);
+    compare_methods(&expected.method, &actual.method, &mut mismatches);
+    if !matching_config.limit_reached(mismatches.len()) {
+        compare_paths(&expected.path, &actual.path, &mut mismatches, matchers);
+    }
+    if !matching_config.limit_reached(mismatches.len()) {
+        match_body_with_config(expected, actual, DiffConfig::NoUnexpectedKeys, matching_config, &mut mismatches,
+            matchers);
+    }
+    if !matching_config.limit_reached(mismatches.len()) {
+        compare_queries(&expected.query, &actual.query, &mut mismatches, matchers);
+    }
+    if !matching_config.limit_reached(mismatches.len()) {
+        compare_headers(&expected.headers, &actual.headers, &mut mismatches, matchers);
+    }
 
     debug!("--> Mismatches: {:?}", mismatches);
-    mismatches
+    matching_config.truncate(mismatches)
 }
 
 /// Matches the actual response status to the expected one.
@@ -955,21 +1265,41 @@ pub fn match_status(expected: u16, actual: u16, mismatches: &mut Vec<Mismatch>)
 
 /// Matches the actual and expected responses.
 pub fn match_response(expected: models::Response, actual: models::Response) -> Vec<Mismatch> {
+    match_response_with_config(expected, actual, &MatchingConfig::default())
+}
+
+/// Matches the actual and expected responses, reporting no more mismatches than the matching
+/// configuration allows.
+pub fn match_response_with_config(expected: models::Response, actual: models::Response,
+    matching_config: &MatchingConfig) -> Vec<Mismatch> {
     let mut mismatches = vec![];
 
     info!("comparing to expected response: {:?}", expected);
-    match_body(&expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &expected.matching_rules);
-    match_status(expected.status, actual.status, &mut mismatches);
-    match_headers(expected.headers, actual.headers, &mut mismatches, &expected.matching_rules);
+    match_body_with_config(&expected, &actual, DiffConfig::AllowUnexpectedKeys, matching_config, &mut mismatches,
+        &expected.matching_rules);
+    if !matching_config.limit_reached(mismatches.len()) {
+        match_status(expected.status, actual.status, &mut mismatches);
+    }
+    if !matching_config.limit_reached(mismatches.len()) {
+        match_headers(expected.headers, actual.headers, &mut mismatches, &expected.matching_rules);
+    }
 
-    mismatches
+    matching_config.truncate(mismatches)
 }
 
 /// Matches the actual message contents to the expected one. This takes into account the content type of each.
 pub fn match_message_contents(expected: &models::message::Message, actual: &models::message::Message, config: DiffConfig,
     mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
+    match_message_contents_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the actual message contents to the expected one, stopping once the mismatch limit of
+/// the matching configuration is reached. The mismatches are not truncated.
+pub fn match_message_contents_with_config(expected: &models::message::Message, actual: &models::message::Message,
+    config: DiffConfig, matching_config: &MatchingConfig, mismatches: &mut Vec<Mismatch>, matchers: &MatchingRules) {
     if expected.mimetype() == actual.mimetype() {
-        match_body_content(expected.mimetype(), &expected.contents, &actual.contents, config, mismatches, matchers)
+        match_body_content(expected.mimetype(), &expected.contents, &actual.contents, config, matching_config,
+            mismatches, matchers)
     } else if expected.contents.is_present() {
         mismatches.push(Mismatch::BodyTypeMismatch { expected: expected.mimetype(),
             actual: actual.mimetype() });
@@ -978,12 +1308,20 @@ pub fn match_message_contents(expected: &models::message::Message, actual: &mode
 
 /// Matches the actual and expected messages.
 pub fn match_message(expected: models::message::Message, actual: models::message::Message) -> Vec<Mismatch> {
+    match_message_with_config(expected, actual, &MatchingConfig::default())
+}
+
+/// Matches the actual and expected messages, reporting no more mismatches than the matching
+/// configuration allows.
+pub fn match_message_with_config(expected: models::message::Message, actual: models::message::Message,
+    matching_config: &MatchingConfig) -> Vec<Mismatch> {
     let mut mismatches = vec![];
 
     info!("comparing to expected message: {:?}", expected);
-    match_message_contents(&expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &expected.matching_rules);
+    match_message_contents_with_config(&expected, &actual, DiffConfig::AllowUnexpectedKeys, matching_config,
+        &mut mismatches, &expected.matching_rules);
 
-    mismatches
+    matching_config.truncate(mismatches)
 }
 
 /// Generates the request by applying any defined generators
@@ -1,8 +1,36 @@
 use models::matchingrules::*;
 use itertools::Itertools;
-use onig::Regex;
+use onig::{self, Regex};
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
 use time_utils::validate_datetime;
 
+/// Maximum number of compiled regular expressions that are kept. When the cache is full, it is
+/// cleared before the next expression is added.
+const MAX_CACHED_REGEXES: usize = 1000;
+
+lazy_static! {
+    static ref REGEX_CACHE: RwLock<HashMap<String, Arc<Regex>>> = RwLock::new(HashMap::new());
+}
+
+/// Returns the compiled regular expression, compiling it the first time it is used. Compiled
+/// expressions are shared between all matches, so the regular expressions of matching rules are
+/// only compiled once (up to `MAX_CACHED_REGEXES` of them). Invalid expressions are not cached.
+pub fn compiled_regex(regex: &str) -> Result<Arc<Regex>, onig::Error> {
+    // the cache is only a map of compiled expressions, so it is still usable if another thread
+    // panicked while holding the lock
+    if let Some(re) = REGEX_CACHE.read().unwrap_or_else(PoisonError::into_inner).get(regex) {
+        return Ok(re.clone());
+    }
+    let re = Arc::new(Regex::new(regex)?);
+    let mut cache = REGEX_CACHE.write().unwrap_or_else(PoisonError::into_inner);
+    if cache.len() >= MAX_CACHED_REGEXES {
+        cache.clear();
+    }
+    cache.insert(regex.to_string(), re.clone());
+    Ok(re)
+}
+
 pub trait Matches<A> {
     fn matches(&self, actual: &A, matcher: &MatchingRule) -> Result<(), String>;
 }
@@ -12,7 +40,7 @@ impl Matches<String> for String {
         debug!("String -> String: comparing '{}' to '{}' using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(actual) {
                   Ok(())
@@ -81,7 +109,7 @@ impl Matches<u64> for String {
         debug!("String -> u64: comparing '{}' to {} using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&actual.to_string()) {
                   Ok(())
@@ -117,7 +145,7 @@ impl Matches<u64> for u64 {
         debug!("u64 -> u64: comparing {} to {} using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&actual.to_string()) {
                   Ok(())
@@ -158,7 +186,7 @@ impl Matches<f64> for u64 {
         debug!("u64 -> f64: comparing {} to {} using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&actual.to_string()) {
                   Ok(())
@@ -194,7 +222,7 @@ impl Matches<f64> for f64 {
         debug!("f64 -> f64: comparing {} to {} using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&actual.to_string()) {
                   Ok(())
@@ -235,7 +263,7 @@ impl Matches<u64> for f64 {
         debug!("f64 -> u64: comparing {} to {} using {:?}", self, actual, matcher);
         match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(&actual.to_string()) {
                   Ok(())
@@ -279,7 +307,7 @@ impl Matches<Vec<u8>> for Vec<u8> {
   }
 }
 
-fn select_best_matcher(category: &str, path: &Vec<String>, matchers: &MatchingRules) -> Option<RuleList> {
+fn select_best_matcher(category: &str, path: &Vec<String>, matchers: &dyn RuleResolver) -> Option<RuleList> {
   if category == "body" {
     matchers.resolve_body_matchers_by_path(path)
   } else {
@@ -290,27 +318,39 @@ fn select_best_matcher(category: &str, path: &Vec<String>, matchers: &MatchingRu
   }
 }
 
-pub fn match_values<E, A>(category: &str, path: &Vec<String>, matchers: MatchingRules, expected: &E, actual: &A) -> Result<(), Vec<String>>
+pub fn match_values<E, A>(category: &str, path: &Vec<String>, matchers: &dyn RuleResolver, expected: &E, actual: &A) -> Result<(), Vec<String>>
     where E: Matches<A> {
-    let matching_rules = select_best_matcher(category, path, &matchers);
+    match_values_with_rules(category, path, matchers, expected, actual)
+      .map_err(|failures| failures.into_iter().map(|(_, message)| message).collect())
+}
+
+/// Matches the values using the matching rules for the path, returning each rule that failed
+/// along with the mismatch message. The rule will be `None` if no matcher was found for the path.
+pub fn match_values_with_rules<E, A>(category: &str, path: &Vec<String>, matchers: &dyn RuleResolver, expected: &E,
+  actual: &A) -> Result<(), Vec<(Option<MatchingRule>, String)>> where E: Matches<A> {
+    let matching_rules = select_best_matcher(category, path, matchers);
     match matching_rules {
-        None => Err(vec![format!("No matcher found for category '{}' and path '{}'", category,
-                            path.iter().join("."))]),
+        None => Err(vec![(None, format!("No matcher found for category '{}' and path '{}'", category,
+                            path.iter().join(".")))]),
         Some(ref rulelist) => {
-          let results = rulelist.rules.iter().map(|rule| expected.matches(actual, rule)).collect::<Vec<Result<(), String>>>();
+          let results = rulelist.rules.iter()
+            .map(|rule| expected.matches(actual, rule).map_err(|message| (Some(rule.clone()), message)))
+            .collect::<Vec<Result<(), (Option<MatchingRule>, String)>>>();
+          let failures = results.iter().filter_map(|result| result.clone().err())
+            .collect::<Vec<(Option<MatchingRule>, String)>>();
           match rulelist.rule_logic {
             RuleLogic::And => {
-              if results.iter().all(|result| result.is_ok()) {
+              if failures.is_empty() {
                 Ok(())
               } else {
-                Err(results.iter().filter(|result| result.is_err()).map(|result| result.clone().unwrap_err()).collect())
+                Err(failures)
               }
             },
             RuleLogic::Or => {
-              if results.iter().any(|result| result.is_ok()) {
+              if failures.len() < results.len() {
                 Ok(())
               } else {
-                Err(results.iter().filter(|result| result.is_err()).map(|result| result.clone().unwrap_err()).collect())
+                Err(failures)
               }
             }
           }
@@ -324,6 +364,13 @@ mod tests {
     use super::select_best_matcher;
     use expectest::prelude::*;
 
+    #[test]
+    fn compiled_regex_reuses_valid_expressions() {
+        let regex = compiled_regex("\\d+-compiled-regex-test").unwrap();
+        expect!(Arc::ptr_eq(&regex, &compiled_regex("\\d+-compiled-regex-test").unwrap())).to(be_true());
+        expect!(compiled_regex("[a-").is_err()).to(be_true());
+    }
+
     #[test]
     fn select_best_matcher_selects_most_appropriate_by_weight() {
         let matchers = matchingrules!{
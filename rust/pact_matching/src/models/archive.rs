@@ -0,0 +1,264 @@
+//! Functions for loading pact files from zip and tar archives
+
+use std::fs::File;
+use std::io::{self, Read, Seek, Error, ErrorKind};
+use std::path::Path;
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+use models::Pact;
+
+/// Type of archive that pact files can be loaded from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveType {
+    /// Zip archive (`.zip`)
+    Zip,
+    /// Tar archive (`.tar`)
+    Tar,
+    /// Gzipped tar archive (`.tar.gz` or `.tgz`)
+    TarGz
+}
+
+impl ArchiveType {
+    /// Determines the type of archive from the extension of the file name, returning `None` if the
+    /// file is not a supported archive.
+    pub fn from_path(path: &Path) -> Option<ArchiveType> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveType::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveType::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveType::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// If the entry in an archive is a pact file. Hidden files (like the `._` files added to archives
+/// created on macOS) are ignored.
+fn is_pact_file(name: &str) -> bool {
+    let file_name = name.trim_end_matches('/').rsplit('/').next().unwrap_or(name);
+    !name.ends_with('/') && !file_name.starts_with('.') && file_name.to_lowercase().ends_with(".json")
+}
+
+fn read_pact<R: Read>(name: &str, reader: R, strict: bool) -> io::Result<Pact> {
+    if strict {
+        Pact::read_pact_from_reader_strict(name, reader)
+    } else {
+        Pact::read_pact_from_reader(name, reader)
+    }
+}
+
+fn read_pacts_from_zip<R: Read + Seek>(reader: R, strict: bool) -> io::Result<Vec<(String, io::Result<Pact>)>> {
+    let mut archive = ZipArchive::new(reader)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, format!("Failed to read zip archive - {}", err)))?;
+    let mut pacts = vec![];
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, format!("Failed to read zip archive - {}", err)))?;
+        let name = entry.name().to_string();
+        if is_pact_file(&name) {
+            debug!("Loading pact file '{}' from zip archive", name);
+            let pact = read_pact(&name, entry, strict);
+            pacts.push((name, pact));
+        }
+    }
+    Ok(pacts)
+}
+
+fn read_pacts_from_tar<R: Read>(reader: R, strict: bool) -> io::Result<Vec<(String, io::Result<Pact>)>> {
+    let mut archive = Archive::new(reader);
+    let mut pacts = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        if entry.header().entry_type().is_file() && is_pact_file(&name) {
+            debug!("Loading pact file '{}' from tar archive", name);
+            let pact = read_pact(&name, entry, strict);
+            pacts.push((name, pact));
+        }
+    }
+    Ok(pacts)
+}
+
+/// Reads all the pact files (the entries with a `.json` extension) from the zip or tar(.gz) archive.
+/// Returns the name of each entry along with the result of loading it, so one invalid pact file
+/// does not prevent the others from being loaded. Returns an error if the archive can not be read.
+pub fn read_pacts_from_archive(path: &Path) -> io::Result<Vec<(String, io::Result<Pact>)>> {
+    read_archive(path, false)
+}
+
+/// Reads all the pact files from the zip or tar(.gz) archive like `read_pacts_from_archive`, but
+/// strictly parses each pact file, so any problems with a pact file are returned as its error.
+pub fn read_pacts_from_archive_strict(path: &Path) -> io::Result<Vec<(String, io::Result<Pact>)>> {
+    read_archive(path, true)
+}
+
+fn read_archive(path: &Path, strict: bool) -> io::Result<Vec<(String, io::Result<Pact>)>> {
+    let archive_type = ArchiveType::from_path(path)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{:?} is not a zip or tar archive", path)))?;
+    let file = File::open(path)?;
+    match archive_type {
+        ArchiveType::Zip => read_pacts_from_zip(file, strict),
+        ArchiveType::Tar => read_pacts_from_tar(file, strict),
+        ArchiveType::TarGz => read_pacts_from_tar(GzDecoder::new(file), strict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use rand;
+    use tar;
+    use zip;
+
+    fn pact_json(consumer: &str) -> String {
+        json!({
+            "consumer": { "name": consumer },
+            "provider": { "name": "archive_provider" },
+            "interactions": [],
+            "metadata": { "pactSpecification": { "version": "3.0.0" } }
+        }).to_string()
+    }
+
+    fn archive_entries() -> Vec<(&'static str, String)> {
+        vec![
+            ("pacts/consumer1-archive_provider.json", pact_json("consumer1")),
+            ("pacts/nested/consumer2-archive_provider.json", pact_json("consumer2")),
+            ("pacts/README.md", s!("not a pact")),
+            ("pacts/._consumer1-archive_provider.json", s!("macOS metadata")),
+            ("pacts/invalid.json", s!("{\"consumer\": "))
+        ]
+    }
+
+    fn temp_path(file_name: &str) -> PathBuf {
+        let mut dir = env::temp_dir();
+        dir.push(format!("pact_archive_test_{}", rand::random::<u16>()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.push(file_name);
+        dir
+    }
+
+    fn tar_archive<W: Write>(writer: W) -> W {
+        let mut builder = tar::Builder::new(writer);
+        for (name, contents) in archive_entries() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn loaded_consumers(pacts: &Vec<(String, io::Result<Pact>)>) -> Vec<String> {
+        pacts.iter()
+            .filter_map(|&(_, ref pact)| pact.as_ref().ok().map(|pact| pact.consumer.name.clone()))
+            .collect()
+    }
+
+    fn failed_entries(pacts: &Vec<(String, io::Result<Pact>)>) -> Vec<String> {
+        pacts.iter().filter(|&&(_, ref pact)| pact.is_err()).map(|&(ref name, _)| name.clone()).collect()
+    }
+
+    #[test]
+    fn archive_type_from_path_test() {
+        expect!(ArchiveType::from_path(Path::new("pacts.zip"))).to(be_some().value(ArchiveType::Zip));
+        expect!(ArchiveType::from_path(Path::new("pacts.tar"))).to(be_some().value(ArchiveType::Tar));
+        expect!(ArchiveType::from_path(Path::new("/tmp/pacts.TAR.GZ"))).to(be_some().value(ArchiveType::TarGz));
+        expect!(ArchiveType::from_path(Path::new("pacts.tgz"))).to(be_some().value(ArchiveType::TarGz));
+        expect!(ArchiveType::from_path(Path::new("pact.json"))).to(be_none());
+    }
+
+    #[test]
+    fn reads_pacts_from_a_zip_archive() {
+        let path = temp_path("pacts.zip");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+            zip.add_directory("pacts/", zip::write::FileOptions::default()).unwrap();
+            for (name, contents) in archive_entries() {
+                zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+                zip.write_all(contents.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+
+        let pacts = read_pacts_from_archive(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+
+        let pacts = pacts.unwrap();
+        expect!(loaded_consumers(&pacts)).to(be_equal_to(vec![s!("consumer1"), s!("consumer2")]));
+        expect!(failed_entries(&pacts)).to(be_equal_to(vec![s!("pacts/invalid.json")]));
+    }
+
+    #[test]
+    fn reads_pacts_from_a_tar_archive() {
+        let path = temp_path("pacts.tar");
+        tar_archive(File::create(&path).unwrap());
+
+        let pacts = read_pacts_from_archive(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+
+        let pacts = pacts.unwrap();
+        expect!(loaded_consumers(&pacts)).to(be_equal_to(vec![s!("consumer1"), s!("consumer2")]));
+        expect!(failed_entries(&pacts)).to(be_equal_to(vec![s!("pacts/invalid.json")]));
+    }
+
+    #[test]
+    fn reads_pacts_from_a_gzipped_tar_archive() {
+        let path = temp_path("pacts.tar.gz");
+        tar_archive(GzEncoder::new(File::create(&path).unwrap(), Compression::default())).finish().unwrap();
+
+        let pacts = read_pacts_from_archive(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+
+        expect!(loaded_consumers(&pacts.unwrap())).to(be_equal_to(vec![s!("consumer1"), s!("consumer2")]));
+    }
+
+    #[test]
+    fn reads_pacts_strictly_from_an_archive() {
+        let path = temp_path("pacts.tar");
+        {
+            let mut builder = tar::Builder::new(File::create(&path).unwrap());
+            for (name, contents) in vec![("pacts/consumer1-archive_provider.json", pact_json("consumer1")),
+                ("pacts/not-a-pact.json", s!("{\"consumer\": 100}"))] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+            }
+            builder.finish().unwrap();
+        }
+
+        let lenient = read_pacts_from_archive(&path);
+        let strict = read_pacts_from_archive_strict(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+
+        expect!(failed_entries(&lenient.unwrap()).is_empty()).to(be_true());
+        let strict = strict.unwrap();
+        expect!(loaded_consumers(&strict)).to(be_equal_to(vec![s!("consumer1")]));
+        expect!(failed_entries(&strict)).to(be_equal_to(vec![s!("pacts/not-a-pact.json")]));
+    }
+
+    #[test]
+    fn returns_an_error_if_the_archive_is_not_valid() {
+        let path = temp_path("pacts.zip");
+        fs::write(&path, "not a zip file").unwrap();
+
+        let pacts = read_pacts_from_archive(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap_or(());
+
+        expect!(pacts).to(be_err());
+        expect!(read_pacts_from_archive(Path::new("pact.json"))).to(be_err());
+    }
+}
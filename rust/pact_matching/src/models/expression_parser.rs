@@ -0,0 +1,238 @@
+//! Module for evaluating the expressions used by provider state generators. An expression is
+//! either the name of a value in the provider state context (i.e. `orderId`), or a template with
+//! `${name}` placeholders (i.e. `/orders/${orderId}/items/${itemId}`). Names can use dots to refer
+//! to the attributes of object values (i.e. `mockServer.href`). Request header names are stored in
+//! lower case, so the names of headers (i.e. `header.X-Request-Id`) are not case sensitive.
+
+use std::collections::HashMap;
+use serde_json::Value;
+use models::json_utils::json_to_string;
+
+/// Data type the result of an expression is converted to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+  /// Converted to a string
+  String,
+  /// Converted to an integer
+  Integer,
+  /// Converted to a decimal number
+  Decimal,
+  /// Converted to a boolean
+  Boolean,
+  /// Not converted
+  Raw
+}
+
+impl DataType {
+  /// Determines the data type from the example value the generator is applied to
+  pub fn from_example(value: &Value) -> DataType {
+    match value {
+      &Value::String(_) => DataType::String,
+      &Value::Number(ref n) if n.is_i64() || n.is_u64() => DataType::Integer,
+      &Value::Number(_) => DataType::Decimal,
+      &Value::Bool(_) => DataType::Boolean,
+      _ => DataType::Raw
+    }
+  }
+
+  /// Converts the value to this data type, returning an error if it can not be converted
+  pub fn convert(&self, value: Value) -> Result<Value, String> {
+    match self {
+      &DataType::String => Ok(Value::String(json_to_string(&value))),
+      &DataType::Integer => match value {
+        Value::Number(ref n) if n.is_i64() || n.is_u64() => Ok(value.clone()),
+        Value::String(ref s) => s.trim().parse::<i64>().map(|n| json!(n))
+          .map_err(|_| format!("'{}' can not be converted to an integer", s)),
+        _ => Err(format!("{} can not be converted to an integer", value))
+      },
+      &DataType::Decimal => match value {
+        Value::Number(ref n) => Ok(json!(n.as_f64().unwrap_or_default())),
+        Value::String(ref s) => s.trim().parse::<f64>().map(|n| json!(n))
+          .map_err(|_| format!("'{}' can not be converted to a decimal", s)),
+        _ => Err(format!("{} can not be converted to a decimal", value))
+      },
+      &DataType::Boolean => match value {
+        Value::Bool(_) => Ok(value),
+        Value::String(ref s) if s.trim().eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+        Value::String(ref s) if s.trim().eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+        _ => Err(format!("{} can not be converted to a boolean", value))
+      },
+      &DataType::Raw => Ok(value)
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Text(String),
+  Placeholder(String)
+}
+
+fn tokenise(expression: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = vec![];
+  let mut remainder = expression;
+  while let Some(start) = remainder.find("${") {
+    if start > 0 {
+      tokens.push(Token::Text(remainder[..start].to_string()));
+    }
+    let end = remainder[start..].find('}')
+      .ok_or_else(|| format!("Missing closing brace in expression '{}'", expression))?;
+    let name = remainder[start + 2..start + end].trim();
+    if name.is_empty() {
+      return Err(format!("Empty placeholder in expression '{}'", expression));
+    }
+    tokens.push(Token::Placeholder(name.to_string()));
+    remainder = &remainder[start + end + 1..];
+  }
+  if !remainder.is_empty() {
+    tokens.push(Token::Text(remainder.to_string()));
+  }
+  Ok(tokens)
+}
+
+/// Looks up the value for the name in the context. If there is no value with the name, and the
+/// name contains dots, the first part is looked up and the remaining parts are used as the
+/// attributes of the value (i.e. `mockServer.href`).
+fn lookup(name: &str, context: &HashMap<String, Value>) -> Result<Value, String> {
+  let value = context.get(name).cloned().or_else(|| {
+    let mut parts = name.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+      (Some("header"), Some(header)) => context.get("header")
+        .and_then(|headers| lookup_attribute(headers, &header.to_lowercase())),
+      (Some(key), Some(attribute)) => context.get(key).and_then(|value| lookup_attribute(value, attribute)),
+      _ => None
+    }
+  });
+  value.ok_or_else(|| format!("Value for '{}' was not found in the provider state context", name))
+}
+
+/// Looks up the attribute of the value. Attribute names can contain dots (i.e. a header named
+/// `x.trace.id`), so the whole name is tried before it is split into nested attributes.
+fn lookup_attribute(value: &Value, name: &str) -> Option<Value> {
+  value.get(name).cloned().or_else(|| {
+    let mut parts = name.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+      (Some(key), Some(attribute)) => value.get(key).and_then(|value| lookup_attribute(value, attribute)),
+      _ => None
+    }
+  })
+}
+
+/// If the string contains any `${name}` placeholders
+pub fn contains_expressions(value: &str) -> bool {
+  value.contains("${")
+}
+
+/// Evaluates the expression against the provider state context, and converts the result to the
+/// data type. If the expression is a single placeholder (or just the name of a value), the value
+/// from the context is converted. Otherwise, all the placeholders are replaced with the string form
+/// of their values.
+pub fn evaluate_expression(expression: &str, context: &HashMap<String, Value>, data_type: DataType) -> Result<Value, String> {
+  let value = if contains_expressions(expression) {
+    let tokens = tokenise(expression)?;
+    match &tokens[..] {
+      &[Token::Placeholder(ref name)] => lookup(name, context)?,
+      _ => {
+        let mut result = String::new();
+        for token in &tokens {
+          match token {
+            &Token::Text(ref text) => result.push_str(text),
+            &Token::Placeholder(ref name) => result.push_str(&json_to_string(&lookup(name, context)?))
+          }
+        }
+        Value::String(result)
+      }
+    }
+  } else {
+    lookup(expression.trim(), context)?
+  };
+  data_type.convert(value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expectest::prelude::*;
+
+  fn context() -> HashMap<String, Value> {
+    hashmap!{
+      s!("orderId") => json!(1234),
+      s!("itemId") => json!("abc"),
+      s!("price") => json!(10.5),
+      s!("active") => json!(true),
+      s!("count") => json!("42")
+    }
+  }
+
+  #[test]
+  fn tokenise_test() {
+    expect!(tokenise("")).to(be_ok().value(vec![]));
+    expect!(tokenise("/orders")).to(be_ok().value(vec![Token::Text(s!("/orders"))]));
+    expect!(tokenise("/orders/${orderId}/items/${ itemId }")).to(be_ok().value(vec![
+      Token::Text(s!("/orders/")), Token::Placeholder(s!("orderId")),
+      Token::Text(s!("/items/")), Token::Placeholder(s!("itemId"))
+    ]));
+    expect!(tokenise("${a}${b}")).to(be_ok().value(vec![Token::Placeholder(s!("a")), Token::Placeholder(s!("b"))]));
+    expect!(tokenise("/orders/${orderId")).to(be_err());
+    expect!(tokenise("/orders/${}")).to(be_err());
+  }
+
+  #[test]
+  fn evaluates_names_with_dots_as_attributes_of_objects() {
+    let mut context = context();
+    context.insert(s!("mockServer"), json!({ "href": "http://localhost:1234", "port": 1234 }));
+    context.insert(s!("x.y"), json!("dotted"));
+    expect!(evaluate_expression("${mockServer.href}/orders/${orderId}", &context, DataType::String))
+      .to(be_ok().value(json!("http://localhost:1234/orders/1234")));
+    expect!(evaluate_expression("mockServer.port", &context, DataType::Integer)).to(be_ok().value(json!(1234)));
+    expect!(evaluate_expression("x.y", &context, DataType::String)).to(be_ok().value(json!("dotted")));
+    expect!(evaluate_expression("${mockServer.missing}", &context, DataType::String)).to(be_err());
+  }
+
+  #[test]
+  fn evaluates_header_names_without_case_sensitivity() {
+    let mut context = context();
+    context.insert(s!("header"), json!({ "x-request-id": "abc", "x.trace.id": "123" }));
+    expect!(evaluate_expression("${header.X-Request-Id}", &context, DataType::String)).to(be_ok().value(json!("abc")));
+    expect!(evaluate_expression("header.x-request-id", &context, DataType::String)).to(be_ok().value(json!("abc")));
+    expect!(evaluate_expression("${header.X.Trace.Id}", &context, DataType::String)).to(be_ok().value(json!("123")));
+  }
+
+  #[test]
+  fn evaluates_templates() {
+    expect!(evaluate_expression("/orders/${orderId}/items/${itemId}", &context(), DataType::String))
+      .to(be_ok().value(json!("/orders/1234/items/abc")));
+    expect!(evaluate_expression("/orders/${missing}", &context(), DataType::String)).to(be_err());
+  }
+
+  #[test]
+  fn evaluates_single_values_with_their_type() {
+    expect!(evaluate_expression("orderId", &context(), DataType::Raw)).to(be_ok().value(json!(1234)));
+    expect!(evaluate_expression("${orderId}", &context(), DataType::Raw)).to(be_ok().value(json!(1234)));
+    expect!(evaluate_expression("${orderId}", &context(), DataType::String)).to(be_ok().value(json!("1234")));
+    expect!(evaluate_expression("missing", &context(), DataType::Raw)).to(be_err());
+  }
+
+  #[test]
+  fn converts_values_to_the_data_type() {
+    expect!(evaluate_expression("${count}", &context(), DataType::Integer)).to(be_ok().value(json!(42)));
+    expect!(evaluate_expression("${orderId}${count}", &context(), DataType::Integer)).to(be_ok().value(json!(123442)));
+    expect!(evaluate_expression("${itemId}", &context(), DataType::Integer)).to(be_err());
+    expect!(evaluate_expression("${price}", &context(), DataType::Decimal)).to(be_ok().value(json!(10.5)));
+    expect!(evaluate_expression("${orderId}", &context(), DataType::Decimal)).to(be_ok().value(json!(1234.0)));
+    expect!(evaluate_expression("${active}", &context(), DataType::Boolean)).to(be_ok().value(json!(true)));
+    expect!(evaluate_expression("${active}", &context(), DataType::String)).to(be_ok().value(json!("true")));
+    expect!(evaluate_expression("${itemId}", &context(), DataType::Boolean)).to(be_err());
+  }
+
+  #[test]
+  fn data_type_from_example_test() {
+    expect!(DataType::from_example(&json!("string"))).to(be_equal_to(DataType::String));
+    expect!(DataType::from_example(&json!(100))).to(be_equal_to(DataType::Integer));
+    expect!(DataType::from_example(&json!(-100))).to(be_equal_to(DataType::Integer));
+    expect!(DataType::from_example(&json!(100.5))).to(be_equal_to(DataType::Decimal));
+    expect!(DataType::from_example(&json!(false))).to(be_equal_to(DataType::Boolean));
+    expect!(DataType::from_example(&json!(null))).to(be_equal_to(DataType::Raw));
+    expect!(DataType::from_example(&json!({}))).to(be_equal_to(DataType::Raw));
+  }
+}
@@ -0,0 +1,166 @@
+//! Module for safely reading and writing pact files when there may be multiple threads or
+//! processes writing the same file at the same time
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use fs2::FileExt;
+use rand;
+
+/// Returns the path of the lock file used to coordinate writes to the given file. The lock file is
+/// kept in the temporary directory (so it does not end up with the pact files), and its name
+/// includes a hash of the absolute path of the file.
+pub fn lock_file_path(path: &Path) -> PathBuf {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from(".")
+    };
+    let file_name = path.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+    let absolute_path = match dir.canonicalize() {
+        Ok(dir) => dir.join(&file_name),
+        Err(_) => path.to_path_buf()
+    };
+    let mut hasher = DefaultHasher::new();
+    absolute_path.hash(&mut hasher);
+    let mut lock_file_name = file_name;
+    lock_file_name.push(format!(".{:016x}.lock", hasher.finish()));
+    env::temp_dir().join(lock_file_name)
+}
+
+/// Takes an exclusive advisory lock on the lock file for the given path, blocking until it is
+/// available, and then invokes the closure. The lock is released once the closure returns. If the
+/// closure succeeds, the lock file is removed so lock files do not pile up in the temporary directory.
+///
+/// A separate lock file is used (rather than locking the file itself), as the file will be
+/// replaced when it is written to.
+pub fn with_write_lock<F, T>(path: &Path, f: F) -> io::Result<T> where F: FnOnce() -> io::Result<T> {
+    let lock_path = lock_file_path(path);
+    let lock_file = lock(&lock_path)?;
+    let result = f();
+    if result.is_ok() {
+        fs::remove_file(&lock_path).unwrap_or(());
+    }
+    lock_file.unlock()?;
+    result
+}
+
+/// Opens and locks the lock file. As the lock file is removed by the previous writer, the lock may
+/// have been taken on a file that has since been removed (or replaced by another writer), in which
+/// case the lock is taken again on the current lock file.
+fn lock(lock_path: &Path) -> io::Result<File> {
+    loop {
+        let lock_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(lock_path)?;
+        lock_file.lock_exclusive()?;
+        match fs::metadata(lock_path) {
+            Ok(ref metadata) if same_file(&lock_file.metadata()?, metadata) => return Ok(lock_file),
+            _ => lock_file.unlock()?
+        }
+    }
+}
+
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[cfg(not(unix))]
+fn same_file(_: &fs::Metadata, _: &fs::Metadata) -> bool {
+    // Open files can not be removed on Windows, so the lock file can not have been replaced
+    true
+}
+
+/// Writes the contents to the file by first writing it to a temporary file in the same directory,
+/// and then renaming it over the file. Readers of the file will either see the previous contents,
+/// or the complete new contents, never a partially written file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temp_name = path.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+    temp_name.push(format!(".{}.tmp", rand::random::<u32>()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        fs::remove_file(&temp_path).unwrap_or(());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn lock_file_path_test() {
+        let lock_file = lock_file_path(Path::new("/tmp/pacts/consumer-provider.json"));
+        let file_name = lock_file.file_name().unwrap().to_string_lossy().to_string();
+        expect!(lock_file.parent()).to(be_some().value(env::temp_dir().as_path()));
+        expect!(file_name.starts_with("consumer-provider.json.")).to(be_true());
+        expect!(file_name.ends_with(".lock")).to(be_true());
+        expect!(lock_file_path(Path::new("/tmp/pacts/consumer-provider.json"))).to(be_equal_to(lock_file.clone()));
+        expect!(lock_file_path(Path::new("/tmp/other/consumer-provider.json"))).to_not(be_equal_to(lock_file));
+    }
+
+    #[test]
+    fn write_atomically_replaces_the_file_contents() {
+        let mut dir = env::temp_dir();
+        dir.push(format!("write_atomically_test_{}", rand::random::<u16>()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.json");
+        fs::write(&path, "old contents that are longer").unwrap();
+
+        let result = write_atomically(&path, b"new contents");
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let files = fs::read_dir(&dir).map(|entries| entries.count()).unwrap_or_default();
+        fs::remove_dir_all(&dir).unwrap_or(());
+
+        expect!(result).to(be_ok());
+        expect!(contents).to(be_equal_to(s!("new contents")));
+        expect!(files).to(be_equal_to(1));
+    }
+
+    #[test]
+    fn with_write_lock_only_allows_one_writer_at_a_time() {
+        let mut dir = env::temp_dir();
+        dir.push(format!("with_write_lock_test_{}", rand::random::<u16>()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.json");
+        let events = Arc::new(Mutex::new(vec![]));
+
+        let handles: Vec<_> = (0..4).map(|i| {
+            let path = path.clone();
+            let events = events.clone();
+            thread::spawn(move || with_write_lock(&path, || {
+                events.lock().unwrap().push(format!("start {}", i));
+                thread::sleep(Duration::from_millis(10));
+                events.lock().unwrap().push(format!("end {}", i));
+                Ok(())
+            }))
+        }).collect();
+        let results: Vec<io::Result<()>> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        let lock_file_exists = lock_file_path(&path).exists();
+        fs::remove_dir_all(&dir).unwrap_or(());
+
+        expect!(results.iter().all(|result| result.is_ok())).to(be_true());
+        expect!(lock_file_exists).to(be_false());
+        let events = events.lock().unwrap();
+        for pair in events.chunks(2) {
+            expect!(pair[1].replace("end", "start")).to(be_equal_to(pair[0].clone()));
+        }
+    }
+}
@@ -7,12 +7,15 @@ use std::{
   ops::Index
 };
 use serde_json::{self, Value};
+use serde_yaml;
 use super::PactSpecification;
 use rand::prelude::*;
 use rand::distributions::Alphanumeric;
 use uuid::Uuid;
-use models::{OptionalBody, DetectedContentType};
+use models::{OptionalBody, DetectedContentType, Interaction, Request};
+use models::matchingrules::MatchingRule;
 use models::json_utils::{JsonToNum, json_to_string};
+use models::expression_parser::{evaluate_expression, DataType};
 use models::xml_utils::parse_bytes;
 use sxd_document::dom::Document;
 use path_exp::*;
@@ -22,6 +25,7 @@ use chrono::prelude::*;
 use time_utils::{parse_pattern, to_chrono_pattern};
 use nom::types::CompleteStr;
 use regex_syntax;
+use matchers::compiled_regex;
 
 /// Trait to represent a generator
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Eq, Hash)]
@@ -46,8 +50,14 @@ pub enum Generator {
   DateTime(Option<String>),
   /// Generates a random boolean value
   RandomBoolean,
-  /// Generates a value that is looked up from the provider state context
-  ProviderStateGenerator(String)
+  /// Generates a value from an expression evaluated against the provider state context. The
+  /// expression can be the name of a value (`orderId`) or a template (`/orders/${orderId}`), and the
+  /// result is converted to the type of the example value.
+  ProviderStateGenerator(String),
+  /// Generates a URL pointing at the running mock server, by appending the groups matched by the
+  /// regex in the example URL (i.e. `.*(/orders/\d+)$`) to the base URL of the mock server. The
+  /// parameters are the example URL and the regex.
+  MockServerURL(String, String)
 }
 
 impl Generator {
@@ -73,7 +83,8 @@ impl Generator {
         &None => json!({ "type": "DateTime" })
       },
       &Generator::RandomBoolean => json!({ "type": "RandomBoolean" }),
-      &Generator::ProviderStateGenerator(ref expression) => json!({"type": "ProviderState", "expression": expression})
+      &Generator::ProviderStateGenerator(ref expression) => json!({"type": "ProviderState", "expression": expression}),
+      &Generator::MockServerURL(ref example, ref regex) => json!({"type": "MockServerURL", "example": example, "regex": regex})
     }
   }
 
@@ -95,6 +106,13 @@ impl Generator {
       "DateTime" => Some(Generator::DateTime(map.get("format").map(|f| json_to_string(f)))),
       "RandomBoolean" => Some(Generator::RandomBoolean),
       "ProviderState" => Some(Generator::ProviderStateGenerator(map.get("expression").map(|f| json_to_string(f)).unwrap())),
+      "MockServerURL" => match (map.get("example"), map.get("regex")) {
+        (Some(example), Some(regex)) => Some(Generator::MockServerURL(json_to_string(example), json_to_string(regex))),
+        _ => {
+          warn!("Ignoring MockServerURL generator without an example and regex -> {:?}", map);
+          None
+        }
+      },
       _ => {
         warn!("'{}' is not a valid generator type", gen_type);
         None
@@ -103,6 +121,45 @@ impl Generator {
   }
 }
 
+/// Rewrites the URL to point at the mock server from the generation context (the `href` of the
+/// `mockServer` entry), keeping the parts of the URL matched by the groups in the regex.
+fn generate_mock_server_url(url: &str, regex: &str, context: &HashMap<String, Value>) -> Option<String> {
+  let href = match context.get("mockServer").and_then(|mock_server| mock_server.get("href")) {
+    Some(href) => json_to_string(href),
+    None => {
+      warn!("No mock server details were found in the generation context, ignoring the MockServerURL generator");
+      return None
+    }
+  };
+  match compiled_regex(regex) {
+    Ok(re) => match re.captures(url) {
+      Some(captures) => {
+        let path = (1..captures.len()).filter_map(|i| captures.at(i)).collect::<String>();
+        Some(format!("{}{}", href.trim_end_matches('/'), path))
+      },
+      None => {
+        warn!("MockServerURL generator regex '{}' does not match the URL '{}'", regex, url);
+        None
+      }
+    },
+    Err(err) => {
+      warn!("'{}' is not a valid regular expression - {}", regex, err);
+      None
+    }
+  }
+}
+
+/// Evaluates a provider state expression, logging a warning and returning `None` if it fails
+fn generate_from_provider_state(expression: &String, context: &HashMap<String, Value>, data_type: DataType) -> Option<Value> {
+  match evaluate_expression(expression, context, data_type) {
+    Ok(value) => Some(value),
+    Err(err) => {
+      warn!("Failed to generate a value from the provider state expression '{}' - {}", expression, err);
+      None
+    }
+  }
+}
+
 /// Trait that represents generation of a value based on a source value.
 pub trait GenerateValue<T> {
   /// Generates a new value based on the source value. `None` will be returned if the value can not
@@ -111,10 +168,12 @@ pub trait GenerateValue<T> {
 }
 
 impl GenerateValue<u16> for Generator {
-  fn generate_value(&self, _: &u16, _context: &HashMap<String, Value>) -> Option<u16> {
+  fn generate_value(&self, _: &u16, context: &HashMap<String, Value>) -> Option<u16> {
     match self {
       &Generator::RandomInt(min, max) => Some(rand::thread_rng().gen_range(min as u16, (max as u16).saturating_add(1))),
-      &Generator::ProviderStateGenerator(ref _exp) => None,
+      &Generator::ProviderStateGenerator(ref exp) => generate_from_provider_state(exp, context, DataType::Integer)
+        .and_then(|value| value.as_u64())
+        .and_then(|value| if value <= u16::max_value() as u64 { Some(value as u16) } else { None }),
       _ => None
     }
   }
@@ -137,7 +196,7 @@ fn generate_ascii_string(size: usize) -> String {
 }
 
 impl GenerateValue<String> for Generator {
-  fn generate_value(&self, _: &String, _context: &HashMap<String, Value>) -> Option<String> {
+  fn generate_value(&self, value: &String, context: &HashMap<String, Value>) -> Option<String> {
     let mut rnd = rand::thread_rng();
     match self {
       &Generator::RandomInt(min, max) => Some(format!("{}", rnd.gen_range(min, max.saturating_add(1)))),
@@ -189,7 +248,10 @@ impl GenerateValue<String> for Generator {
         None => Some(Local::now().format("%Y-%m-%dT%H:%M:%S.%3f%z").to_string())
       },
       &Generator::RandomBoolean => Some(format!("{}", rnd.gen::<bool>())),
-      &Generator::ProviderStateGenerator(ref _exp) => None
+      &Generator::ProviderStateGenerator(ref exp) => generate_from_provider_state(exp, context, DataType::String)
+        .map(|value| json_to_string(&value)),
+      &Generator::MockServerURL(ref example, ref regex) => generate_mock_server_url(
+        if value.is_empty() { example } else { value }, regex, context)
     }
   }
 }
@@ -201,7 +263,7 @@ impl GenerateValue<Vec<String>> for Generator {
 }
 
 impl GenerateValue<Value> for Generator {
-  fn generate_value(&self, value: &Value, _context: &HashMap<String, Value>) -> Option<Value> {
+  fn generate_value(&self, value: &Value, context: &HashMap<String, Value>) -> Option<Value> {
     match self {
       &Generator::RandomInt(min, max) => {
         let rand_int = rand::thread_rng().gen_range(min, max.saturating_add(1));
@@ -275,7 +337,12 @@ impl GenerateValue<Value> for Generator {
         None => Some(json!(Local::now().format("%Y-%m-%dT%H:%M:%S.%3f%z").to_string()))
       },
       &Generator::RandomBoolean => Some(json!(rand::thread_rng().gen::<bool>())),
-      &Generator::ProviderStateGenerator(ref _exp) => None
+      &Generator::ProviderStateGenerator(ref exp) => generate_from_provider_state(exp, context,
+        DataType::from_example(value)),
+      &Generator::MockServerURL(ref example, ref regex) => match value {
+        &Value::String(ref url) => generate_mock_server_url(url, regex, context),
+        _ => generate_mock_server_url(example, regex, context)
+      }.map(|url| json!(url))
     }
   }
 }
@@ -418,16 +485,68 @@ impl JsonHandler {
   }
 }
 
+/// Expands the path expression into the JSON pointers of all the values it matches in the
+/// document. Used for path expressions with a recursive descent (`..`), which can match values at
+/// any depth.
+fn expand_json_pointers(tokens: &[PathToken], pointer: String, value: &Value, pointers: &mut Vec<String>) {
+  let child_pointer = |key: &str| format!("{}/{}", pointer, key.replace("~", "~0").replace("/", "~1"));
+  match tokens.split_first() {
+    None => pointers.push(pointer.clone()),
+    Some((token, remaining)) => match token {
+      &PathToken::Root => expand_json_pointers(remaining, pointer.clone(), value, pointers),
+      &PathToken::Field(ref name) => if let Some(child) = value.as_object().and_then(|map| map.get(name)) {
+        expand_json_pointers(remaining, child_pointer(name), child, pointers);
+      },
+      &PathToken::Index(index) => if let Some(child) = value.as_array().and_then(|items| items.get(index)) {
+        expand_json_pointers(remaining, child_pointer(&index.to_string()), child, pointers);
+      },
+      &PathToken::Star => if let Some(map) = value.as_object() {
+        for (key, child) in map {
+          expand_json_pointers(remaining, child_pointer(key), child, pointers);
+        }
+      },
+      &PathToken::StarIndex => if let Some(items) = value.as_array() {
+        for (index, child) in items.iter().enumerate() {
+          expand_json_pointers(remaining, child_pointer(&index.to_string()), child, pointers);
+        }
+      },
+      &PathToken::RecursiveDescent => {
+        expand_json_pointers(remaining, pointer.clone(), value, pointers);
+        match value {
+          &Value::Object(ref map) => for (key, child) in map {
+            expand_json_pointers(tokens, child_pointer(key), child, pointers);
+          },
+          &Value::Array(ref items) => for (index, child) in items.iter().enumerate() {
+            expand_json_pointers(tokens, child_pointer(&index.to_string()), child, pointers);
+          },
+          _ => ()
+        }
+      }
+    }
+  }
+}
+
 impl ContentTypeHandler<Value> for JsonHandler {
   fn process_body(&mut self, generators: &HashMap<String, Generator>, context: &HashMap<String, Value>) -> OptionalBody {
     for (key, generator) in generators {
       self.apply_key(key, generator, context);
     };
-    OptionalBody::Present(self.value.to_string().into())
+    OptionalBody::Present(self.value.to_string().into(), None)
   }
 
   fn apply_key(&mut self, key: &String, generator: &Generator, context: &HashMap<String, Value>) {
     match parse_path_exp(key.clone()) {
+      Ok(ref path_exp) if path_exp.contains(&PathToken::RecursiveDescent) => {
+        let mut pointers = vec![];
+        expand_json_pointers(path_exp, s!(""), &self.value, &mut pointers);
+        for pointer_str in pointers {
+          if let Some(json_value) = self.value.pointer_mut(&pointer_str) {
+            if let Some(new_value) = generator.generate_value(&json_value.clone(), context) {
+              *json_value = new_value;
+            }
+          }
+        }
+      },
       Ok(path_exp) => {
         let mut tree = Arena::new();
         let root = tree.new_node("".into());
@@ -552,9 +671,11 @@ impl Generators {
           }
         },
         _ => {
-          for (key, val) in category {
-            map.insert(cat.clone(), json!({ key.as_str(): val.to_json() }));
-          }
+          let generators = category.iter().fold(serde_json::Map::new(), |mut map, (key, val)| {
+            map.insert(key.clone(), val.to_json());
+            map
+          });
+          map.insert(cat.clone(), Value::Object(generators));
         }
       }
       map
@@ -603,6 +724,23 @@ impl Generators {
             }
           }
         },
+        DetectedContentType::Yaml => match serde_yaml::from_slice(&body.value()) {
+          Ok(val) => {
+            let mut handler = JsonHandler { value: val };
+            handler.process_body(&generators, context);
+            match serde_yaml::to_string(&handler.value) {
+              Ok(yaml) => OptionalBody::Present(yaml.into(), None),
+              Err(err) => {
+                error!("Failed to write the body as YAML, so not applying any generators: {}", err);
+                body.clone()
+              }
+            }
+          },
+          Err(err) => {
+            error!("Failed to parse the body, so not applying any generators: {}", err);
+            body.clone()
+          }
+        },
         DetectedContentType::Xml => match parse_bytes(&body.value()) {
           Ok(val) => {
             let mut handler = XmlHandler { value: val.as_document() };
@@ -665,6 +803,66 @@ pub fn generators_to_json(generators: &Generators, spec_version: &PactSpecificat
   }
 }
 
+fn multi_value_to_json(values: &Vec<String>) -> Value {
+  if values.len() == 1 {
+    Value::String(values[0].clone())
+  } else {
+    json!(values)
+  }
+}
+
+/// Extracts the values of the named groups of any regex matching rules on the path of the
+/// expected request from the actual path. I.e. the rule `/orders/(?<orderId>\d+)` will extract
+/// `orderId` from the path `/orders/1234`.
+fn path_parameters(expected: &Request, path: &str) -> HashMap<String, Value> {
+  let mut parameters = hashmap!{};
+  if let Some(category) = expected.matching_rules.rules_for_category(&s!("path")) {
+    for rule_list in category.rules.values() {
+      for rule in &rule_list.rules {
+        if let &MatchingRule::Regex(ref regex) = rule {
+          match compiled_regex(regex) {
+            Ok(re) => if let Some(captures) = re.captures(path) {
+              for (name, groups) in re.capture_names() {
+                if let Some(value) = groups.iter().filter_map(|group| captures.at(*group as usize)).next() {
+                  parameters.insert(name.to_string(), Value::String(value.to_string()));
+                }
+              }
+            },
+            Err(err) => warn!("'{}' is not a valid regular expression - {}", regex, err)
+          }
+        }
+      }
+    }
+  }
+  parameters
+}
+
+/// Builds the context used to generate the response to a request that matched the interaction.
+/// The context contains the provider state parameters of the interaction, overridden by the path
+/// parameters (named groups in the regex matching rules for the path) of the actual request. The
+/// header values and query parameters of the request are kept separate from these, under the
+/// `header` and `query` keys (i.e. `${header.x-correlation-id}` or `${query.status}`), so they can
+/// not override them. Header names are converted to lower case, as they are not case sensitive.
+/// Headers and query parameters with a single value are added as strings, otherwise as an array of
+/// strings.
+pub fn request_generation_context(interaction: &Interaction, request: &Request) -> HashMap<String, Value> {
+  let mut context: HashMap<String, Value> = interaction.provider_states.iter()
+    .flat_map(|state| state.params.clone())
+    .collect();
+  context.extend(path_parameters(&interaction.request, &request.path));
+  if let Some(ref headers) = request.headers {
+    context.insert(s!("header"), Value::Object(headers.iter()
+      .map(|(name, values)| (name.to_lowercase(), multi_value_to_json(values)))
+      .collect()));
+  }
+  if let Some(ref query) = request.query {
+    context.insert(s!("query"), Value::Object(query.iter()
+      .map(|(name, values)| (name.clone(), multi_value_to_json(values)))
+      .collect()));
+  }
+  context
+}
+
 /// Macro to make constructing generators easy
 /// Example usage:
 /// ```ignore
@@ -712,6 +910,8 @@ mod tests {
   use super::Generator;
   use std::str::FromStr;
   use hamcrest2::prelude::*;
+  use models::provider_states::ProviderState;
+  use models::matchingrules::RuleLogic;
 
   #[test]
   fn rules_are_empty_when_there_are_no_categories() {
@@ -838,6 +1038,26 @@ mod tests {
     expect!(Generator::from_map(&s!("DateTime"), &json!({ "format": 5 }).as_object().unwrap())).to(be_some().value(Generator::DateTime(Some(s!("5")))));
   }
 
+  #[test]
+  fn generators_to_json_includes_all_the_generators_in_a_category() {
+    let generators = generators!{
+      "BODY" => {
+        "$.a" => Generator::RandomInt(1, 10),
+        "$.b" => Generator::Uuid,
+        "$.c" => Generator::RandomBoolean
+      },
+      "PATH" => { "" => Generator::RandomString(10) }
+    };
+    expect!(generators.to_json()).to(be_equal_to(json!({
+      "body": {
+        "$.a": { "type": "RandomInt", "min": 1, "max": 10 },
+        "$.b": { "type": "Uuid" },
+        "$.c": { "type": "RandomBoolean" }
+      },
+      "path": { "type": "RandomString", "size": 10 }
+    })));
+  }
+
   #[test]
   fn generator_to_json_test() {
     expect!(Generator::RandomInt(5, 15).to_json()).to(be_equal_to(json!({
@@ -891,6 +1111,32 @@ mod tests {
     })));
   }
 
+  #[test]
+  fn mock_server_url_generator_json_test() {
+    let generator = Generator::MockServerURL(s!("http://localhost:9000/orders/1"), s!(".*(/orders/\\d+)$"));
+    expect!(generator.to_json()).to(be_equal_to(json!({
+      "type": "MockServerURL",
+      "example": "http://localhost:9000/orders/1",
+      "regex": ".*(/orders/\\d+)$"
+    })));
+    expect!(Generator::from_map(&s!("MockServerURL"), generator.to_json().as_object().unwrap()))
+      .to(be_some().value(generator));
+    expect!(Generator::from_map(&s!("MockServerURL"), &json!({ "regex": ".*" }).as_object().unwrap())).to(be_none());
+  }
+
+  #[test]
+  fn mock_server_url_generator_test() {
+    let generator = Generator::MockServerURL(s!("http://localhost:9000/orders/1"), s!(".*(/orders/\\d+)$"));
+    let context = hashmap!{ s!("mockServer") => json!({ "href": "http://localhost:34567/", "port": 34567 }) };
+    expect!(generator.generate_value(&s!("http://provider.example.com/orders/1234"), &context))
+      .to(be_some().value(s!("http://localhost:34567/orders/1234")));
+    expect!(generator.generate_value(&s!(""), &context)).to(be_some().value(s!("http://localhost:34567/orders/1")));
+    expect!(generator.generate_value(&json!("http://provider.example.com/orders/2"), &context))
+      .to(be_some().value(json!("http://localhost:34567/orders/2")));
+    expect!(generator.generate_value(&s!("http://provider.example.com/users/1"), &context)).to(be_none());
+    expect!(generator.generate_value(&s!("http://provider.example.com/orders/1"), &hashmap!{})).to(be_none());
+  }
+
   #[test]
   fn generate_decimal_test() {
     assert_that!(generate_decimal(4), matches_regex(r"^\d{4}$"));
@@ -902,4 +1148,51 @@ mod tests {
     assert_that!(Generator::RandomInt(0, i32::max_value()).generate_value(&0,
       &hashmap!{}).unwrap().to_string(), matches_regex(r"^\d+$"));
   }
+
+  #[test]
+  fn request_generation_context_test() {
+    let interaction = Interaction {
+      provider_states: vec![ ProviderState {
+        name: s!("an order exists"),
+        params: hashmap!{ s!("orderId") => json!(1), s!("status") => json!("shipped") }
+      } ],
+      request: Request {
+        path: s!("/orders/1"),
+        matching_rules: matchingrules!{
+          "path" => { "" => [ MatchingRule::Regex(s!("/orders/(?<orderId>\\d+)")) ] }
+        },
+        .. Request::default()
+      },
+      .. Interaction::default()
+    };
+    let request = Request {
+      path: s!("/orders/1234"),
+      query: Some(hashmap!{ s!("status") => vec![s!("pending")], s!("item") => vec![s!("a"), s!("b")] }),
+      headers: Some(hashmap!{ s!("x-correlation-id") => vec![s!("abc")], s!("X-Request-Id") => vec![s!("123")] }),
+      .. Request::default()
+    };
+
+    expect!(request_generation_context(&interaction, &request)).to(be_equal_to(hashmap!{
+      s!("orderId") => json!("1234"),
+      s!("status") => json!("shipped"),
+      s!("query") => json!({ "status": "pending", "item": ["a", "b"] }),
+      s!("header") => json!({ "x-correlation-id": "abc", "x-request-id": "123" })
+    }));
+  }
+
+  #[test]
+  fn request_generation_context_ignores_path_rules_without_named_groups() {
+    let interaction = Interaction {
+      request: Request {
+        matching_rules: matchingrules!{
+          "path" => { "" => [ MatchingRule::Regex(s!("/orders/\\d+")) ] }
+        },
+        .. Request::default()
+      },
+      .. Interaction::default()
+    };
+    let request = Request { path: s!("/orders/1234"), .. Request::default() };
+
+    expect!(request_generation_context(&interaction, &request)).to(be_equal_to(hashmap!{}));
+  }
 }
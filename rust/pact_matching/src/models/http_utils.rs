@@ -1,6 +1,13 @@
 //! Module for fetching documents via HTTP
 
-use reqwest::{Client, Error};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use reqwest::{Client, Certificate, Error, Proxy, RequestBuilder, Response, StatusCode};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde_json::Value;
 
 /// Type of authentication to use
@@ -12,20 +19,154 @@ pub enum HttpAuth {
   Token(String)
 }
 
+/// Options used when fetching documents via HTTP
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchOptions {
+  /// Timeout for each request. Defaults to the timeout of the HTTP client (30 seconds).
+  pub timeout: Option<Duration>,
+  /// Number of times to retry a request that failed to connect, timed out or received a
+  /// server error (5xx) or 429 response. Defaults to no retries.
+  pub retries: u32,
+  /// Delay before the first retry. The delay is doubled for each subsequent retry, up to a
+  /// maximum of one minute.
+  pub retry_backoff: Duration,
+  /// Additional headers to send with each request
+  pub headers: HashMap<String, String>,
+  /// URL of a proxy to send all the requests through
+  pub proxy: Option<String>,
+  /// Path to a PEM file with additional CA certificates to trust
+  pub ca_bundle: Option<PathBuf>,
+  /// Disables the validation of TLS certificates. Only use this for testing.
+  pub insecure: bool
+}
+
+impl Default for FetchOptions {
+  fn default() -> Self {
+    FetchOptions {
+      timeout: None,
+      retries: 0,
+      retry_backoff: Duration::from_secs(1),
+      headers: HashMap::new(),
+      proxy: None,
+      ca_bundle: None,
+      insecure: false
+    }
+  }
+}
+
+/// Splits a PEM bundle into the individual certificates
+fn read_ca_bundle(path: &PathBuf) -> Result<Vec<Certificate>, String> {
+  let mut pem = String::new();
+  File::open(path).and_then(|mut f| f.read_to_string(&mut pem))
+    .map_err(|err| format!("Failed to read CA bundle {:?} - {}", path, err))?;
+  let end_marker = "-----END CERTIFICATE-----";
+  let certificates = pem.split(end_marker)
+    .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+    .map(|block| Certificate::from_pem(format!("{}{}\n", block.trim_start(), end_marker).as_bytes())
+      .map_err(|err| format!("Failed to load certificate from CA bundle {:?} - {}", path, err)))
+    .collect::<Result<Vec<Certificate>, String>>()?;
+  if certificates.is_empty() {
+    Err(format!("No certificates were found in CA bundle {:?}", path))
+  } else {
+    Ok(certificates)
+  }
+}
+
+fn build_client(options: &FetchOptions) -> Result<Client, String> {
+  let mut builder = Client::builder();
+  if let Some(timeout) = options.timeout {
+    builder = builder.timeout(timeout);
+  }
+  if let Some(ref proxy) = options.proxy {
+    builder = builder.proxy(Proxy::all(proxy.as_str())
+      .map_err(|err| format!("Invalid proxy URL '{}' - {}", proxy, err))?);
+  }
+  if let Some(ref ca_bundle) = options.ca_bundle {
+    for certificate in read_ca_bundle(ca_bundle)? {
+      builder = builder.add_root_certificate(certificate);
+    }
+  }
+  if options.insecure {
+    warn!("TLS certificate validation has been disabled");
+    builder = builder.danger_accept_invalid_certs(true);
+  }
+  builder.build().map_err(|err| format!("Failed to create the HTTP client - {}", err))
+}
+
+fn build_headers(options: &FetchOptions) -> Result<HeaderMap, String> {
+  let mut headers = HeaderMap::new();
+  for (name, value) in &options.headers {
+    let header_name = HeaderName::from_bytes(name.as_bytes())
+      .map_err(|err| format!("Invalid header name '{}' - {}", name, err))?;
+    let header_value = HeaderValue::from_str(value)
+      .map_err(|err| format!("Invalid value for header '{}' - {}", name, err))?;
+    headers.insert(header_name, header_value);
+  }
+  Ok(headers)
+}
+
+fn should_retry(result: &Result<Response, Error>) -> bool {
+  match result {
+    &Ok(ref res) => res.status().is_server_error() || res.status() == StatusCode::TOO_MANY_REQUESTS,
+    &Err(ref err) => err.is_http() || err.is_timeout() || err.is_server_error()
+  }
+}
+
+/// The longest delay between two retries of a request
+const MAX_RETRY_DELAY_SECS: u64 = 60;
+
+fn next_retry_delay(delay: Duration) -> Duration {
+  let max_delay = Duration::from_secs(MAX_RETRY_DELAY_SECS);
+  match delay.checked_mul(2) {
+    Some(next_delay) if next_delay < max_delay => next_delay,
+    _ => max_delay
+  }
+}
+
+fn send_with_retries<F>(request: F, options: &FetchOptions) -> Result<Response, Error>
+  where F: Fn() -> RequestBuilder {
+  let mut delay = options.retry_backoff;
+  let mut attempt = 0;
+  loop {
+    let result = request().send();
+    if attempt >= options.retries || !should_retry(&result) {
+      return result;
+    }
+    attempt += 1;
+    match result {
+      Ok(ref res) => warn!("Request failed with status {}, retrying in {:?} (attempt {} of {})", res.status(),
+        delay, attempt, options.retries),
+      Err(ref err) => warn!("Request failed - {}, retrying in {:?} (attempt {} of {})", err, delay, attempt,
+        options.retries)
+    }
+    thread::sleep(delay);
+    delay = next_retry_delay(delay);
+  }
+}
+
 /// Fetches the JSON from a URL
 pub fn fetch_json_from_url(url: &String, auth: &Option<HttpAuth>) -> Result<(String, Value), String> {
-  let client = Client::new();
-  let request = match auth {
-    &Some(ref auth) => {
-      match auth {
-        &HttpAuth::User(ref username, ref password) => client.get(url).basic_auth(username.clone(), password.clone()),
-        &HttpAuth::Token(ref token) => client.get(url).bearer_auth(token.clone())
-      }
-    },
-    &None => client.get(url)
+  fetch_json_from_url_with_options(url, auth, &FetchOptions::default())
+}
+
+/// Fetches the JSON from a URL, using the options to configure the HTTP client
+pub fn fetch_json_from_url_with_options(url: &String, auth: &Option<HttpAuth>, options: &FetchOptions) -> Result<(String, Value), String> {
+  let client = build_client(options)?;
+  let headers = build_headers(options)?;
+  let request = || {
+    let request = client.get(url).headers(headers.clone());
+    match auth {
+      &Some(ref auth) => {
+        match auth {
+          &HttpAuth::User(ref username, ref password) => request.basic_auth(username.clone(), password.clone()),
+          &HttpAuth::Token(ref token) => request.bearer_auth(token.clone())
+        }
+      },
+      &None => request
+    }
   };
 
-  match request.send() {
+  match send_with_retries(request, options) {
     Ok(mut res) => if res.status().is_success() {
       let pact_json: Result<Value, Error> = res.json();
       match pact_json {
@@ -38,3 +179,143 @@ pub fn fetch_json_from_url(url: &String, auth: &Option<HttpAuth>) -> Result<(Str
     Err(err) => Err(format!("Request failed - {}", err))
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expectest::prelude::*;
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn next_retry_delay_doubles_the_delay_up_to_the_maximum() {
+    expect!(next_retry_delay(Duration::from_secs(1))).to(be_equal_to(Duration::from_secs(2)));
+    expect!(next_retry_delay(Duration::from_secs(40))).to(be_equal_to(Duration::from_secs(60)));
+    expect!(next_retry_delay(Duration::from_secs(u64::max_value()))).to(be_equal_to(Duration::from_secs(60)));
+  }
+
+  /// Starts a stub HTTP server that returns the responses in order (one per connection), and
+  /// records the requests it receives. Returns the URL of the server.
+  fn stub_server(responses: Vec<(u16, &'static str)>, requests: Arc<Mutex<Vec<String>>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/pact.json", listener.local_addr().unwrap());
+    thread::spawn(move || {
+      for (status, body) in responses {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = String::new();
+        loop {
+          let mut line = String::new();
+          if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+          }
+          request.push_str(&line);
+        }
+        requests.lock().unwrap().push(request);
+        let mut stream = stream;
+        write!(stream, "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+          status, body.len(), body).unwrap();
+      }
+    });
+    url
+  }
+
+  #[test]
+  fn fetch_json_sends_the_configured_headers() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let url = stub_server(vec![(200, "{\"ok\": true}")], requests.clone());
+    let options = FetchOptions {
+      headers: hashmap!{ s!("X-Artifact-Token") => s!("secret") },
+      .. FetchOptions::default()
+    };
+
+    let result = fetch_json_from_url_with_options(&url, &Some(HttpAuth::Token(s!("token"))), &options);
+
+    expect!(result).to(be_ok().value((url.clone(), json!({ "ok": true }))));
+    let request = requests.lock().unwrap()[0].to_lowercase();
+    expect!(request.contains("x-artifact-token: secret")).to(be_true());
+    expect!(request.contains("authorization: bearer token")).to(be_true());
+  }
+
+  #[test]
+  fn fetch_json_retries_server_errors() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let url = stub_server(vec![(503, "{}"), (500, "{}"), (200, "{\"ok\": true}")], requests.clone());
+    let options = FetchOptions {
+      retries: 2,
+      retry_backoff: Duration::from_millis(10),
+      .. FetchOptions::default()
+    };
+
+    let result = fetch_json_from_url_with_options(&url, &None, &options);
+
+    expect!(result).to(be_ok().value((url.clone(), json!({ "ok": true }))));
+    expect!(requests.lock().unwrap().len()).to(be_equal_to(3));
+  }
+
+  #[test]
+  fn fetch_json_fails_once_the_retries_are_exhausted() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let url = stub_server(vec![(503, "{}"), (503, "{}")], requests.clone());
+    let options = FetchOptions {
+      retries: 1,
+      retry_backoff: Duration::from_millis(10),
+      .. FetchOptions::default()
+    };
+
+    let result = fetch_json_from_url_with_options(&url, &None, &options);
+
+    expect!(result).to(be_err().value(s!("Request failed with status - 503 Service Unavailable")));
+    expect!(requests.lock().unwrap().len()).to(be_equal_to(2));
+  }
+
+  #[test]
+  fn fetch_json_does_not_retry_client_errors() {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let url = stub_server(vec![(404, "{}"), (200, "{}")], requests.clone());
+    let options = FetchOptions {
+      retries: 3,
+      retry_backoff: Duration::from_millis(10),
+      .. FetchOptions::default()
+    };
+
+    let result = fetch_json_from_url_with_options(&url, &None, &options);
+
+    expect!(result).to(be_err().value(s!("Request failed with status - 404 Not Found")));
+    expect!(requests.lock().unwrap().len()).to(be_equal_to(1));
+  }
+
+  #[test]
+  fn fetch_json_times_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/pact.json", listener.local_addr().unwrap());
+    thread::spawn(move || {
+      let _connection = listener.accept().unwrap();
+      thread::sleep(Duration::from_secs(2));
+    });
+    let options = FetchOptions {
+      timeout: Some(Duration::from_millis(100)),
+      .. FetchOptions::default()
+    };
+
+    expect!(fetch_json_from_url_with_options(&url, &None, &options)).to(be_err());
+  }
+
+  #[test]
+  fn fetch_json_returns_an_error_for_invalid_options() {
+    let url = s!("http://127.0.0.1:1/pact.json");
+    let options = FetchOptions {
+      ca_bundle: Some(PathBuf::from("/does/not/exist.pem")),
+      .. FetchOptions::default()
+    };
+    expect!(fetch_json_from_url_with_options(&url, &None, &options)).to(be_err());
+
+    let options = FetchOptions {
+      headers: hashmap!{ s!("Invalid Header") => s!("value") },
+      .. FetchOptions::default()
+    };
+    expect!(fetch_json_from_url_with_options(&url, &None, &options))
+      .to(be_err().value(s!("Invalid header name 'Invalid Header' - invalid HTTP header name")));
+  }
+}
@@ -63,6 +63,24 @@ pub fn json_to_num(value: Option<Value>) -> Option<usize> {
   }
 }
 
+/// Returns a copy of the JSON value with the keys of all objects (recursively) in sorted order.
+/// This does not depend on how `serde_json` has been configured to store object keys, so it can be
+/// used where byte-identical output is required.
+pub fn sort_json_keys(value: &Value) -> Value {
+  match value {
+    &Value::Object(ref map) => {
+      let mut keys = map.keys().collect::<Vec<&String>>();
+      keys.sort();
+      Value::Object(keys.iter().fold(serde_json::Map::new(), |mut sorted, key| {
+        sorted.insert((*key).clone(), sort_json_keys(&map[*key]));
+        sorted
+      }))
+    },
+    &Value::Array(ref values) => Value::Array(values.iter().map(|v| sort_json_keys(v)).collect()),
+    _ => value.clone()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -109,4 +127,15 @@ mod tests {
     expect!(json_to_num(Value::from_str("-100").ok())).to(be_none());
     expect!(json_to_num(Value::from_str("100.10").ok())).to(be_some().value(100));
   }
+
+  #[test]
+  fn sort_json_keys_test() {
+    let json = json!({
+      "b": [{ "z": 1, "a": 2 }],
+      "a": { "d": true, "c": null }
+    });
+    expect!(sort_json_keys(&json).to_string()).to(be_equal_to(
+      s!("{\"a\":{\"c\":null,\"d\":true},\"b\":[{\"a\":2,\"z\":1}]}")));
+    expect!(sort_json_keys(&json!("string"))).to(be_equal_to(json!("string")));
+  }
 }
@@ -3,6 +3,7 @@
 use serde_json::{self, Value};
 use serde_json::map::Map;
 use std::{
+  borrow::Cow,
   collections::{HashMap, HashSet},
   hash::{Hash, Hasher}
 };
@@ -11,6 +12,7 @@ use std::str::FromStr;
 use models::json_utils::{json_to_string, json_to_num};
 use path_exp::*;
 use super::PactSpecification;
+use super::parse_errors::{PactParseError, validate_matching_rule};
 
 fn matches_token(path_fragment: &String, path_token: &PathToken) -> usize {
   match *path_token {
@@ -29,34 +31,216 @@ fn matches_token(path_fragment: &String, path_token: &PathToken) -> usize {
   }
 }
 
-fn calc_path_weight(path_exp: String, path: &Vec<String>) -> usize {
-  let weight = match parse_path_exp(path_exp.clone()) {
-    Ok(path_tokens) => {
-      debug!("Calculating weight for path tokens '{:?}' and path '{:?}'", path_tokens, path);
-      if path.len() >= path_tokens.len() {
-        path_tokens.iter().zip(path.iter())
-          .fold(1, |acc, (token, fragment)| acc * matches_token(fragment, token))
-      } else {
-        0
+/// Calculates the weight of the path tokens against the path. A recursive descent (`..`) can match
+/// any number of path elements, and the best match is used. If `exact` is false, the tokens only
+/// need to match the start of the path (so rules defined on a parent also apply to the children).
+fn match_path_tokens(path_tokens: &[PathToken], path: &[String], exact: bool) -> usize {
+  match path_tokens.split_first() {
+    None => if exact && !path.is_empty() { 0 } else { 1 },
+    Some((&PathToken::RecursiveDescent, remaining)) => (0..path.len() + 1)
+      .map(|skip| match_path_tokens(remaining, &path[skip..], exact))
+      .max()
+      .unwrap_or(0),
+    Some((token, remaining)) => match path.split_first() {
+      Some((fragment, rest)) => match matches_token(fragment, token) {
+        0 => 0,
+        weight => weight * match_path_tokens(remaining, rest, exact)
+      },
+      None => 0
+    }
+  }
+}
+
+/// Path expression of a matching rule that has been parsed, so its weight against many paths can
+/// be calculated without parsing it again
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPathExp {
+  /// The path expression
+  pub expression: String,
+  /// Parsed tokens of the expression, or `None` if it could not be parsed
+  tokens: Option<Vec<PathToken>>,
+  /// If the expression contains a recursive descent (`..`). These expressions are less specific
+  /// than ones with the same weight without one.
+  recursive_descent: bool
+}
+
+impl CompiledPathExp {
+
+  /// Parses the path expression. Expressions that can not be parsed are logged, and do not match
+  /// any path.
+  pub fn new(expression: &String) -> CompiledPathExp {
+    let tokens = match parse_path_exp(expression.clone()) {
+      Ok(tokens) => Some(tokens),
+      Err(err) => {
+        warn!("Failed to parse path expression - {}", err);
+        None
       }
-    },
-    Err(err) => {
-      warn!("Failed to parse path expression - {}", err);
-      0
+    };
+    let recursive_descent = tokens.as_ref()
+      .map(|tokens| tokens.contains(&PathToken::RecursiveDescent))
+      .unwrap_or(false);
+    CompiledPathExp { expression: expression.clone(), tokens, recursive_descent }
+  }
+
+  /// Calculates the weight of the path expression against the path. If `exact` is true, the
+  /// expression must match the whole path (and not just a parent of the path).
+  pub fn weight(&self, path: &[String], exact: bool) -> usize {
+    let weight = match self.tokens {
+      Some(ref tokens) => match_path_tokens(tokens, path, exact),
+      None => 0
+    };
+    debug!("Calculated weight {} for path '{}' and '{:?}'", weight, self.expression, path);
+    weight
+  }
+
+}
+
+/// The rules of a category with their path expressions parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledCategory {
+  name: String,
+  rules: Vec<(CompiledPathExp, RuleList)>
+}
+
+impl CompiledCategory {
+
+  /// Parses the path expressions of the rules of the category
+  pub fn new(category: &Category) -> CompiledCategory {
+    CompiledCategory {
+      name: category.name.clone(),
+      rules: category.rules.iter()
+        .map(|(path_exp, rules)| (CompiledPathExp::new(path_exp), rules.clone()))
+        .collect()
     }
-  };
-  debug!("Calculated weight {} for path '{}' and '{:?}'", weight, path_exp, path);
-  weight
+  }
+
+  /// Returns the rules accepted by the predicate that apply to the path along with the weight of
+  /// their path expressions, ordered from the best match to the worst. The rules with the highest
+  /// weight are better, and a rule without a recursive descent is better than one with the same
+  /// weight with a recursive descent. If a recursive descent expression matches the whole path
+  /// (i.e. `$..id`), the rules that match the whole path are better than the rules inherited from
+  /// a parent of the path, so the recursive descent is not hidden by a rule on a parent.
+  fn rules_by_weight<F>(&self, path: &Vec<String>, predicate: F) -> Vec<(&CompiledPathExp, usize, &RuleList)>
+    where F: Fn(&CompiledPathExp) -> bool {
+    let mut rules: Vec<(&CompiledPathExp, usize, bool, &RuleList)> = self.rules.iter()
+      .filter(|&&(ref path_exp, _)| predicate(path_exp))
+      .map(|&(ref path_exp, ref rules)| (path_exp, path_exp.weight(path, false), rules))
+      .filter(|&(_, w, _)| w > 0)
+      .map(|(path_exp, w, rules)| (path_exp, w, path_exp.weight(path, true) > 0, rules))
+      .collect();
+    let prefer_whole_path = rules.iter().any(|&(path_exp, _, whole_path, _)| whole_path && path_exp.recursive_descent);
+    rules.sort_by(|a, b| (prefer_whole_path && b.2, b.1, !b.0.recursive_descent)
+      .cmp(&(prefer_whole_path && a.2, a.1, !a.0.recursive_descent))
+      .then_with(|| a.0.expression.cmp(&b.0.expression)));
+    rules.into_iter().map(|(path_exp, w, _, rules)| (path_exp, w, rules)).collect()
+  }
+
+  fn max_by_path<F>(&self, path: &Vec<String>, predicate: F) -> Option<RuleList>
+    where F: Fn(&CompiledPathExp) -> bool {
+    self.rules_by_weight(path, predicate).into_iter().next().map(|(_, _, rules)| rules.clone())
+  }
+
+  fn filter<F>(&self, predicate: F) -> Category where F: Fn(&CompiledPathExp) -> bool {
+    Category {
+      name: self.name.clone(),
+      rules: self.rules.iter().filter(|&&(ref path_exp, _)| predicate(path_exp))
+        .map(|&(ref path_exp, ref rules)| (path_exp.expression.clone(), rules.clone())).collect()
+    }
+  }
+
 }
 
-fn path_length(path_exp: String) -> usize {
-  match parse_path_exp(path_exp.clone()) {
-    Ok(path_tokens) => path_tokens.len(),
-    Err(err) => {
-      warn!("Failed to parse path expression - {}", err);
-      0
+/// If the rules of the category are selected by matching their path expressions
+pub(crate) fn is_path_category(category: &str) -> bool {
+  category == "body" || category == "header" || category == "query"
+}
+
+/// Resolves the matching rules that apply to a path. This is implemented by `MatchingRules`, which
+/// parses the path expressions of a category each time it is used, and by the request matching
+/// plans, which parse them once when the plan is compiled.
+pub trait RuleResolver {
+
+  /// The matching rules being resolved
+  fn matching_rules(&self) -> &MatchingRules;
+
+  /// Returns the rules of a category that is matched by path (body, header or query), with their
+  /// path expressions parsed
+  fn compiled_category(&self, category: &str) -> Option<Cow<CompiledCategory>>;
+
+  /// If there is a matcher defined for the category and path
+  fn matcher_is_defined(&self, category: &str, path: &Vec<String>) -> bool {
+    if is_path_category(category) {
+      match self.compiled_category(category) {
+        Some(compiled) => compiled.rules.iter().any(|&(ref path_exp, _)| path_exp.weight(path, false) > 0),
+        None => false
+      }
+    } else {
+      match self.matching_rules().rules.get(category) {
+        Some(category) => !category.is_empty(),
+        None => false
+      }
+    }
+  }
+
+  /// If there is a wildcard matcher defined for the category and path
+  fn wildcard_matcher_is_defined(&self, category: &str, path: &Vec<String>) -> bool {
+    if is_path_category(category) {
+      match self.compiled_category(category) {
+        Some(compiled) => compiled.rules.iter().any(|&(ref path_exp, _)| {
+          path_exp.expression.ends_with(".*") && if category == "body" {
+            path_exp.weight(path, true) > 0
+          } else {
+            path.len() == 1 && path[0] == path_exp.expression
+          }
+        }),
+        None => false
+      }
+    } else {
+      match self.matching_rules().rules.get(category) {
+        Some(category) => category.rules.keys().any(|path_exp| path_exp.ends_with(".*")),
+        None => false
+      }
+    }
+  }
+
+  /// Returns a `Category` filtered with all rules that match the given path.
+  fn resolve_matchers(&self, category: &str, path: &Vec<String>) -> Option<Category> {
+    if is_path_category(category) {
+      self.compiled_category(category).map(|compiled| compiled.filter(|path_exp| path_exp.weight(path, false) > 0))
+    } else {
+      self.matching_rules().rules_for_category(&s!(category))
+    }
+  }
+
+  /// Returns a list of rules from the body category that match the given path
+  fn resolve_body_matchers_by_path(&self, path: &Vec<String>) -> Option<RuleList> {
+    match self.compiled_category("body") {
+      Some(compiled) => compiled.max_by_path(path, |_| true),
+      None => None
+    }
+  }
+
+  /// Returns all the rules from the body category that apply to the given path, along with the
+  /// path expression and weight of each. They are ordered from the rules that will be selected
+  /// for the path (the first entry) to the least specific.
+  fn resolve_body_matchers_with_weights(&self, path: &Vec<String>) -> Vec<(String, usize, RuleList)> {
+    match self.compiled_category("body") {
+      Some(compiled) => compiled.rules_by_weight(path, |_| true).into_iter()
+        .map(|(path_exp, weight, rules)| (path_exp.expression.clone(), weight, rules.clone()))
+        .collect(),
+      None => vec![]
     }
   }
+
+  /// Returns a list of rules from the body category that are defined for the given path itself,
+  /// ignoring any rules that only apply to it by being defined on a parent path
+  fn resolve_body_matchers_for_exact_path(&self, path: &Vec<String>) -> Option<RuleList> {
+    match self.compiled_category("body") {
+      Some(compiled) => compiled.max_by_path(path, |path_exp| path_exp.weight(path, true) > 0),
+      None => None
+    }
+  }
+
 }
 
 /// Set of all matching rules
@@ -164,6 +348,17 @@ impl MatchingRule {
     }
   }
 
+  /// Builds a `MatchingRule` from a `Value` struct, returning the problems with the JSON instead
+  /// of ignoring the rule
+  pub fn from_json_strict(value: &Value) -> Result<MatchingRule, Vec<PactParseError>> {
+    let errors = validate_matching_rule("$", value);
+    if errors.is_empty() {
+      MatchingRule::from_json(value).ok_or_else(|| vec![PactParseError::new("$", "Unrecognised matching rule", Some(value))])
+    } else {
+      Err(errors)
+    }
+  }
+
   /// Builds a `MatchingRule` from a `Value` struct used by language integrations
   pub fn from_integration_json(m: &Map<String, Value>) -> Option<MatchingRule> {
     match m.get("pact:matcher:type") {
@@ -368,19 +563,15 @@ impl Category {
     }
   }
 
-  fn max_by_path(&self, path: &Vec<String>) -> Option<RuleList> {
-    self.rules.iter().map(|(k, v)| (k, v, calc_path_weight(k.clone(), path)))
-      .filter(|&(_, _, w)| w > 0)
-      .max_by_key(|&(_, _, w)| w)
-      .map(|(_, v, _)| v.clone())
-  }
-
   /// Returns a JSON Value representation in V3 format
   pub fn to_v3_json(&self) -> Value {
-    Value::Object(self.rules.iter().fold(serde_json::Map::new(), |mut map, (category, rulelist)| {
-      map.insert(category.clone(), rulelist.to_v3_json());
-      map
-    }))
+    match self.rules.get("") {
+      Some(rulelist) if self.name == "path" => rulelist.to_v3_json(),
+      _ => Value::Object(self.rules.iter().fold(serde_json::Map::new(), |mut map, (category, rulelist)| {
+        map.insert(category.clone(), rulelist.to_v3_json());
+        map
+      }))
+    }
   }
 
   /// Returns a JSON Value representation in V2 format
@@ -391,9 +582,17 @@ impl Category {
       for (k, v) in self.rules.clone() {
         map.insert(k.replace("$", "$.body"), v.to_v2_json());
       }
+    } else if self.name == "header" {
+      for (k, v) in self.rules.clone() {
+        map.insert(format!("$.headers.{}", k), v.to_v2_json());
+      }
     } else {
       for (k, v) in self.rules.clone() {
-        map.insert(format!("$.{}.{}", self.name, k), v.to_v2_json());
+        if k.is_empty() {
+          map.insert(format!("$.{}", self.name), v.to_v2_json());
+        } else {
+          map.insert(format!("$.{}.{}", self.name, k), v.to_v2_json());
+        }
       }
     }
 
@@ -454,51 +653,35 @@ impl MatchingRules {
 
     /// If there is a matcher defined for the category and path
     pub fn matcher_is_defined(&self, category: &str, path: &Vec<String>) -> bool {
-      match self.resolve_matchers(category, path) {
-        Some(ref category) => !category.is_empty(),
-        None => false
-      }
+      RuleResolver::matcher_is_defined(self, category, path)
     }
 
     /// If there is a wildcard matcher defined for the category and path
     pub fn wildcard_matcher_is_defined(&self, category: &str, path: &Vec<String>) -> bool {
-      match self.resolve_wildcard_matchers(category, path) {
-        Some(ref category) => !category.filter(|&(val, _)| val.ends_with(".*")).is_empty(),
-        None => false
-      }
+      RuleResolver::wildcard_matcher_is_defined(self, category, path)
     }
 
     /// Returns a `Category` filtered with all rules that match the given path.
     pub fn resolve_matchers(&self, category: &str, path: &Vec<String>) -> Option<Category> {
-      if category == "body" || category == "header" || category == "query" {
-        self.rules_for_category(&s!(category)).map(|category| category.filter(|&(val, _)| {
-          calc_path_weight(val.clone(), path) > 0
-        }))
-      } else {
-        self.rules_for_category(&s!(category))
-      }
+      RuleResolver::resolve_matchers(self, category, path)
     }
 
     /// Returns a list of rules from the body category that match the given path
     pub fn resolve_body_matchers_by_path(&self, path: &Vec<String>) -> Option<RuleList> {
-      match self.rules_for_category(&s!("body")) {
-        Some(category) => category.max_by_path(path),
-        None => None
-      }
+      RuleResolver::resolve_body_matchers_by_path(self, path)
     }
 
-    fn resolve_wildcard_matchers(&self, category: &str, path: &Vec<String>) -> Option<Category> {
-      if category == "body" {
-        self.rules_for_category(&s!(category)).map(|category| category.filter(|&(val, _)| {
-          calc_path_weight(val.clone(), path) > 0 && path_length(val.clone()) == path.len()
-        }))
-      } else if category == "header" || category == "query" {
-        self.rules_for_category(&s!(category)).map(|category| category.filter(|&(val, _)| {
-          path.len() == 1 && path[0] == *val
-        }))
-      } else {
-        self.rules_for_category(&s!(category))
-      }
+    /// Returns all the rules from the body category that apply to the given path, along with the
+    /// path expression and weight of each. They are ordered from the rules that will be selected
+    /// for the path (the first entry) to the least specific.
+    pub fn resolve_body_matchers_with_weights(&self, path: &Vec<String>) -> Vec<(String, usize, RuleList)> {
+      RuleResolver::resolve_body_matchers_with_weights(self, path)
+    }
+
+    /// Returns a list of rules from the body category that are defined for the given path itself,
+    /// ignoring any rules that only apply to it by being defined on a parent path
+    pub fn resolve_body_matchers_for_exact_path(&self, path: &Vec<String>) -> Option<RuleList> {
+      RuleResolver::resolve_body_matchers_for_exact_path(self, path)
     }
 
     fn load_from_v2_map(&mut self, map: &serde_json::Map<String, Value>) {
@@ -594,6 +777,17 @@ impl MatchingRules {
   }
 }
 
+impl RuleResolver for MatchingRules {
+  fn matching_rules(&self) -> &MatchingRules {
+    self
+  }
+
+  /// Parses the path expressions of the category each time it is called
+  fn compiled_category(&self, category: &str) -> Option<Cow<CompiledCategory>> {
+    self.rules.get(category).map(|category| Cow::Owned(CompiledCategory::new(category)))
+  }
+}
+
 impl Hash for MatchingRules {
   fn hash<H: Hasher>(&self, state: &mut H) {
     for (k, v) in self.rules.iter() {
@@ -672,10 +866,18 @@ macro_rules! matchingrules {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::{calc_path_weight, matches_token};
+    use super::matches_token;
     use expectest::prelude::*;
     use serde_json::Value;
 
+    fn calc_path_weight(path_exp: String, path: &Vec<String>) -> usize {
+      CompiledPathExp::new(&path_exp).weight(path, false)
+    }
+
+    fn calc_exact_path_weight(path_exp: String, path: &Vec<String>) -> usize {
+      CompiledPathExp::new(&path_exp).weight(path, true)
+    }
+
     #[test]
     fn rules_are_empty_when_there_are_no_categories() {
         expect!(MatchingRules::default().is_empty()).to(be_true());
@@ -847,6 +1049,19 @@ mod tests {
     }));
   }
 
+  #[test]
+  fn path_matching_rules_to_json_test() {
+    let matching_rules = matchingrules!{
+      "path" => { "" => [ MatchingRule::Regex(s!("/path/\\d+")) ] }
+    };
+    expect!(matchers_to_json(&matching_rules, &PactSpecification::V3)).to(be_equal_to(json!({
+      "path": { "combine": "AND", "matchers": [ { "match": "regex", "regex": "/path/\\d+" } ] }
+    })));
+    expect!(matchers_to_json(&matching_rules, &PactSpecification::V2)).to(be_equal_to(json!({
+      "$.path": { "match": "regex", "regex": "/path/\\d+" }
+    })));
+  }
+
   #[test]
   fn matching_rule_from_json_test() {
     expect!(MatchingRule::from_json(&Value::from_str("\"test string\"").unwrap())).to(be_none());
@@ -1006,6 +1221,24 @@ mod tests {
     expect!(matchers.wildcard_matcher_is_defined("body", &vec![s!("$"), s!("a"), s!("b"), s!("c")])).to(be_false());
   }
 
+  #[test]
+  fn resolve_body_matchers_for_exact_path_ignores_rules_on_parent_paths() {
+    let matchers = matchingrules!{
+      "body" => {
+        "$.a" => [ MatchingRule::Regex(s!("1")) ],
+        "$.a.*" => [ MatchingRule::Regex(s!("2")) ],
+        "$.a.b" => [ MatchingRule::Regex(s!("3")) ]
+      }
+    };
+    expect!(matchers.resolve_body_matchers_for_exact_path(&vec![s!("$"), s!("a")])).to(
+      be_some().value(RuleList::new(MatchingRule::Regex(s!("1")))));
+    expect!(matchers.resolve_body_matchers_for_exact_path(&vec![s!("$"), s!("a"), s!("b")])).to(
+      be_some().value(RuleList::new(MatchingRule::Regex(s!("3")))));
+    expect!(matchers.resolve_body_matchers_for_exact_path(&vec![s!("$"), s!("a"), s!("c")])).to(
+      be_some().value(RuleList::new(MatchingRule::Regex(s!("2")))));
+    expect!(matchers.resolve_body_matchers_for_exact_path(&vec![s!("$"), s!("a"), s!("c"), s!("d")])).to(be_none());
+  }
+
   #[test]
   fn matches_token_test_with_root() {
     expect!(matches_token(&s!("$"), &PathToken::Root)).to(be_equal_to(2));
@@ -1081,6 +1314,67 @@ mod tests {
     expect!(calc_path_weight(s!("$[*]"), &vec![s!("$"), s!("name")]) > 0).to(be_false());
   }
 
+  #[test]
+  fn matches_path_matches_with_recursive_descent() {
+    let path = vec![s!("$"), s!("data"), s!("items"), s!("0"), s!("id")];
+    expect!(calc_path_weight(s!("$..id"), &path)).to(be_equal_to(4));
+    expect!(calc_path_weight(s!("$.data..id"), &path)).to(be_equal_to(8));
+    expect!(calc_path_weight(s!("$..items[*].id"), &path)).to(be_equal_to(8));
+    expect!(calc_path_weight(s!("$.data.items[*].id"), &path)).to(be_equal_to(16));
+    expect!(calc_path_weight(s!("$..name"), &path)).to(be_equal_to(0));
+    expect!(calc_path_weight(s!("$.other..id"), &path)).to(be_equal_to(0));
+    expect!(calc_path_weight(s!("$..data.items"), &path)).to(be_equal_to(8));
+    expect!(calc_exact_path_weight(s!("$..data.items"), &path)).to(be_equal_to(0));
+    expect!(calc_exact_path_weight(s!("$..id"), &path)).to(be_equal_to(4));
+    expect!(calc_path_weight(s!("$..id"), &vec![s!("$"), s!("id")])).to(be_equal_to(4));
+  }
+
+  #[test]
+  fn more_specific_rules_are_selected_over_recursive_descent() {
+    let matching_rules = matchingrules!{
+      "body" => {
+        "$..id" => [ MatchingRule::Regex(s!("\\d+")) ],
+        "$.*.id" => [ MatchingRule::Type ],
+        "$.data.items[*].id" => [ MatchingRule::Integer ]
+      }
+    };
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("items"), s!("0"), s!("id")]))
+      .to(be_some().value(RuleList::new(MatchingRule::Integer)));
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("id")]))
+      .to(be_some().value(RuleList::new(MatchingRule::Type)));
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("other"), s!("id")]))
+      .to(be_some().value(RuleList::new(MatchingRule::Regex(s!("\\d+")))));
+  }
+
+  #[test]
+  fn rules_for_the_whole_path_are_selected_over_rules_on_a_parent() {
+    let matching_rules = matchingrules!{
+      "body" => {
+        "$.data" => [ MatchingRule::Type ],
+        "$.data.items" => [ MatchingRule::MinType(1) ],
+        "$..id" => [ MatchingRule::Integer ]
+      }
+    };
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("items"), s!("0"), s!("id")]))
+      .to(be_some().value(RuleList::new(MatchingRule::Integer)));
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("items"), s!("0"), s!("name")]))
+      .to(be_some().value(RuleList::new(MatchingRule::MinType(1))));
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("data"), s!("items")]))
+      .to(be_some().value(RuleList::new(MatchingRule::MinType(1))));
+  }
+
+  #[test]
+  fn rules_on_a_parent_with_a_higher_weight_are_selected_when_there_is_no_recursive_descent() {
+    let matching_rules = matchingrules!{
+      "body" => {
+        "$.*.*[*].id" => [ MatchingRule::Integer ],
+        "$.item1.level[1]" => [ MatchingRule::Type ]
+      }
+    };
+    expect!(matching_rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("item1"), s!("level"), s!("1"), s!("id")]))
+      .to(be_some().value(RuleList::new(MatchingRule::Type)));
+  }
+
   #[test]
   fn min_and_max_values_get_serialised_to_json_as_numbers() {
     expect!(MatchingRule::MinType(1).to_json().to_string()).to(be_equal_to("{\"match\":\"type\",\"min\":1}"));
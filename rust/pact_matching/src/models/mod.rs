@@ -9,25 +9,30 @@ use super::strip_whitespace;
 use onig::Regex;
 use semver::Version;
 use itertools::Itertools;
-use std::io::{self, Error, ErrorKind};
-use std::io::prelude::*;
+use std::io::{self, Read, Error, ErrorKind};
 use std::fs;
 use std::fs::File;
 use std::path::Path;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 use std::str;
+use std::str::FromStr;
 use std::default::Default;
 use base64::{encode, decode};
 use std::fmt::{Display, Formatter};
-use models::http_utils::HttpAuth;
+use models::http_utils::{HttpAuth, FetchOptions};
+use models::parse_errors::PactParseError;
 use super::json::value_of;
 
 pub mod json_utils;
+pub mod expression_parser;
 pub mod xml_utils;
 #[macro_use] pub mod matchingrules;
 #[macro_use] pub mod generators;
 pub mod http_utils;
+pub mod file_utils;
+pub mod archive;
+pub mod parse_errors;
 
 /// Version of the library
 pub const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
@@ -130,7 +135,7 @@ impl Provider {
 
 /// Enum that defines the four main states that a body of a request and response can be in a pact
 /// file.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum OptionalBody {
     /// A body is missing if it is not present in the pact file
     Missing,
@@ -139,8 +144,39 @@ pub enum OptionalBody {
     /// A JSON body that is the null value. This state is to protect other language implementations
     /// from null values. It is treated as `Empty`.
     Null,
-    /// A non-empty body that is present in the pact file.
-    Present(Vec<u8>)
+    /// A non-empty body that is present in the pact file, along with the content type of the body
+    /// if it is known. The content type is recorded for binary bodies, so that they can be written
+    /// to a pact file and served back with the same content type. It is not used when comparing
+    /// bodies, so a body read from a pact file is equal to the same body built in memory.
+    Present(Vec<u8>, Option<String>)
+}
+
+impl PartialEq for OptionalBody {
+    fn eq(&self, other: &OptionalBody) -> bool {
+        match (self, other) {
+            (&OptionalBody::Missing, &OptionalBody::Missing) => true,
+            (&OptionalBody::Empty, &OptionalBody::Empty) => true,
+            (&OptionalBody::Null, &OptionalBody::Null) => true,
+            (&OptionalBody::Present(ref a, _), &OptionalBody::Present(ref b, _)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl Eq for OptionalBody {}
+
+impl Hash for OptionalBody {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            OptionalBody::Missing => 0u8.hash(state),
+            OptionalBody::Empty => 1u8.hash(state),
+            OptionalBody::Null => 2u8.hash(state),
+            OptionalBody::Present(ref body, _) => {
+                3u8.hash(state);
+                body.hash(state);
+            }
+        }
+    }
 }
 
 impl OptionalBody {
@@ -148,7 +184,7 @@ impl OptionalBody {
     /// If the body is present in the pact file and not empty or null.
     pub fn is_present(&self) -> bool {
         match *self {
-            OptionalBody::Present(_) => true,
+            OptionalBody::Present(_, _) => true,
             _ => false
         }
     }
@@ -156,15 +192,23 @@ impl OptionalBody {
     /// Returns the body if present, otherwise returns the empty Vec.
     pub fn value(&self) -> Vec<u8> {
         match *self {
-            OptionalBody::Present(ref s) => s.clone(),
+            OptionalBody::Present(ref s, _) => s.clone(),
             _ => vec![]
         }
     }
 
+    /// Returns the content type of the body if it is present and known.
+    pub fn content_type(&self) -> Option<String> {
+        match *self {
+            OptionalBody::Present(_, ref content_type) => content_type.clone(),
+            _ => None
+        }
+    }
+
   /// Returns the body if present as a string, otherwise returns the empty string.
   pub fn str_value(&self) -> &str {
     match *self {
-      OptionalBody::Present(ref s) => str::from_utf8(s).unwrap_or(""),
+      OptionalBody::Present(ref s, _) => str::from_utf8(s).unwrap_or(""),
       _ => ""
     }
   }
@@ -175,7 +219,7 @@ impl From<String> for OptionalBody {
     if s.is_empty() {
       OptionalBody::Empty
     } else {
-      OptionalBody::Present(Vec::from(s.as_bytes()))
+      OptionalBody::Present(Vec::from(s.as_bytes()), None)
     }
   }
 }
@@ -185,7 +229,7 @@ impl <'a> From<&'a str> for OptionalBody {
     if s.is_empty() {
       OptionalBody::Empty
     } else {
-      OptionalBody::Present(Vec::from(s.as_bytes()))
+      OptionalBody::Present(Vec::from(s.as_bytes()), None)
     }
   }
 }
@@ -196,7 +240,7 @@ impl Display for OptionalBody {
       OptionalBody::Missing => write!(f, "Missing"),
       OptionalBody::Empty => write!(f, "Empty"),
       OptionalBody::Null => write!(f, "Null"),
-      OptionalBody::Present(ref s) => write!(f, "Present({} bytes)", s.len())
+      OptionalBody::Present(ref s, _) => write!(f, "Present({} bytes)", s.len())
     }
   }
 }
@@ -209,6 +253,15 @@ lazy_static! {
 
     static ref JSON_CONTENT_TYPE: Regex = Regex::new("application/.*json.*").unwrap();
     static ref XML_CONTENT_TYPE: Regex = Regex::new("application/.*xml").unwrap();
+    static ref YAML_CONTENT_TYPE: Regex = Regex::new("(application|text)/.*yaml").unwrap();
+    static ref TEXT_CONTENT_TYPE: Regex = Regex::new("^(text/.*|application/(x-www-form-urlencoded|javascript|csv))$").unwrap();
+}
+
+/// If bodies with the given content type can be written to a pact file as text. All other bodies
+/// are written base64 encoded.
+fn is_text_content_type(content_type: &str) -> bool {
+    TEXT_CONTENT_TYPE.is_match(content_type) || JSON_CONTENT_TYPE.is_match(content_type) ||
+        XML_CONTENT_TYPE.is_match(content_type) || YAML_CONTENT_TYPE.is_match(content_type)
 }
 
 /// Enumeration of general content types
@@ -218,6 +271,8 @@ pub enum DetectedContentType {
     Json,
     /// XML content types
     Xml,
+    /// YAML content types
+    Yaml,
     /// All other content types
     Text
 }
@@ -257,25 +312,27 @@ pub trait HttpPart {
     fn generators(&self) -> &generators::Generators;
 
     /// Determine the content type of the HTTP part. If a `Content-Type` header is present, the
-    /// value of that header will be returned. Otherwise, the body will be inspected.
+    /// value of that header will be returned. Otherwise, the content type of the body is used if
+    /// known, and failing that the body will be inspected.
     fn content_type(&self) -> String {
         match self.lookup_header_value(&s!("content-type")) {
             Some(ref h) => match strip_whitespace::<Vec<&str>>(h, ";").first() {
                 Some(v) => s!(*v),
                 None => self.detect_content_type()
             },
-            None => self.detect_content_type()
+            None => self.body().content_type().unwrap_or_else(|| self.detect_content_type())
         }
     }
 
     /// Tries to detect the content type of the body by matching some regular expressions against
-    /// the first 32 characters. Default to `text/plain` if no match is found.
+    /// the first 32 characters. Defaults to `application/octet-stream` if the body is not valid
+    /// UTF-8, and to `text/plain` if no match is found.
     fn detect_content_type(&self) -> String {
         match *self.body() {
-            OptionalBody::Present(ref body) => {
+            OptionalBody::Present(ref body, _) => {
                 let s: String = match str::from_utf8(body) {
                   Ok(s) => s.to_string(),
-                  Err(_) => String::new()
+                  Err(_) => return s!("application/octet-stream")
                 };
                 debug!("Detecting content type from contents: '{}'", s);
                 if is_match(&XMLREGEXP, s.as_str()) {
@@ -301,6 +358,8 @@ pub trait HttpPart {
             DetectedContentType::Json
         } else if XML_CONTENT_TYPE.is_match(&content_type[..]) {
             DetectedContentType::Xml
+        } else if YAML_CONTENT_TYPE.is_match(&content_type[..]) {
+            DetectedContentType::Yaml
         } else {
             DetectedContentType::Text
         }
@@ -445,6 +504,11 @@ enum JsonParsable {
     KeyValue(HashMap<String, Value>)
 }
 
+/// The name of the attribute that marks a body as being encoded in the pact file (i.e. `bodyEncoding`)
+fn encoding_fieldname(fieldname: &str) -> String {
+    format!("{}Encoding", fieldname)
+}
+
 fn body_from_json(request: &Value, fieldname: &str, headers: &Option<HashMap<String, Vec<String>>>) -> OptionalBody {
     let content_type = match headers {
       &Some(ref h) => match h.iter().find(|kv| kv.0.to_lowercase() == s!("content-type")) {
@@ -459,35 +523,80 @@ fn body_from_json(request: &Value, fieldname: &str, headers: &Option<HashMap<Str
       &None => None
     };
 
+    let encoding = request.get(&encoding_fieldname(fieldname)).and_then(|v| v.as_str()).map(|v| v.to_lowercase());
+
     match request.get(fieldname) {
         Some(v) => match *v {
             Value::String(ref s) => {
                 if s.is_empty() {
                   OptionalBody::Empty
+                } else if encoding == Some(s!("base64")) {
+                  match decode(s) {
+                    Ok(bytes) => OptionalBody::Present(bytes, Some(content_type.unwrap_or(s!("application/octet-stream")))),
+                    Err(err) => {
+                      warn!("Failed to decode the base64 encoded {}, using it as is: {}", fieldname, err);
+                      OptionalBody::Present(s.clone().into(), content_type)
+                    }
+                  }
                 } else {
                   let content_type = content_type.unwrap_or(s!("text/plain"));
                   if JSON_CONTENT_TYPE.is_match(&content_type) {
                     match serde_json::from_str::<JsonParsable>(&s) {
-                      Ok(_) => OptionalBody::Present(s.clone().into()),
-                      Err(_) => OptionalBody::Present(format!("\"{}\"", s).into())
+                      Ok(_) => OptionalBody::Present(s.clone().into(), None),
+                      Err(_) => OptionalBody::Present(format!("\"{}\"", s).into(), None)
                     }
-                  } else if content_type.starts_with("text/") {
-                    OptionalBody::Present(s.clone().into())
+                  } else if is_text_content_type(&content_type) {
+                    OptionalBody::Present(s.clone().into(), None)
                   } else {
+                    // Pact files written before the encoding marker was added (and by other
+                    // implementations) have binary bodies base64 encoded without the marker
                     match decode(s) {
-                      Ok(bytes) => OptionalBody::Present(bytes.clone()),
-                      Err(_) => OptionalBody::Present(s.clone().into())
+                      Ok(bytes) => OptionalBody::Present(bytes, Some(content_type)),
+                      Err(_) => OptionalBody::Present(s.clone().into(), None)
                     }
                   }
                 }
             },
             Value::Null => OptionalBody::Null,
-            _ => OptionalBody::Present(v.to_string().into())
+            _ => OptionalBody::Present(v.to_string().into(), None)
         },
         None => OptionalBody::Missing
     }
 }
 
+/// Adds the body to the JSON map. JSON bodies are written as JSON, and text bodies as strings. All
+/// other bodies are written base64 encoded, with an encoding attribute (i.e. `"bodyEncoding": "base64"`)
+/// so they can be decoded when the pact file is read.
+fn body_to_json(body: &OptionalBody, content_type: &String, fieldname: &str, map: &mut serde_json::Map<String, Value>) {
+    match *body {
+        OptionalBody::Present(ref body, _) => {
+            if content_type == "application/json" {
+                match serde_json::from_slice(body) {
+                    Ok(json_body) => { map.insert(s!(fieldname), json_body); },
+                    Err(err) => {
+                        warn!("Failed to parse json body: {}", err);
+                        map.insert(s!(fieldname), Value::String(encode(body)));
+                        map.insert(encoding_fieldname(fieldname), json!("base64"));
+                    }
+                }
+            } else {
+                match str::from_utf8(body) {
+                    Ok(s) if is_text_content_type(content_type) => {
+                        map.insert(s!(fieldname), Value::String(s.to_string()));
+                    },
+                    _ => {
+                        map.insert(s!(fieldname), Value::String(encode(body)));
+                        map.insert(encoding_fieldname(fieldname), json!("base64"));
+                    }
+                }
+            }
+        },
+        OptionalBody::Empty => { map.insert(s!(fieldname), Value::String(s!(""))); },
+        OptionalBody::Missing => (),
+        OptionalBody::Null => { map.insert(s!(fieldname), Value::Null); }
+    }
+}
+
 /// Converts a query string map into a query string
 pub fn build_query_string(query: HashMap<String, Vec<String>>) -> String {
     query.into_iter()
@@ -580,6 +689,17 @@ impl Request {
         }
     }
 
+    /// Builds a `Request` from a `Value` struct, returning all the problems found with the JSON
+    /// instead of falling back to the defaults.
+    pub fn from_json_strict(request_json: &Value, spec_version: &PactSpecification) -> Result<Request, Vec<PactParseError>> {
+        let errors = parse_errors::validate_request("$", request_json, spec_version);
+        if errors.is_empty() {
+            Ok(Request::from_json(request_json, spec_version))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Converts this `Request` to a `Value` struct.
     pub fn to_json(&self, spec_version: &PactSpecification) -> Value {
         let mut json = json!({
@@ -594,27 +714,7 @@ impl Request {
             if self.headers.is_some() {
                 map.insert(s!("headers"), headers_to_json(&self.headers.clone().unwrap()));
             }
-            match self.body {
-                OptionalBody::Present(ref body) => {
-                    if self.content_type() == "application/json" {
-                        match serde_json::from_slice(body) {
-                            Ok(json_body) => { map.insert(s!("body"), json_body); },
-                            Err(err) => {
-                                warn!("Failed to parse json body: {}", err);
-                                map.insert(s!("body"), Value::String(encode(body)));
-                            }
-                        }
-                    } else {
-                        match str::from_utf8(body) {
-                        Ok(s) =>map.insert(s!("body"), Value::String(s.to_string())),
-                        Err(_) => map.insert(s!("body"), Value::String(encode(body)))
-                      };
-                    }
-                },
-                OptionalBody::Empty => { map.insert(s!("body"), Value::String(s!(""))); },
-                OptionalBody::Missing => (),
-                OptionalBody::Null => { map.insert(s!("body"), Value::Null); }
-            }
+            body_to_json(&self.body, &self.content_type(), "body", map);
             if self.matching_rules.is_not_empty() {
                 map.insert(s!("matchingRules"), matchingrules::matchers_to_json(
                 &self.matching_rules.clone(), spec_version));
@@ -680,7 +780,7 @@ impl Response {
     /// Build a `Response` from a `Value` struct.
     pub fn from_json(response: &Value, _: &PactSpecification) -> Response {
         let status_val = match response.get("status") {
-            Some(v) => v.as_u64().unwrap() as u16,
+            Some(v) => v.as_u64().map(|status| status as u16).unwrap_or(200),
             None => 200
         };
         let headers = headers_from_json(response);
@@ -693,6 +793,17 @@ impl Response {
         }
     }
 
+    /// Build a `Response` from a `Value` struct, returning all the problems found with the JSON
+    /// instead of falling back to the defaults.
+    pub fn from_json_strict(response: &Value, spec_version: &PactSpecification) -> Result<Response, Vec<PactParseError>> {
+        let errors = parse_errors::validate_response("$", response);
+        if errors.is_empty() {
+            Ok(Response::from_json(response, spec_version))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Returns a default response: Status 200
     #[deprecated(since="0.5.4", note="please use `default()` from the standard Default trait instead")]
     pub fn default_response() -> Response {
@@ -710,27 +821,7 @@ impl Response {
             if self.headers.is_some() {
                 map.insert(s!("headers"), headers_to_json(&self.headers.clone().unwrap()));
             }
-            match self.body {
-                OptionalBody::Present(ref body) => {
-                    if self.content_type() == "application/json" {
-                        match serde_json::from_slice(body) {
-                            Ok(json_body) => { map.insert(s!("body"), json_body); },
-                            Err(err) => {
-                                warn!("Failed to parse json body: {}", err);
-                                map.insert(s!("body"), Value::String(encode(body)));
-                            }
-                        }
-                    } else {
-                      match str::from_utf8(body) {
-                        Ok(s) => map.insert(s!("body"), Value::String(s.to_string())),
-                        Err(_) => map.insert(s!("body"), Value::String(encode(body)))
-                      };
-                    }
-                },
-                OptionalBody::Empty => { map.insert(s!("body"), Value::String(s!(""))); },
-                OptionalBody::Missing => (),
-                OptionalBody::Null => { map.insert(s!("body"), Value::Null); }
-            }
+            body_to_json(&self.body, &self.content_type(), "body", map);
             if self.matching_rules.is_not_empty() {
                 map.insert(s!("matchingRules"), matchingrules::matchers_to_json(
               &self.matching_rules.clone(), spec_version));
@@ -817,7 +908,7 @@ impl Default for Response {
 pub mod provider_states;
 
 /// Struct that defined an interaction conflict
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PactConflict {
     /// Description of the interactions
     pub interaction: String,
@@ -825,6 +916,62 @@ pub struct PactConflict {
     pub description: String
 }
 
+/// Strategy to use when merging a pact into an existing pact with conflicting interactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Fail the merge, returning all the conflicts that were found
+    Fail,
+    /// The interactions from the pact being merged in replace any existing interactions with the
+    /// same description and provider states
+    LastWriterWins,
+    /// Keep both of the conflicting interactions, renaming the description of the one being merged in
+    KeepBoth
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        MergeStrategy::Fail
+    }
+}
+
+impl FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(MergeStrategy::Fail),
+            "last-writer-wins" => Ok(MergeStrategy::LastWriterWins),
+            "keep-both" => Ok(MergeStrategy::KeepBoth),
+            _ => Err(format!("'{}' is not a valid merge strategy, expected one of fail, last-writer-wins or keep-both", s))
+        }
+    }
+}
+
+/// Error returned when two pacts can not be merged
+#[derive(Debug, Clone, PartialEq)]
+pub enum PactMergeError {
+    /// The pacts are for a different consumer or provider
+    DifferentParticipants,
+    /// There were conflicts between the interactions of the two pacts
+    Conflicts(Vec<PactConflict>)
+}
+
+impl Display for PactMergeError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            PactMergeError::DifferentParticipants =>
+                write!(f, "Unable to merge pacts, as they have different consumers or providers"),
+            PactMergeError::Conflicts(ref conflicts) => {
+                write!(f, "Unable to merge pacts, as there were {} conflict(s) between the interactions", conflicts.len())?;
+                for conflict in conflicts {
+                    write!(f, "\n  - Interaction '{}': {}", conflict.interaction, conflict.description)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Struct that defines an interaction (request and response pair)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Interaction {
@@ -870,6 +1017,18 @@ impl Interaction {
         }
     }
 
+    /// Constructs an `Interaction` from the `Value` struct, returning all the problems found with
+    /// the JSON instead of falling back to the defaults. The paths of the errors are relative to
+    /// the interaction.
+    pub fn from_json_strict(index: usize, pact_json: &Value, spec_version: &PactSpecification) -> Result<Interaction, Vec<PactParseError>> {
+        let errors = parse_errors::validate_interaction("$", pact_json, spec_version);
+        if errors.is_empty() {
+            Ok(Interaction::from_json(index, pact_json, spec_version))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Converts this interaction to a `Value` struct.
     pub fn to_json(&self, spec_version: &PactSpecification) -> Value {
         let mut value = json!({
@@ -982,6 +1141,13 @@ fn parse_interactions(pact_json: &Value, spec_version: PactSpecification) -> Vec
     }
 }
 
+/// Orders interactions by the names of their provider states and then by their description.
+fn interaction_ordering(a: &Interaction, b: &Interaction) -> Ordering {
+    Ord::cmp(&a.provider_states.iter().map(|p| p.name.clone()).collect::<Vec<String>>(),
+        &b.provider_states.iter().map(|p| p.name.clone()).collect::<Vec<String>>())
+        .then_with(|| Ord::cmp(&a.description, &b.description))
+}
+
 fn determine_spec_version(file: &String, metadata: &BTreeMap<String, BTreeMap<String, String>>) -> PactSpecification {
     let specification = if metadata.get("pact-specification").is_none()
         { metadata.get("pactSpecification") } else { metadata.get("pact-specification") };
@@ -1052,6 +1218,17 @@ impl Pact {
         }
     }
 
+    /// Creates a `Pact` from a `Value` struct, returning all the problems found with the JSON
+    /// instead of falling back to the defaults.
+    pub fn from_json_strict(file: &String, pact_json: &Value) -> Result<Pact, Vec<PactParseError>> {
+        let errors = parse_errors::validate_pact(pact_json);
+        if errors.is_empty() {
+            Ok(Pact::from_json(file, pact_json))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Converts this pact to a `Value` struct.
     pub fn to_json(&self, pact_spec: PactSpecification) -> Value {
         json!({
@@ -1062,6 +1239,30 @@ impl Pact {
         })
     }
 
+    /// Converts this pact to a `Value` struct in canonical form. The interactions are sorted by
+    /// provider states and description (falling back to their JSON form for interactions that
+    /// only differ in their request or response), and the keys of all objects are sorted, so the
+    /// same pact will always serialise to the same bytes.
+    pub fn to_canonical_json(&self, pact_spec: PactSpecification) -> Value {
+        let mut interactions = self.interactions.iter()
+            .map(|i| (i, i.to_json(&pact_spec)))
+            .collect::<Vec<(&Interaction, Value)>>();
+        interactions.sort_by(|a, b| interaction_ordering(a.0, b.0)
+            .then_with(|| Ord::cmp(&a.1.to_string(), &b.1.to_string())));
+        json_utils::sort_json_keys(&json!({
+            s!("consumer"): self.consumer.to_json(),
+            s!("provider"): self.provider.to_json(),
+            s!("interactions"): Value::Array(interactions.into_iter().map(|i| i.1).collect()),
+            s!("metadata"): json!(self.metadata_to_json(&pact_spec))
+        }))
+    }
+
+    /// Returns the canonical form of this pact as a pretty-printed JSON string. This is the form
+    /// that is written to pact files.
+    pub fn to_canonical_string(&self, pact_spec: PactSpecification) -> String {
+        serde_json::to_string_pretty(&self.to_canonical_json(pact_spec)).unwrap()
+    }
+
     /// Creates a BTreeMap of the metadata of this pact.
     pub fn metadata_to_json(&self, pact_spec: &PactSpecification) -> BTreeMap<String, Value> {
         let mut md_map: BTreeMap<String, Value> = self.metadata.iter()
@@ -1086,46 +1287,83 @@ impl Pact {
     /// Returns an error if there is a merge conflict, which will occur if any interaction has the
     /// same description and provider state and the requests and responses are different.
     pub fn merge(&self, pact: &Pact) -> Result<Pact, String> {
-        if self.consumer.name == pact.consumer.name && self.provider.name == pact.provider.name {
-            let conflicts = iproduct!(self.interactions.clone(), pact.interactions.clone())
-                .map(|i| i.0.conflicts_with(&i.1))
-                .filter(|conflicts| !conflicts.is_empty())
-                .collect::<Vec<Vec<PactConflict>>>();
-            let num_conflicts = conflicts.len();
-            if num_conflicts > 0 {
+        self.merge_with_strategy(pact, MergeStrategy::Fail).map_err(|err| match err {
+            PactMergeError::Conflicts(ref conflicts) => {
                 warn!("The following conflicting interactions where found:");
-                for interaction_conflicts in conflicts {
-                    warn!(" Interaction '{}':", interaction_conflicts.first().unwrap().interaction);
-                    for conflict in interaction_conflicts {
+                let interactions = conflicts.iter().map(|conflict| conflict.interaction.clone()).unique().collect_vec();
+                for interaction in &interactions {
+                    warn!(" Interaction '{}':", interaction);
+                    for conflict in conflicts.iter().filter(|conflict| &conflict.interaction == interaction) {
                         warn!("   {}", conflict.description);
                     }
                 }
-                Err(format!("Unable to merge pacts, as there were {} conflict(s) between the interactions",
-                    num_conflicts))
-            } else {
-                Ok(Pact {
-                    provider: self.provider.clone(),
-                    consumer: self.consumer.clone(),
-                    interactions: self.interactions.iter()
-                        .chain(pact.interactions.iter())
-                        .cloned()
-                        .sorted_by(|a, b| {
-                            let cmp = Ord::cmp(&a.provider_states.iter().map(|p| p.name.clone()).collect::<Vec<String>>(),
-                                &b.provider_states.iter().map(|p| p.name.clone()).collect::<Vec<String>>());
-                            if cmp == Ordering::Equal {
-                                Ord::cmp(&a.description, &b.description)
-                            } else {
-                                cmp
-                            }
-                        }).into_iter()
-                        .unique()
-                        .collect(),
-                    metadata: self.metadata.clone(),
-                    specification_version: self.specification_version.clone()
-                })
+                format!("Unable to merge pacts, as there were {} conflict(s) between the interactions",
+                    interactions.len())
+            },
+            PactMergeError::DifferentParticipants => err.to_string()
+        })
+    }
+
+    /// Merges the other pact into this pact using the given strategy, and returns a new Pact with
+    /// the interactions sorted. Interactions are the same if they have the same description and
+    /// provider states. With the `Fail` strategy, all the conflicts between them are returned in
+    /// the error. With the `LastWriterWins` strategy, the interaction being merged in always
+    /// replaces the existing one, and with `KeepBoth` it is renamed if it differs from the existing
+    /// one in any way (including the body or matching rules).
+    pub fn merge_with_strategy(&self, pact: &Pact, strategy: MergeStrategy) -> Result<Pact, PactMergeError> {
+        if self.consumer.name != pact.consumer.name || self.provider.name != pact.provider.name {
+            return Err(PactMergeError::DifferentParticipants)
+        }
+
+        let same_interaction = |a: &Interaction, b: &Interaction| a.description == b.description &&
+            a.provider_states == b.provider_states;
+        let interactions: Vec<Interaction> = match strategy {
+            MergeStrategy::Fail => {
+                let conflicts = iproduct!(self.interactions.clone(), pact.interactions.clone())
+                    .flat_map(|i| i.0.conflicts_with(&i.1))
+                    .collect::<Vec<PactConflict>>();
+                if !conflicts.is_empty() {
+                    return Err(PactMergeError::Conflicts(conflicts))
+                }
+                self.interactions.iter().chain(pact.interactions.iter()).cloned().collect()
+            },
+            MergeStrategy::LastWriterWins => self.interactions.iter()
+                .filter(|i| !pact.interactions.iter().any(|other| same_interaction(i, other)))
+                .chain(pact.interactions.iter())
+                .cloned()
+                .collect(),
+            MergeStrategy::KeepBoth => {
+                let mut interactions = self.interactions.clone();
+                for interaction in &pact.interactions {
+                    if self.interactions.iter().any(|existing| same_interaction(existing, interaction) && existing != interaction) {
+                        let mut renamed = interaction.clone();
+                        let mut count = 2;
+                        while interactions.iter().any(|i| same_interaction(i, &renamed)) {
+                            renamed.description = format!("{} ({})", interaction.description, count);
+                            count += 1;
+                        }
+                        interactions.push(renamed);
+                    } else {
+                        interactions.push(interaction.clone());
+                    }
+                }
+                interactions
             }
-        } else {
-            Err(s!("Unable to merge pacts, as they have different consumers or providers"))
+        };
+
+        Ok(self.merged_pact(interactions))
+    }
+
+    fn merged_pact(&self, interactions: Vec<Interaction>) -> Pact {
+        Pact {
+            provider: self.provider.clone(),
+            consumer: self.consumer.clone(),
+            interactions: interactions.into_iter()
+                .sorted_by(interaction_ordering).into_iter()
+                .unique()
+                .collect(),
+            metadata: self.metadata.clone(),
+            specification_version: self.specification_version.clone()
         }
     }
 
@@ -1147,29 +1385,85 @@ impl Pact {
 
     /// Reads the pact file from a URL and parses the resulting JSON into a `Pact` struct
     pub fn from_url(url: &String, auth: &Option<HttpAuth>) -> Result<Pact, String> {
-      http_utils::fetch_json_from_url(url, auth).map(|(ref url, ref json)| Pact::from_json(url, json))
+      Pact::from_url_with_options(url, auth, &FetchOptions::default())
+    }
+
+    /// Reads the pact file from a URL and parses the resulting JSON into a `Pact` struct, using the
+    /// fetch options to configure the HTTP client (timeouts, retries, headers, proxy and TLS).
+    pub fn from_url_with_options(url: &String, auth: &Option<HttpAuth>, options: &FetchOptions) -> Result<Pact, String> {
+      http_utils::fetch_json_from_url_with_options(url, auth, options).map(|(ref url, ref json)| Pact::from_json(url, json))
+    }
+
+    /// Reads the pact file and strictly parses the resulting JSON into a `Pact` struct. Any problems
+    /// found with the pact file are returned as an `InvalidData` error.
+    pub fn read_pact_strict(file: &Path) -> io::Result<Pact> {
+        let mut f = File::open(file)?;
+        let pact_json = serde_json::from_reader(&mut f);
+        match pact_json {
+            Ok(ref json) => Pact::from_json_strict(&format!("{:?}", file), json)
+                .map_err(|errors| Error::new(ErrorKind::InvalidData, format!("Pact file {:?} is not valid:\n{}",
+                    file, parse_errors::display_errors(&errors)))),
+            Err(err) => Err(Error::new(ErrorKind::Other, format!("Failed to parse Pact JSON - {}", err)))
+        }
+    }
+
+    /// Reads a pact from the reader (i.e. standard input or an entry in an archive) and parses the
+    /// resulting JSON into a `Pact` struct. The source is used in any error messages.
+    pub fn read_pact_from_reader<R: Read>(source: &str, reader: R) -> io::Result<Pact> {
+        let pact_json: serde_json::Result<Value> = serde_json::from_reader(reader);
+        match pact_json {
+            Ok(ref json) => Ok(Pact::from_json(&s!(source), json)),
+            Err(err) => Err(Error::new(ErrorKind::Other, format!("Failed to parse Pact JSON from {} - {}", source, err)))
+        }
+    }
+
+    /// Reads a pact from the reader (i.e. standard input or an entry in an archive) and strictly
+    /// parses the resulting JSON into a `Pact` struct. The source is used in any error messages.
+    pub fn read_pact_from_reader_strict<R: Read>(source: &str, reader: R) -> io::Result<Pact> {
+        let pact_json: serde_json::Result<Value> = serde_json::from_reader(reader);
+        match pact_json {
+            Ok(ref json) => Pact::from_json_strict(&s!(source), json)
+                .map_err(|errors| Error::new(ErrorKind::InvalidData, format!("Pact file {} is not valid:\n{}",
+                    source, parse_errors::display_errors(&errors)))),
+            Err(err) => Err(Error::new(ErrorKind::Other, format!("Failed to parse Pact JSON from {} - {}", source, err)))
+        }
+    }
+
+    /// Reads the pact file from a URL and strictly parses the resulting JSON into a `Pact` struct
+    pub fn from_url_strict(url: &String, auth: &Option<HttpAuth>) -> Result<Pact, String> {
+      Pact::from_url_strict_with_options(url, auth, &FetchOptions::default())
+    }
+
+    /// Reads the pact file from a URL using the fetch options, and strictly parses the resulting
+    /// JSON into a `Pact` struct
+    pub fn from_url_strict_with_options(url: &String, auth: &Option<HttpAuth>, options: &FetchOptions) -> Result<Pact, String> {
+      http_utils::fetch_json_from_url_with_options(url, auth, options).and_then(|(ref url, ref json)| Pact::from_json_strict(url, json)
+        .map_err(|errors| format!("Pact from {} is not valid:\n{}", url, parse_errors::display_errors(&errors))))
     }
 
   /// Writes this pact out to the provided file path. All directories in the path will
   /// automatically created. If an existing pact is found at the path, this pact will be
-  /// merged into the pact file.
+  /// merged into the pact file. An advisory lock is held while the pact file is merged and
+  /// written, and the file is replaced atomically, so multiple threads or processes can safely
+  /// write the same pact file.
     pub fn write_pact(&self, path: &Path, pact_spec: PactSpecification) -> io::Result<()> {
+        self.write_pact_with_strategy(path, pact_spec, MergeStrategy::Fail)
+    }
+
+    /// Writes this pact out to the provided file path, using the given strategy to resolve any
+    /// conflicts with the interactions of an existing pact file.
+    pub fn write_pact_with_strategy(&self, path: &Path, pact_spec: PactSpecification, strategy: MergeStrategy) -> io::Result<()> {
         fs::create_dir_all(path.parent().unwrap())?;
-        if path.exists() {
-            let existing_pact = Pact::read_pact(path)?;
-            match existing_pact.merge(self) {
-                Ok(ref merged_pact) => {
-                    let mut file = File::create(path)?;
-                    file.write_all(format!("{}", serde_json::to_string_pretty(&merged_pact.to_json(pact_spec)).unwrap()).as_bytes())?;
-                    Ok(())
-                },
-                Err(ref message) => Err(Error::new(ErrorKind::Other, message.clone()))
-            }
-        } else {
-            let mut file = File::create(path)?;
-            file.write_all(format!("{}", serde_json::to_string_pretty(&self.to_json(pact_spec)).unwrap()).as_bytes())?;
-            Ok(())
-        }
+        file_utils::with_write_lock(path, || {
+            let pact = if path.exists() {
+                let existing_pact = Pact::read_pact(path)?;
+                existing_pact.merge_with_strategy(self, strategy)
+                    .map_err(|err| Error::new(ErrorKind::Other, err.to_string()))?
+            } else {
+                self.clone()
+            };
+            file_utils::write_atomically(path, pact.to_canonical_string(pact_spec).as_bytes())
+        })
     }
 
     /// Returns a default Pact struct
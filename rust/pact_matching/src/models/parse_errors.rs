@@ -0,0 +1,623 @@
+//! The `parse_errors` module provides the strict validation of pact files. Where the `from_json`
+//! functions fall back to defaults when an attribute is malformed, the strict versions of those
+//! functions use these validations to return all the problems found in the pact file.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde_json::{Map, Value};
+use onig::Regex;
+use semver::Version;
+use path_exp::parse_path_exp;
+use super::PactSpecification;
+use super::generators::GeneratorCategory;
+
+/// Error found while strictly parsing a pact file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PactParseError {
+    /// JSON path to the attribute with the problem (i.e. `$.interactions[0].request.method`)
+    pub path: String,
+    /// Description of the problem
+    pub problem: String,
+    /// The offending value, if there was one
+    pub value: Option<Value>
+}
+
+impl PactParseError {
+    /// Creates a new error for the given path and problem
+    pub fn new<P: Into<String>, S: Into<String>>(path: P, problem: S, value: Option<&Value>) -> PactParseError {
+        PactParseError {
+            path: path.into(),
+            problem: problem.into(),
+            value: value.cloned()
+        }
+    }
+}
+
+impl Display for PactParseError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.value {
+            Some(ref value) => write!(f, "{}: {} (found {})", self.path, self.problem, value),
+            None => write!(f, "{}: {}", self.path, self.problem)
+        }
+    }
+}
+
+/// Formats the list of errors for display, one error per line
+pub fn display_errors(errors: &Vec<PactParseError>) -> String {
+    errors.iter().map(|error| format!("  - {}", error)).collect::<Vec<String>>().join("\n")
+}
+
+lazy_static! {
+    static ref NAME_REGEX: Regex = Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_\-]*$").unwrap();
+}
+
+/// The standard HTTP methods. Requests with other methods (i.e. WebDAV or extension methods) are
+/// still valid, but a warning is logged in case the method is a typo.
+const HTTP_METHODS: [&str; 9] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "CONNECT"];
+const MATCHING_RULE_CATEGORIES: [&str; 6] = ["path", "query", "header", "body", "status", "metadata"];
+const GENERATOR_TYPES: [&str; 12] = ["RandomInt", "Uuid", "RandomDecimal", "RandomHexadecimal", "RandomString",
+    "Regex", "Date", "Time", "DateTime", "RandomBoolean", "ProviderState", "MockServerURL"];
+
+pub(crate) fn child_path(path: &str, key: &str) -> String {
+    if NAME_REGEX.is_match(key) {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}['{}']", path, key)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match *value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object"
+    }
+}
+
+fn expect_object<'a>(path: &str, value: &'a Value, errors: &mut Vec<PactParseError>) -> Option<&'a Map<String, Value>> {
+    match *value {
+        Value::Object(ref map) => Some(map),
+        _ => {
+            errors.push(PactParseError::new(path, format!("Expected an object but was {}", type_name(value)), Some(value)));
+            None
+        }
+    }
+}
+
+fn expect_string(path: &str, value: &Value, errors: &mut Vec<PactParseError>) -> Option<String> {
+    match *value {
+        Value::String(ref s) => Some(s.clone()),
+        _ => {
+            errors.push(PactParseError::new(path, format!("Expected a string but was {}", type_name(value)), Some(value)));
+            None
+        }
+    }
+}
+
+fn validate_name(path: &str, map: &Map<String, Value>, attribute: &str, errors: &mut Vec<PactParseError>) {
+    let path = child_path(path, attribute);
+    match map.get(attribute) {
+        Some(value) => match expect_string(&path, value, errors) {
+            Some(ref name) if name.trim().is_empty() => errors.push(PactParseError::new(path.clone(),
+                format!("The {} can not be empty", attribute), Some(value))),
+            _ => ()
+        },
+        None => errors.push(PactParseError::new(path, format!("Missing the {}", attribute), None))
+    }
+}
+
+/// Validates the JSON of a pact file, returning any problems found
+pub(crate) fn validate_pact(json: &Value) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    let path = "$";
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        for participant in &["consumer", "provider"] {
+            let participant_path = child_path(path, participant);
+            match map.get(*participant) {
+                Some(value) => if let Some(participant_map) = expect_object(&participant_path, value, &mut errors) {
+                    validate_name(&participant_path, participant_map, "name", &mut errors);
+                },
+                None => errors.push(PactParseError::new(participant_path, format!("Missing the {}", participant), None))
+            }
+        }
+
+        let spec_version = match map.get("metadata") {
+            Some(metadata) => validate_metadata(&child_path(path, "metadata"), metadata, &mut errors),
+            None => PactSpecification::V3
+        };
+
+        let interactions_path = child_path(path, "interactions");
+        match map.get("interactions") {
+            Some(&Value::Array(ref interactions)) => for (index, interaction) in interactions.iter().enumerate() {
+                errors.extend(validate_interaction(&format!("{}[{}]", interactions_path, index), interaction, &spec_version));
+            },
+            Some(value) => errors.push(PactParseError::new(interactions_path,
+                format!("Expected an array but was {}", type_name(value)), Some(value))),
+            None => errors.push(PactParseError::new(interactions_path, "Missing the interactions", None))
+        }
+    }
+    errors
+}
+
+/// Validates the metadata of the pact. Only the shape of the specification version is checked, and
+/// any other entries (like the `pactSpecificationVersion` string of older pact files) are ignored,
+/// as they are not needed to load the pact.
+fn validate_metadata(path: &str, metadata: &Value, errors: &mut Vec<PactParseError>) -> PactSpecification {
+    let mut spec_version = PactSpecification::V3;
+    if let Some(map) = expect_object(path, metadata, errors) {
+        let specification = map.get("pact-specification").map(|value| ("pact-specification", value))
+            .or_else(|| map.get("pactSpecification").map(|value| ("pactSpecification", value)));
+        if let Some((key, value)) = specification {
+            let key_path = child_path(path, key);
+            if let Some(entries) = expect_object(&key_path, value, errors) {
+                if let Some(version) = entries.get("version") {
+                    let version_path = child_path(&key_path, "version");
+                    if let Some(version_str) = expect_string(&version_path, version, errors) {
+                        match Version::parse(&version_str) {
+                            Ok(ver) => match (ver.major, ver.minor) {
+                                (1, 0) => spec_version = PactSpecification::V1,
+                                (1, 1) => spec_version = PactSpecification::V1_1,
+                                (2, _) => spec_version = PactSpecification::V2,
+                                (3, _) => spec_version = PactSpecification::V3,
+                                _ => errors.push(PactParseError::new(version_path,
+                                    "Unsupported specification version", Some(version)))
+                            },
+                            Err(err) => errors.push(PactParseError::new(version_path,
+                                format!("Specification version is not a valid version - {}", err), Some(version)))
+                        }
+                    }
+                }
+            }
+        }
+    }
+    spec_version
+}
+
+/// Validates the JSON of an interaction, returning any problems found
+pub(crate) fn validate_interaction(path: &str, json: &Value, spec_version: &PactSpecification) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        validate_name(path, map, "description", &mut errors);
+
+        if let Some(state) = map.get("providerState") {
+            match *state {
+                Value::String(_) | Value::Null => (),
+                _ => errors.push(PactParseError::new(child_path(path, "providerState"),
+                    format!("Expected a string but was {}", type_name(state)), Some(state)))
+            }
+        }
+        if let Some(states) = map.get("providerStates") {
+            let states_path = child_path(path, "providerStates");
+            match *states {
+                Value::Array(ref states) => for (index, state) in states.iter().enumerate() {
+                    let state_path = format!("{}[{}]", states_path, index);
+                    if let Some(state_map) = expect_object(&state_path, state, &mut errors) {
+                        validate_name(&state_path, state_map, "name", &mut errors);
+                        if let Some(params) = state_map.get("params") {
+                            expect_object(&child_path(&state_path, "params"), params, &mut errors);
+                        }
+                    }
+                },
+                _ => errors.push(PactParseError::new(states_path,
+                    format!("Expected an array but was {}", type_name(states)), Some(states)))
+            }
+        }
+
+        match map.get("request") {
+            Some(request) => errors.extend(validate_request(&child_path(path, "request"), request, spec_version)),
+            None => errors.push(PactParseError::new(child_path(path, "request"), "Missing the request", None))
+        }
+        match map.get("response") {
+            Some(response) => errors.extend(validate_response(&child_path(path, "response"), response)),
+            None => errors.push(PactParseError::new(child_path(path, "response"), "Missing the response", None))
+        }
+    }
+    errors
+}
+
+/// Validates the JSON of a request, returning any problems found
+pub(crate) fn validate_request(path: &str, json: &Value, spec_version: &PactSpecification) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        if let Some(method) = map.get("method") {
+            let method_path = child_path(path, "method");
+            if let Some(method_str) = expect_string(&method_path, method, &mut errors) {
+                if !HTTP_METHODS.contains(&method_str.to_uppercase().as_str()) {
+                    warn!("{}: '{}' is not a standard HTTP method", method_path, method_str);
+                }
+            }
+        }
+        if let Some(request_path) = map.get("path") {
+            let path_path = child_path(path, "path");
+            if let Some(path_str) = expect_string(&path_path, request_path, &mut errors) {
+                if !path_str.starts_with("/") {
+                    errors.push(PactParseError::new(path_path, "The request path must start with a '/'", Some(request_path)));
+                }
+            }
+        }
+        if let Some(query) = map.get("query") {
+            validate_query(&child_path(path, "query"), query, spec_version, &mut errors);
+        }
+        validate_http_part(path, map, "requestMatchingRules", &mut errors);
+    }
+    errors
+}
+
+/// Validates the JSON of a response, returning any problems found
+pub(crate) fn validate_response(path: &str, json: &Value) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        if let Some(status) = map.get("status") {
+            match status.as_u64() {
+                Some(code) if code >= 100 && code < 600 => (),
+                _ => errors.push(PactParseError::new(child_path(path, "status"),
+                    "Expected a HTTP status code between 100 and 599", Some(status)))
+            }
+        }
+        validate_http_part(path, map, "responseMatchingRules", &mut errors);
+    }
+    errors
+}
+
+fn validate_query(path: &str, query: &Value, spec_version: &PactSpecification, errors: &mut Vec<PactParseError>) {
+    match *query {
+        Value::String(_) => (),
+        Value::Object(ref map) if *spec_version == PactSpecification::V3 => for (key, value) in map {
+            validate_string_values(&child_path(path, key), value, errors);
+        },
+        _ => errors.push(PactParseError::new(path, format!("Expected a query string but was {}", type_name(query)), Some(query)))
+    }
+}
+
+fn validate_string_values(path: &str, value: &Value, errors: &mut Vec<PactParseError>) {
+    match *value {
+        Value::String(_) => (),
+        Value::Array(ref values) => for (index, item) in values.iter().enumerate() {
+            expect_string(&format!("{}[{}]", path, index), item, errors);
+        },
+        _ => errors.push(PactParseError::new(path,
+            format!("Expected a string or an array of strings but was {}", type_name(value)), Some(value)))
+    }
+}
+
+fn validate_http_part(path: &str, map: &Map<String, Value>, deprecated_rules_name: &str, errors: &mut Vec<PactParseError>) {
+    if let Some(headers) = map.get("headers") {
+        let headers_path = child_path(path, "headers");
+        if let Some(headers_map) = expect_object(&headers_path, headers, errors) {
+            for (key, value) in headers_map {
+                validate_string_values(&child_path(&headers_path, key), value, errors);
+            }
+        }
+    }
+    if let Some(rules) = map.get("matchingRules") {
+        errors.extend(validate_matching_rules(&child_path(path, "matchingRules"), rules));
+    } else if let Some(rules) = map.get(deprecated_rules_name) {
+        errors.extend(validate_matching_rules(&child_path(path, deprecated_rules_name), rules));
+    }
+    if let Some(generators) = map.get("generators") {
+        validate_generators(&child_path(path, "generators"), generators, errors);
+    }
+}
+
+/// Validates the JSON of the matching rules, in either the V2 or V3 format
+pub(crate) fn validate_matching_rules(path: &str, json: &Value) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        if map.keys().next().map(|key| key.starts_with("$")).unwrap_or(false) {
+            for (key, rule) in map {
+                let rule_path = child_path(path, key);
+                if let Err(err) = parse_path_exp(key.clone()) {
+                    errors.push(PactParseError::new(rule_path.clone(), format!("Invalid matching rule path - {}", err), None));
+                }
+                errors.extend(validate_matching_rule(&rule_path, rule));
+            }
+        } else {
+            for (category, rules) in map {
+                let category_path = child_path(path, category);
+                if !MATCHING_RULE_CATEGORIES.contains(&category.as_str()) {
+                    errors.push(PactParseError::new(category_path, "Unknown matching rule category", None));
+                } else if category == "path" && rules.get("matchers").is_some() {
+                    validate_rule_list(&category_path, rules, &mut errors);
+                } else if let Some(rules_map) = expect_object(&category_path, rules, &mut errors) {
+                    for (key, rule_list) in rules_map {
+                        let rule_path = child_path(&category_path, key);
+                        if category == "body" {
+                            if let Err(err) = parse_path_exp(key.clone()) {
+                                errors.push(PactParseError::new(rule_path.clone(), format!("Invalid matching rule path - {}", err), None));
+                            }
+                        }
+                        validate_rule_list(&rule_path, rule_list, &mut errors);
+                    }
+                }
+            }
+        }
+    }
+    errors
+}
+
+fn validate_rule_list(path: &str, json: &Value, errors: &mut Vec<PactParseError>) {
+    if let Some(map) = expect_object(path, json, errors) {
+        if let Some(combine) = map.get("combine") {
+            let combine_path = child_path(path, "combine");
+            if let Some(combine_str) = expect_string(&combine_path, combine, errors) {
+                let combine_str = combine_str.to_uppercase();
+                if combine_str != "AND" && combine_str != "OR" {
+                    errors.push(PactParseError::new(combine_path, "Expected either 'AND' or 'OR'", Some(combine)));
+                }
+            }
+        }
+        let matchers_path = child_path(path, "matchers");
+        match map.get("matchers") {
+            Some(&Value::Array(ref matchers)) => for (index, matcher) in matchers.iter().enumerate() {
+                errors.extend(validate_matching_rule(&format!("{}[{}]", matchers_path, index), matcher));
+            },
+            Some(value) => errors.push(PactParseError::new(matchers_path,
+                format!("Expected an array but was {}", type_name(value)), Some(value))),
+            None => errors.push(PactParseError::new(matchers_path, "Missing the matchers", None))
+        }
+    }
+}
+
+fn validate_rule_attribute(path: &str, map: &Map<String, Value>, attribute: &str, errors: &mut Vec<PactParseError>) -> Option<String> {
+    match map.get(attribute) {
+        Some(value) => expect_string(&child_path(path, attribute), value, errors),
+        None => {
+            errors.push(PactParseError::new(child_path(path, attribute), format!("Missing the {} attribute", attribute), None));
+            None
+        }
+    }
+}
+
+fn validate_size(path: &str, map: &Map<String, Value>, attribute: &str, errors: &mut Vec<PactParseError>) {
+    if let Some(value) = map.get(attribute) {
+        if value.as_u64().is_none() {
+            errors.push(PactParseError::new(child_path(path, attribute),
+                format!("Expected {} to be a positive integer", attribute), Some(value)));
+        }
+    }
+}
+
+/// Validates the JSON of a single matching rule, returning any problems found
+pub(crate) fn validate_matching_rule(path: &str, json: &Value) -> Vec<PactParseError> {
+    let mut errors = vec![];
+    if let Some(map) = expect_object(path, json, &mut errors) {
+        let match_type = match map.get("match") {
+            Some(value) => match expect_string(&child_path(path, "match"), value, &mut errors) {
+                Some(match_type) => match_type,
+                None => return errors
+            },
+            None => if map.contains_key("regex") {
+                s!("regex")
+            } else if map.contains_key("min") || map.contains_key("max") {
+                s!("type")
+            } else if let Some(key) = ["timestamp", "time", "date"].iter().find(|key| map.contains_key(**key)) {
+                s!(*key)
+            } else {
+                errors.push(PactParseError::new(path, "Unrecognised matching rule", Some(json)));
+                return errors
+            }
+        };
+        match match_type.as_str() {
+            "regex" => if let Some(regex) = validate_rule_attribute(path, map, "regex", &mut errors) {
+                if let Err(err) = Regex::new(&regex) {
+                    errors.push(PactParseError::new(child_path(path, "regex"),
+                        format!("Not a valid regular expression - {}", err), map.get("regex")));
+                }
+            },
+            "include" => { validate_rule_attribute(path, map, "value", &mut errors); },
+            "timestamp" | "date" | "time" => { validate_rule_attribute(path, map, &match_type, &mut errors); },
+            "type" => {
+                validate_size(path, map, "min", &mut errors);
+                validate_size(path, map, "max", &mut errors);
+            },
+            "min" | "max" => match map.get(&match_type) {
+                Some(_) => validate_size(path, map, &match_type, &mut errors),
+                None => errors.push(PactParseError::new(child_path(path, &match_type),
+                    format!("Missing the {} attribute", match_type), None))
+            },
+            "equality" | "number" | "integer" | "decimal" | "real" | "null" => (),
+            _ => errors.push(PactParseError::new(child_path(path, "match"), "Unknown matching rule type", map.get("match")))
+        }
+    }
+    errors
+}
+
+fn validate_generator(path: &str, json: &Value, errors: &mut Vec<PactParseError>) {
+    if let Some(map) = expect_object(path, json, errors) {
+        match map.get("type") {
+            Some(gen_type) => match expect_string(&child_path(path, "type"), gen_type, errors) {
+                Some(ref gen_type_str) if !GENERATOR_TYPES.contains(&gen_type_str.as_str()) =>
+                    errors.push(PactParseError::new(child_path(path, "type"), "Unknown generator type", Some(gen_type))),
+                Some(ref gen_type_str) if gen_type_str == "Regex" => { validate_rule_attribute(path, map, "regex", errors); },
+                Some(ref gen_type_str) if gen_type_str == "ProviderState" => { validate_rule_attribute(path, map, "expression", errors); },
+                Some(ref gen_type_str) if gen_type_str == "MockServerURL" => {
+                    validate_rule_attribute(path, map, "example", errors);
+                    validate_rule_attribute(path, map, "regex", errors);
+                },
+                _ => ()
+            },
+            None => errors.push(PactParseError::new(child_path(path, "type"), "Missing the generator type", None))
+        }
+    }
+}
+
+fn validate_generators(path: &str, json: &Value, errors: &mut Vec<PactParseError>) {
+    if let Some(map) = expect_object(path, json, errors) {
+        for (category, generators) in map {
+            let category_path = child_path(path, category);
+            match GeneratorCategory::from_str(category) {
+                Ok(GeneratorCategory::PATH) | Ok(GeneratorCategory::METHOD) | Ok(GeneratorCategory::STATUS) =>
+                    validate_generator(&category_path, generators, errors),
+                Ok(_) => if let Some(generators_map) = expect_object(&category_path, generators, errors) {
+                    for (key, generator) in generators_map {
+                        validate_generator(&child_path(&category_path, key), generator, errors);
+                    }
+                },
+                Err(_) => errors.push(PactParseError::new(category_path, "Unknown generator category", None))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use serde_json;
+    use itertools::Itertools;
+
+    #[test]
+    fn validate_pact_with_a_valid_pact() {
+        let json = json!({
+          "consumer": { "name": "consumer" },
+          "provider": { "name": "provider" },
+          "interactions": [{
+            "description": "get a user",
+            "providerStates": [{ "name": "user exists", "params": { "id": 100 } }],
+            "request": {
+              "method": "get",
+              "path": "/users/100",
+              "query": { "full": ["true"] },
+              "headers": { "Accept": "application/json" },
+              "matchingRules": {
+                "query": { "full": { "matchers": [{ "match": "regex", "regex": "true|false" }] } }
+              }
+            },
+            "response": {
+              "status": 200,
+              "body": { "id": 100 },
+              "matchingRules": {
+                "body": { "$.id": { "matchers": [{ "match": "integer" }], "combine": "AND" } }
+              },
+              "generators": { "body": { "$.id": { "type": "RandomInt", "min": 1, "max": 10 } } }
+            }
+          }],
+          "metadata": { "pactSpecification": { "version": "3.0.0" } }
+        });
+        expect!(validate_pact(&json).iter()).to(be_empty());
+    }
+
+    #[test]
+    fn validate_pact_returns_an_error_for_missing_attributes() {
+        let json = json!({ "consumer": { "name": "" }, "interaction": [] });
+        expect!(validate_pact(&json)).to(be_equal_to(vec![
+            PactParseError::new("$.consumer.name", "The name can not be empty", Some(&json!(""))),
+            PactParseError::new("$.provider", "Missing the provider", None),
+            PactParseError::new("$.interactions", "Missing the interactions", None)
+        ]));
+    }
+
+    #[test]
+    fn validate_pact_returns_errors_with_the_path_to_the_problem() {
+        let json = json!({
+          "consumer": { "name": "consumer" },
+          "provider": { "name": "provider" },
+          "interactions": [{
+            "description": "get a user",
+            "request": { "method": "GTE", "path": "/users", "headers": { "Accept": 100 } },
+            "response": { "status": "200" }
+          }],
+          "metadata": { "pactSpecification": { "version": "three" } }
+        });
+        let errors = validate_pact(&json);
+        expect!(errors.iter().map(|error| error.path.clone()).collect::<Vec<String>>()).to(be_equal_to(vec![
+          s!("$.metadata.pactSpecification.version"),
+          s!("$.interactions[0].request.headers.Accept"),
+          s!("$.interactions[0].response.status")
+        ]));
+        expect!(errors[2].value.clone()).to(be_some().value(json!("200")));
+    }
+
+    #[test]
+    fn validate_pact_accepts_extension_http_methods() {
+        let interaction = |method: &str| json!({
+          "description": "a request",
+          "request": { "method": method, "path": "/calendars" },
+          "response": { "status": 207 }
+        });
+        let json = json!({
+          "consumer": { "name": "consumer" },
+          "provider": { "name": "provider" },
+          "interactions": [interaction("PROPFIND"), interaction("REPORT"), interaction("x-custom")]
+        });
+        expect!(validate_pact(&json).iter()).to(be_empty());
+    }
+
+    #[test]
+    fn validate_pact_ignores_unknown_and_scalar_metadata() {
+        let json = json!({
+          "consumer": { "name": "consumer" },
+          "provider": { "name": "provider" },
+          "interactions": [],
+          "metadata": { "pactSpecificationVersion": "2.0.0", "pact-jvm": { "version": "2.4.18" }, "generatedBy": 1 }
+        });
+        expect!(validate_pact(&json).iter()).to(be_empty());
+    }
+
+    #[test]
+    fn validate_pact_checks_the_shape_of_the_specification_metadata() {
+        let json = json!({
+          "consumer": { "name": "consumer" },
+          "provider": { "name": "provider" },
+          "interactions": [],
+          "metadata": { "pactSpecification": "3.0.0" }
+        });
+        expect!(validate_pact(&json)).to(be_equal_to(vec![
+          PactParseError::new("$.metadata.pactSpecification", "Expected an object but was a string", Some(&json!("3.0.0")))
+        ]));
+    }
+
+    #[test]
+    fn validate_matching_rules_test() {
+        let json: Value = serde_json::from_str(r#"{
+          "body": {
+            "$.id": { "matchers": [{ "match": "integr" }] },
+            "$.name": { "matchers": [{ "match": "regex", "regex": "[a-z" }] },
+            "$.items": { "matchers": [{ "match": "type", "min": -1 }], "combine": "XOR" },
+            "$[": { "matchers": [] }
+          },
+          "headers": {}
+        }"#).unwrap();
+        let errors = validate_matching_rules("$.matchingRules", &json);
+        let paths = errors.iter().map(|error| error.path.clone()).sorted().collect::<Vec<String>>();
+        expect!(paths).to(be_equal_to(vec![
+          s!("$.matchingRules.body['$.id'].matchers[0].match"),
+          s!("$.matchingRules.body['$.items'].combine"),
+          s!("$.matchingRules.body['$.items'].matchers[0].min"),
+          s!("$.matchingRules.body['$.name'].matchers[0].regex"),
+          s!("$.matchingRules.body['$[']"),
+          s!("$.matchingRules.headers")
+        ]));
+    }
+
+    #[test]
+    fn validate_matching_rules_with_v2_rules() {
+        let json = json!({
+          "$.body.id": { "match": "type" },
+          "$.headers.Accept": { "regex": "application/.*" },
+          "$.body.name": { "matches": "type" }
+        });
+        let errors = validate_matching_rules("$.requestMatchingRules", &json);
+        expect!(errors).to(be_equal_to(vec![
+          PactParseError::new("$.requestMatchingRules['$.body.name']", "Unrecognised matching rule", Some(&json!({ "matches": "type" })))
+        ]));
+    }
+
+    #[test]
+    fn validate_generators_test() {
+        let mut errors = vec![];
+        validate_generators("$.generators", &json!({
+          "body": { "$.id": { "type": "RandomInteger" }, "$.name": { "type": "ProviderState" } },
+          "status": { "type": "RandomInt", "min": 200, "max": 299 },
+          "cookies": {}
+        }), &mut errors);
+        expect!(errors).to(be_equal_to(vec![
+          PactParseError::new("$.generators.body['$.id'].type", "Unknown generator type", Some(&json!("RandomInteger"))),
+          PactParseError::new("$.generators.body['$.name'].expression", "Missing the expression attribute", None),
+          PactParseError::new("$.generators.cookies", "Unknown generator category", None)
+        ]));
+    }
+}
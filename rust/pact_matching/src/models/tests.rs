@@ -6,10 +6,12 @@ use std::collections::hash_map::DefaultHasher;
 use std::fs::{self, File};
 use std::io;
 use std::env;
+use std::thread;
 use expectest::prelude::*;
 use rand;
 use std::hash::{Hash, Hasher};
 use super::provider_states::*;
+use super::parse_errors::PactParseError;
 use super::matchingrules::*;
 use super::generators::{Generators, Generator, generators_from_json};
 use std::str::FromStr;
@@ -53,6 +55,85 @@ fn response_from_json_defaults_to_status_200() {
     assert_eq!(response.status, 200);
 }
 
+#[test]
+fn request_from_json_strict_returns_the_problems_with_the_request() {
+    let request_json = json!({ "method": 100, "path": "users", "headers": { "Accept": ["application/json", 1] } });
+    let result = Request::from_json_strict(&request_json, &PactSpecification::V3);
+    expect!(result).to(be_err().value(vec![
+        PactParseError::new("$.method", "Expected a string but was a number", Some(&json!(100))),
+        PactParseError::new("$.path", "The request path must start with a '/'", Some(&json!("users"))),
+        PactParseError::new("$.headers.Accept[1]", "Expected a string but was a number", Some(&json!(1)))
+    ]));
+}
+
+#[test]
+fn response_from_json_strict_returns_an_error_for_an_invalid_status() {
+    let response_json = json!({ "status": 1000 });
+    let result = Response::from_json_strict(&response_json, &PactSpecification::V3);
+    expect!(result).to(be_err().value(vec![
+        PactParseError::new("$.status", "Expected a HTTP status code between 100 and 599", Some(&json!(1000)))
+    ]));
+    expect!(Response::from_json_strict(&json!({ "status": 204 }), &PactSpecification::V3).map(|r| r.status)).to(be_ok().value(204));
+}
+
+#[test]
+fn interaction_from_json_strict_requires_a_description_and_request_and_response() {
+    let interaction_json = json!({ "providerState": "provider state" });
+    let result = Interaction::from_json_strict(0, &interaction_json, &PactSpecification::V1_1);
+    expect!(result).to(be_err().value(vec![
+        PactParseError::new("$.description", "Missing the description", None),
+        PactParseError::new("$.request", "Missing the request", None),
+        PactParseError::new("$.response", "Missing the response", None)
+    ]));
+}
+
+#[test]
+fn pact_from_json_strict_returns_errors_with_the_path_in_the_pact() {
+    let pact_json = json!({
+        "consumer": { "name": "consumer" },
+        "provider": { "name": "provider" },
+        "interactions": [
+          { "description": "one", "request": { "path": "/" }, "response": {} },
+          { "description": "two", "request": { "path": "/" }, "response": {
+            "matchingRules": { "body": { "$.id": { "matchers": [{ "match": "regex" }] } } }
+          } }
+        ]
+    });
+    let result = Pact::from_json_strict(&s!("pact.json"), &pact_json);
+    expect!(result).to(be_err().value(vec![
+        PactParseError::new("$.interactions[1].response.matchingRules.body['$.id'].matchers[0].regex",
+            "Missing the regex attribute", None)
+    ]));
+}
+
+#[test]
+fn pact_from_json_strict_loads_pacts_with_legacy_metadata() {
+    let pact_json = json!({
+        "consumer": { "name": "consumer" },
+        "provider": { "name": "provider" },
+        "interactions": [
+          { "description": "one", "request": { "method": "GET", "path": "/" }, "response": { "status": 200 } }
+        ],
+        "metadata": { "pactSpecificationVersion": "2.0.0" }
+    });
+    let result = Pact::from_json_strict(&s!("pact.json"), &pact_json);
+    expect!(result.map(|pact| pact.interactions.len())).to(be_ok().value(1));
+}
+
+#[test]
+fn read_pact_strict_returns_invalid_data_for_an_invalid_pact() {
+    let dir = env::temp_dir();
+    let mut path = dir.clone();
+    path.push(format!("invalid-pact-{}.json", rand::random::<u16>()));
+    fs::write(&path, r#"{ "consumer": { "name": "consumer" }, "interactions": {} }"#).unwrap();
+    let result = Pact::read_pact_strict(&path);
+    fs::remove_file(&path).unwrap_or(());
+    let error = result.unwrap_err();
+    expect!(error.kind()).to(be_equal_to(io::ErrorKind::InvalidData));
+    expect!(error.to_string().contains("  - $.provider: Missing the provider")).to(be_true());
+    expect!(error.to_string().contains("  - $.interactions: Expected an array but was an object (found {})")).to(be_true());
+}
+
 #[test]
 fn parse_query_string_test() {
     let query = "a=b&c=d".to_string();
@@ -157,32 +238,42 @@ fn request_mimetype_is_based_on_the_content_type_header() {
         headers: Some(hashmap!{ s!("CONTENT-TYPE") => vec![s!("application/json ; charset=UTF-8")] }), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("{\"json\": true}".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("{\"json\": true}".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("{}".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("{}".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("[]".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("[]".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("[1,2,3]".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("[1,2,3]".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("\"string\"".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("\"string\"".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/json"));
     expect!(Request {
-        body: OptionalBody::Present("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<json>false</json>".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<json>false</json>".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/xml"));
     expect!(Request {
-        body: OptionalBody::Present("<json>false</json>".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("<json>false</json>".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("application/xml"));
     expect!(Request {
-        body: OptionalBody::Present("this is not json".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("this is not json".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("text/plain"));
     expect!(Request {
-        body: OptionalBody::Present("<html><body>this is also not json</body></html>".into()), .. request.clone() }.content_type())
+        body: OptionalBody::Present("<html><body>this is also not json</body></html>".into(), None), .. request.clone() }.content_type())
         .to(be_equal_to("text/html"));
+    expect!(Request {
+        body: OptionalBody::Present(vec![0xFF, 0x00, 0xFE], None), .. request.clone() }.content_type())
+        .to(be_equal_to("application/octet-stream"));
+    expect!(Request {
+        body: OptionalBody::Present(vec![0xFF, 0x00, 0xFE], Some(s!("image/gif"))), .. request.clone() }.content_type())
+        .to(be_equal_to("image/gif"));
+    expect!(Request {
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("image/png")] }),
+        body: OptionalBody::Present(vec![0xFF, 0x00, 0xFE], Some(s!("image/gif"))), .. request.clone() }.content_type())
+        .to(be_equal_to("image/png"));
 }
 
 #[test]
@@ -208,6 +299,12 @@ fn content_type_enum_test() {
     expect!(Request {
         headers: Some(hashmap!{ s!("CONTENT-TYPE") => vec![s!("application/stuff+xml")] }), .. request.clone() }.content_type_enum())
         .to(be_equal_to(DetectedContentType::Xml));
+    expect!(Request {
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/yaml")] }), .. request.clone() }.content_type_enum())
+        .to(be_equal_to(DetectedContentType::Yaml));
+    expect!(Request {
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/x-yaml")] }), .. request.clone() }.content_type_enum())
+        .to(be_equal_to(DetectedContentType::Yaml));
 }
 
 #[test]
@@ -387,7 +484,7 @@ fn load_basic_pact() {
     expect!(interaction.response).to(be_equal_to(Response {
         status: 200,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/html")] }),
-        body: OptionalBody::Present("\"That is some good Mallory.\"".into()),
+        body: OptionalBody::Present("\"That is some good Mallory.\"".into(), None),
       .. Response::default()
     }));
     expect!(pact.specification_version).to(be_equal_to(PactSpecification::V3));
@@ -528,13 +625,13 @@ fn load_v3_pact() {
         path: s!("/"),
         query: Some(hashmap!{ s!("q") => vec![s!("p"), s!("p2")], s!("r") => vec![s!("s")] }),
         headers: Some(hashmap!{ s!("testreqheader") => vec![s!("testreqheadervalue")] }),
-        body: OptionalBody::Present("{\"test\":true}".into()),
+        body: OptionalBody::Present("{\"test\":true}".into(), None),
       .. Request::default()
     }));
     expect!(interaction.response).to(be_equal_to(Response {
         status: 200,
         headers: Some(hashmap!{ s!("testreqheader") => vec![s!("testreqheaderval")] }),
-        body: OptionalBody::Present("{\"responsetest\":true}".into()),
+        body: OptionalBody::Present("{\"responsetest\":true}".into(), None),
         .. Response::default()
     }));
 }
@@ -592,7 +689,7 @@ fn load_pact_encoded_query_string() {
         query: Some(hashmap!{ s!("datetime") => vec![s!("2011-12-03T10:15:30+01:00")],
             s!("description") => vec![s!("hello world!")] }),
         headers: Some(hashmap!{ s!("testreqheader") => vec![s!("testreqheadervalue")] }),
-        body: OptionalBody::Present("{\"test\":true}".into()),
+        body: OptionalBody::Present("{\"test\":true}".into(), None),
       .. Request::default()
     }));
 }
@@ -716,7 +813,7 @@ fn request_to_json_with_headers() {
 fn request_to_json_with_json_body() {
     let request = Request { headers: Some(hashmap!{
         s!("Content-Type") => vec![s!("application/json")]
-    }), body: OptionalBody::Present(r#"{"key": "value"}"#.into()), .. Request::default() };
+    }), body: OptionalBody::Present(r#"{"key": "value"}"#.into(), None), .. Request::default() };
     expect!(request.to_json(&PactSpecification::V3).to_string()).to(
         be_equal_to(r#"{"body":{"key":"value"},"headers":{"Content-Type":"application/json"},"method":"GET","path":"/"}"#)
     );
@@ -726,7 +823,7 @@ fn request_to_json_with_json_body() {
 #[test]
 fn request_to_json_with_non_json_body() {
     let request = Request { headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/plain")] }),
-        body: OptionalBody::Present("This is some text".into()), .. Request::default() };
+        body: OptionalBody::Present("This is some text".into(), None), .. Request::default() };
     expect!(request.to_json(&PactSpecification::V3).to_string()).to(
         be_equal_to(r#"{"body":"This is some text","headers":{"Content-Type":"text/plain"},"method":"GET","path":"/"}"#)
     );
@@ -769,7 +866,7 @@ fn response_to_json_with_headers() {
 fn response_to_json_with_json_body() {
     let response = Response { headers: Some(hashmap!{
         s!("Content-Type") => vec![s!("application/json")]
-    }), body: OptionalBody::Present(r#"{"key": "value"}"#.into()), .. Response::default() };
+    }), body: OptionalBody::Present(r#"{"key": "value"}"#.into(), None), .. Response::default() };
     expect!(response.to_json(&PactSpecification::V3).to_string()).to(
         be_equal_to(r#"{"body":{"key":"value"},"headers":{"Content-Type":"application/json"},"status":200}"#)
     );
@@ -778,12 +875,54 @@ fn response_to_json_with_json_body() {
 #[test]
 fn response_to_json_with_non_json_body() {
     let response = Response { headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/plain")] }),
-        body: OptionalBody::Present("This is some text".into()), .. Response::default() };
+        body: OptionalBody::Present("This is some text".into(), None), .. Response::default() };
     expect!(response.to_json(&PactSpecification::V3).to_string()).to(
         be_equal_to(r#"{"body":"This is some text","headers":{"Content-Type":"text/plain"},"status":200}"#)
     );
 }
 
+#[test]
+fn response_to_json_with_binary_body() {
+    let response = Response { headers: Some(hashmap!{ s!("Content-Type") => vec![s!("image/png")] }),
+        body: OptionalBody::Present(vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A], None), .. Response::default() };
+    expect!(response.to_json(&PactSpecification::V3).to_string()).to(
+        be_equal_to(r#"{"body":"iVBORw0KGgo=","bodyEncoding":"base64","headers":{"Content-Type":"image/png"},"status":200}"#)
+    );
+}
+
+#[test]
+fn response_to_json_with_binary_body_encodes_the_body_even_if_it_is_valid_utf8() {
+    let response = Response { headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/octet-stream")] }),
+        body: OptionalBody::Present("c29tZSBieXRlcw==".into(), None), .. Response::default() };
+    expect!(response.to_json(&PactSpecification::V3).to_string()).to(
+        be_equal_to(r#"{"body":"YzI5dFpTQmllWFJsY3c9PQ==","bodyEncoding":"base64","headers":{"Content-Type":"application/octet-stream"},"status":200}"#)
+    );
+}
+
+#[test]
+fn response_to_json_with_binary_body_and_no_content_type_header() {
+    let response = Response { body: OptionalBody::Present(vec![0xFF, 0x00, 0xFE], Some(s!("image/gif"))), .. Response::default() };
+    expect!(response.to_json(&PactSpecification::V3).to_string()).to(
+        be_equal_to(r#"{"body":"/wD+","bodyEncoding":"base64","status":200}"#)
+    );
+}
+
+#[test]
+fn response_with_binary_body_survives_writing_and_reading() {
+    let response = Response { headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/protobuf")] }),
+        body: OptionalBody::Present(vec![0x08, 0x96, 0x01, 0x12, 0x00], None), .. Response::default() };
+    let json = response.to_json(&PactSpecification::V3);
+    let read_response = Response::from_json(&json, &PactSpecification::V3);
+    expect!(read_response.body.value()).to(be_equal_to(response.body.value()));
+    expect!(read_response.body.content_type()).to(be_some().value(s!("application/protobuf")));
+
+    let response = Response { body: OptionalBody::Present(vec![0xFF, 0x00, 0xFE], Some(s!("image/gif"))), .. Response::default() };
+    let json = response.to_json(&PactSpecification::V3);
+    let read_response = Response::from_json(&json, &PactSpecification::V3);
+    expect!(read_response.body.value()).to(be_equal_to(vec![0xFF, 0x00, 0xFE]));
+    expect!(read_response.content_type()).to(be_equal_to(s!("application/octet-stream")));
+}
+
 #[test]
 fn response_to_json_with_empty_body() {
     let response = Response { body: OptionalBody::Empty, .. Response::default() };
@@ -970,6 +1109,38 @@ fn write_pact_test_should_merge_pacts() {
 }}"#, super::VERSION.unwrap())));
 }
 
+#[test]
+fn write_pact_from_multiple_threads_should_merge_all_the_interactions() {
+    let mut dir = env::temp_dir();
+    let x = rand::random::<u16>();
+    dir.push(format!("pact_test_{}", x));
+    dir.push("concurrent_consumer-concurrent_provider.json");
+
+    let handles: Vec<_> = (0..8).map(|i| {
+        let path = dir.clone();
+        thread::spawn(move || {
+            let pact = Pact { consumer: Consumer { name: s!("concurrent_consumer") },
+                provider: Provider { name: s!("concurrent_provider") },
+                interactions: vec![
+                    Interaction {
+                        description: format!("Test Interaction {}", i),
+                        .. Interaction::default()
+                    }
+                ],
+                .. Pact::default()
+            };
+            pact.write_pact(path.as_path(), PactSpecification::V3)
+        })
+    }).collect();
+    let results: Vec<io::Result<()>> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+
+    let pact = Pact::read_pact(dir.as_path());
+    fs::remove_dir_all(dir.parent().unwrap()).unwrap_or(());
+
+    expect!(results.iter().all(|result| result.is_ok())).to(be_true());
+    expect!(pact.unwrap().interactions.len()).to(be_equal_to(8));
+}
+
 #[test]
 fn write_pact_test_should_not_merge_pacts_with_conflicts() {
     let pact = Pact { consumer: Consumer { name: s!("write_pact_test_consumer") },
@@ -1139,6 +1310,128 @@ fn pact_merge_removes_duplicates() {
     expect!(merged_pact.clone().unwrap().interactions.len()).to(be_equal_to(2));
 }
 
+fn conflicting_pacts() -> (Pact, Pact) {
+    let pact = Pact { consumer: Consumer { name: s!("test_consumer") },
+        provider: Provider { name: s!("test_provider") },
+        interactions: vec![
+            Interaction {
+                description: s!("Test Interaction"),
+                provider_states: vec![ProviderState { name: s!("Good state to be in"), params: hashmap!{} }],
+                .. Interaction::default()
+            },
+            Interaction {
+                description: s!("Other Interaction"),
+                .. Interaction::default()
+            }
+        ],
+        .. Pact::default()
+    };
+    let pact2 = Pact { consumer: Consumer { name: s!("test_consumer") },
+        provider: Provider { name: s!("test_provider") },
+        interactions: vec![
+            Interaction {
+                description: s!("Test Interaction"),
+                provider_states: vec![ProviderState { name: s!("Good state to be in"), params: hashmap!{} }],
+                request: Request { method: s!("POST"), .. Request::default() },
+                response: Response { status: 400, .. Response::default() },
+                .. Interaction::default()
+            }
+        ],
+        .. Pact::default()
+    };
+    (pact, pact2)
+}
+
+#[test]
+fn pact_merge_with_fail_strategy_returns_all_the_conflicts() {
+    let (pact, pact2) = conflicting_pacts();
+    let result = pact.merge_with_strategy(&pact2, MergeStrategy::Fail);
+    expect!(result.clone()).to(be_err().value(PactMergeError::Conflicts(vec![
+        PactConflict { interaction: s!("Test Interaction"), description: s!("Request method GET != POST") },
+        PactConflict { interaction: s!("Test Interaction"), description: s!("Response status 200 != 400") }
+    ])));
+    expect!(result.unwrap_err().to_string()).to(be_equal_to(s!("Unable to merge pacts, as there were 2 conflict(s) between the interactions\n  - Interaction 'Test Interaction': Request method GET != POST\n  - Interaction 'Test Interaction': Response status 200 != 400")));
+    expect!(pact.merge(&pact2)).to(be_err().value(s!("Unable to merge pacts, as there were 1 conflict(s) between the interactions")));
+}
+
+#[test]
+fn pact_merge_with_last_writer_wins_strategy_replaces_the_conflicting_interaction() {
+    let (pact, pact2) = conflicting_pacts();
+    let merged_pact = pact.merge_with_strategy(&pact2, MergeStrategy::LastWriterWins).unwrap();
+    expect!(merged_pact.interactions.iter().map(|i| (i.description.clone(), i.response.status)).collect::<Vec<(String, u16)>>())
+        .to(be_equal_to(vec![(s!("Other Interaction"), 200), (s!("Test Interaction"), 400)]));
+}
+
+#[test]
+fn pact_merge_with_keep_both_strategy_renames_the_conflicting_interaction() {
+    let (pact, pact2) = conflicting_pacts();
+    let merged_pact = pact.merge_with_strategy(&pact2, MergeStrategy::KeepBoth).unwrap();
+    expect!(merged_pact.interactions.iter().map(|i| (i.description.clone(), i.response.status)).collect::<Vec<(String, u16)>>())
+        .to(be_equal_to(vec![(s!("Other Interaction"), 200), (s!("Test Interaction"), 200), (s!("Test Interaction (2)"), 400)]));
+}
+
+fn pacts_with_a_changed_body() -> (Pact, Pact) {
+    let interaction = |body: &str| Interaction {
+        description: s!("Test Interaction"),
+        provider_states: vec![ProviderState { name: s!("Good state to be in"), params: hashmap!{} }],
+        response: Response { body: OptionalBody::Present(body.into(), None), .. Response::default() },
+        .. Interaction::default()
+    };
+    let pact = Pact { consumer: Consumer { name: s!("test_consumer") },
+        provider: Provider { name: s!("test_provider") },
+        interactions: vec![ interaction(r#"{"id": 1}"#) ],
+        .. Pact::default()
+    };
+    let pact2 = Pact { interactions: vec![ interaction(r#"{"id": 2}"#) ], .. pact.clone() };
+    (pact, pact2)
+}
+
+#[test]
+fn pact_merge_with_a_changed_body_keeps_both_interactions_like_merge() {
+    let (pact, pact2) = pacts_with_a_changed_body();
+    let merged_pact = pact.merge_with_strategy(&pact2, MergeStrategy::Fail).unwrap();
+    expect!(merged_pact.interactions.len()).to(be_equal_to(2));
+    expect!(pact.merge(&pact2).unwrap().interactions.len()).to(be_equal_to(2));
+}
+
+#[test]
+fn pact_merge_with_last_writer_wins_strategy_replaces_an_interaction_with_a_changed_body() {
+    let (pact, pact2) = pacts_with_a_changed_body();
+    let merged_pact = pact.merge_with_strategy(&pact2, MergeStrategy::LastWriterWins).unwrap();
+    expect!(merged_pact.interactions.iter().map(|i| (i.description.clone(), i.response.body.str_value().to_string()))
+        .collect::<Vec<(String, String)>>()).to(be_equal_to(vec![(s!("Test Interaction"), s!(r#"{"id": 2}"#))]));
+}
+
+#[test]
+fn pact_merge_with_keep_both_strategy_renames_an_interaction_with_a_changed_body() {
+    let (pact, pact2) = pacts_with_a_changed_body();
+    let merged_pact = pact.merge_with_strategy(&pact2, MergeStrategy::KeepBoth).unwrap();
+    expect!(merged_pact.interactions.iter().map(|i| (i.description.clone(), i.response.body.str_value().to_string()))
+        .collect::<Vec<(String, String)>>()).to(be_equal_to(vec![
+            (s!("Test Interaction"), s!(r#"{"id": 1}"#)),
+            (s!("Test Interaction (2)"), s!(r#"{"id": 2}"#))
+        ]));
+
+    let merged_pact = pact.merge_with_strategy(&pact.clone(), MergeStrategy::KeepBoth).unwrap();
+    expect!(merged_pact.interactions.len()).to(be_equal_to(1));
+}
+
+#[test]
+fn pact_merge_with_strategy_does_not_merge_different_consumers() {
+    let (pact, mut pact2) = conflicting_pacts();
+    pact2.consumer = Consumer { name: s!("other_consumer") };
+    expect!(pact.merge_with_strategy(&pact2, MergeStrategy::LastWriterWins)).to(be_err().value(PactMergeError::DifferentParticipants));
+}
+
+#[test]
+fn merge_strategy_from_str() {
+    expect!(MergeStrategy::from_str("fail")).to(be_ok().value(MergeStrategy::Fail));
+    expect!(MergeStrategy::from_str("Last-Writer-Wins")).to(be_ok().value(MergeStrategy::LastWriterWins));
+    expect!(MergeStrategy::from_str("keep-both")).to(be_ok().value(MergeStrategy::KeepBoth));
+    expect!(MergeStrategy::from_str("merge")).to(be_err());
+    expect!(MergeStrategy::from_str("overwrite")).to(be_err());
+}
+
 #[test]
 fn interactions_do_not_conflict_if_they_have_different_descriptions() {
     let interaction1 = Interaction {
@@ -1563,7 +1856,7 @@ fn body_from_json_returns_json_string_if_the_body_is_json_but_not_a_string() {
       }
      "#).unwrap();
     let body = body_from_json(&json, "body", &None);
-    expect!(body).to(be_equal_to(OptionalBody::Present("{\"test\":true}".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("{\"test\":true}".into(), None)));
 }
 
 #[test]
@@ -1591,7 +1884,7 @@ fn body_from_json_returns_the_body_if_the_body_is_a_string() {
       }
      "#).unwrap();
     let body = body_from_json(&json, "body", &None);
-    expect!(body).to(be_equal_to(OptionalBody::Present("<?xml version=\"1.0\"?> <body></body>".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("<?xml version=\"1.0\"?> <body></body>".into(), None)));
 }
 
 #[test]
@@ -1606,7 +1899,7 @@ fn body_from_text_plain_type_returns_the_same_formatted_body() {
      "#).unwrap();
     let headers = headers_from_json(&json);
     let body = body_from_json(&json, "body", &headers);
-    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is a string\"".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is a string\"".into(), None)));
 }
 
 #[test]
@@ -1621,7 +1914,7 @@ fn body_from_text_html_type_returns_the_same_formatted_body() {
      "#).unwrap();
     let headers = headers_from_json(&json);
     let body = body_from_json(&json, "body", &headers);
-    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is a string\"".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is a string\"".into(), None)));
 }
 
 #[test]
@@ -1636,7 +1929,7 @@ fn body_from_json_returns_the_a_json_formatted_body_if_the_body_is_a_string_and_
      "#).unwrap();
     let headers = headers_from_json(&json);
     let body = body_from_json(&json, "body", &headers);
-    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is actually a JSON string\"".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is actually a JSON string\"".into(), None)));
 }
 
 #[test]
@@ -1651,7 +1944,7 @@ fn body_from_json_returns_the_a_json_formatted_body_if_the_body_is_a_valid_json_
      "#).unwrap();
     let headers = headers_from_json(&json);
     let body = body_from_json(&json, "body", &headers);
-    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is actually a JSON string\"".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("\"This is actually a JSON string\"".into(), None)));
 }
 
 #[test]
@@ -1666,7 +1959,101 @@ fn body_from_json_returns_the_body_if_the_content_type_is_json() {
      "#).unwrap();
     let headers = headers_from_json(&json);
     let body = body_from_json(&json, "body", &headers);
-    expect!(body).to(be_equal_to(OptionalBody::Present("{\"test\":true}".into())));
+    expect!(body).to(be_equal_to(OptionalBody::Present("{\"test\":true}".into(), None)));
+}
+
+#[test]
+fn body_from_json_decodes_the_body_if_it_is_base64_encoded() {
+    let json : serde_json::Value = serde_json::from_str(r#"
+      {
+          "path": "/",
+          "query": "",
+          "headers": {"Content-Type": "image/png"},
+          "body": "iVBORw0KGgo=",
+          "bodyEncoding": "base64"
+      }
+     "#).unwrap();
+    let headers = headers_from_json(&json);
+    let body = body_from_json(&json, "body", &headers);
+    expect!(body).to(be_equal_to(OptionalBody::Present(vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A],
+        Some(s!("image/png")))));
+}
+
+#[test]
+fn body_from_json_defaults_the_content_type_of_a_base64_encoded_body() {
+    let json : serde_json::Value = serde_json::from_str(r#"
+      {
+          "path": "/",
+          "query": "",
+          "body": "/wD+",
+          "bodyEncoding": "base64"
+      }
+     "#).unwrap();
+    let body = body_from_json(&json, "body", &None);
+    expect!(body.clone()).to(be_equal_to(OptionalBody::Present(vec![0xFF, 0x00, 0xFE], None)));
+    expect!(body.content_type()).to(be_some().value(s!("application/octet-stream")));
+}
+
+#[test]
+fn body_from_json_does_not_decode_a_text_body_if_it_is_not_marked_as_base64_encoded() {
+    let json : serde_json::Value = serde_json::from_str(r#"
+      {
+          "path": "/",
+          "query": "",
+          "headers": {"Content-Type": "application/csv"},
+          "body": "abcd"
+      }
+     "#).unwrap();
+    let headers = headers_from_json(&json);
+    let body = body_from_json(&json, "body", &headers);
+    expect!(body.clone()).to(be_equal_to(OptionalBody::Present("abcd".into(), None)));
+    expect!(body.content_type()).to(be_none());
+}
+
+#[test]
+fn body_from_json_decodes_a_binary_body_that_is_not_marked_as_base64_encoded() {
+    let json : serde_json::Value = serde_json::from_str(r#"
+      {
+          "path": "/",
+          "query": "",
+          "headers": {"Content-Type": "image/gif"},
+          "body": "/wD+"
+      }
+     "#).unwrap();
+    let headers = headers_from_json(&json);
+    let body = body_from_json(&json, "body", &headers);
+    expect!(body.clone()).to(be_equal_to(OptionalBody::Present(vec![0xFF, 0x00, 0xFE], None)));
+    expect!(body.content_type()).to(be_some().value(s!("image/gif")));
+}
+
+#[test]
+fn pact_from_json_loads_binary_bodies_from_pacts_without_the_encoding_marker() {
+    let pact_json = json!({
+        "consumer": { "name": "consumer" },
+        "provider": { "name": "provider" },
+        "interactions": [{
+            "description": "a request for an image",
+            "request": { "method": "GET", "path": "/image" },
+            "response": {
+                "status": 200,
+                "headers": { "Content-Type": "image/png" },
+                "body": "iVBORw0KGgo="
+            }
+        }],
+        "metadata": { "pact-specification": { "version": "2.0.0" } }
+    });
+    let pact = Pact::from_json(&s!("baseline-pact.json"), &pact_json);
+    let response = &pact.interactions[0].response;
+    expect!(response.body.value()).to(be_equal_to(vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]));
+    expect!(response.body.content_type()).to(be_some().value(s!("image/png")));
+}
+
+#[test]
+fn optional_body_equality_ignores_the_content_type() {
+    let body = OptionalBody::Present(vec![0xFF, 0x00, 0xFE], Some(s!("image/gif")));
+    expect!(body.clone()).to(be_equal_to(OptionalBody::Present(vec![0xFF, 0x00, 0xFE], None)));
+    expect!(hash(&body)).to(be_equal_to(hash(&OptionalBody::Present(vec![0xFF, 0x00, 0xFE], None))));
+    expect!(body).to_not(be_equal_to(OptionalBody::Present(vec![0xFF, 0x00], None)));
 }
 
 #[test]
@@ -1931,3 +2318,69 @@ fn write_pact_test_with_generators() {
   }}
 }}"#, super::VERSION.unwrap())));
 }
+
+#[test]
+fn canonical_json_is_the_same_regardless_of_the_interaction_and_hash_map_order() {
+    let interaction1 = Interaction {
+        description: s!("Test Interaction 1"),
+        provider_states: vec![ProviderState { name: s!("state"), params: hashmap!{ s!("b") => json!(2), s!("a") => json!(1) } }],
+        request: Request {
+            headers: Some(hashmap!{ s!("Z-Header") => vec![s!("z")], s!("A-Header") => vec![s!("a")] }),
+            generators: generators!{
+                "BODY" => {
+                  "$.b" => Generator::RandomInt(1, 10),
+                  "$.a" => Generator::Uuid
+                }
+            },
+            .. Request::default()
+        },
+        .. Interaction::default()
+    };
+    let interaction2 = Interaction { description: s!("Test Interaction 2"), .. Interaction::default() };
+    let interaction3 = Interaction { description: s!("Test Interaction 2"), request: Request {
+        method: s!("POST"), .. Request::default() }, .. Interaction::default() };
+    let pact = Pact { interactions: vec![interaction1.clone(), interaction2.clone(), interaction3.clone()], .. Pact::default() };
+    let pact2 = Pact { interactions: vec![interaction3, interaction2, interaction1], .. Pact::default() };
+
+    let canonical = pact.to_canonical_string(PactSpecification::V3);
+    expect!(pact2.to_canonical_string(PactSpecification::V3)).to(be_equal_to(canonical.clone()));
+    let reloaded = Pact::from_json(&s!("test"), &serde_json::from_str(&canonical).unwrap());
+    expect!(reloaded.to_canonical_string(PactSpecification::V3)).to(be_equal_to(canonical.clone()));
+
+    let descriptions: Vec<String> = reloaded.interactions.iter().map(|i| i.description.clone()).collect();
+    expect!(descriptions).to(be_equal_to(vec![s!("Test Interaction 2"), s!("Test Interaction 2"), s!("Test Interaction 1")]));
+    expect!(canonical.find("\"$.a\"").unwrap() < canonical.find("\"$.b\"").unwrap()).to(be_true());
+    expect!(canonical.find("\"A-Header\"").unwrap() < canonical.find("\"Z-Header\"").unwrap()).to(be_true());
+}
+
+#[test]
+fn read_pact_from_reader_test() {
+    let pact_json = r#"{
+      "consumer": { "name": "reader_consumer" },
+      "provider": { "name": "reader_provider" },
+      "interactions": [],
+      "metadata": { "pactSpecification": { "version": "3.0.0" } }
+    }"#;
+    let pact = Pact::read_pact_from_reader("<stdin>", pact_json.as_bytes());
+    expect!(pact.as_ref().map(|pact| pact.consumer.name.clone())).to(be_ok().value(s!("reader_consumer")));
+    let pact = Pact::read_pact_from_reader("<stdin>", r#"{"consumer": 100}"#.as_bytes());
+    expect!(pact.as_ref().map(|pact| pact.consumer.name.clone())).to(be_ok().value(s!("consumer")));
+    expect!(Pact::read_pact_from_reader("<stdin>", "not json".as_bytes())).to(be_err());
+}
+
+#[test]
+fn read_pact_from_reader_strict_test() {
+    let pact_json = r#"{
+      "consumer": { "name": "reader_consumer" },
+      "provider": { "name": "reader_provider" },
+      "interactions": [],
+      "metadata": { "pactSpecification": { "version": "3.0.0" } }
+    }"#;
+    let pact = Pact::read_pact_from_reader_strict("<stdin>", pact_json.as_bytes());
+    expect!(pact.as_ref().map(|pact| pact.consumer.name.clone())).to(be_ok().value(s!("reader_consumer")));
+
+    let error = Pact::read_pact_from_reader_strict("<stdin>", r#"{"consumer": 100}"#.as_bytes()).unwrap_err();
+    expect!(error.kind()).to(be_equal_to(io::ErrorKind::InvalidData));
+    expect!(error.to_string().starts_with("Pact file <stdin> is not valid")).to(be_true());
+    expect!(Pact::read_pact_from_reader_strict("<stdin>", "not json".as_bytes())).to(be_err());
+}
@@ -0,0 +1,558 @@
+//! The `pact_diff` module provides functions to compare two versions of a pact, and classify each
+//! change as either compatible or breaking.
+//!
+//! Changes are classified from the point of view of the provider: a change is breaking if a
+//! provider that has been verified against the old version of the pact may fail verification
+//! against the new version.
+//!
+//! * Adding an interaction is breaking, removing one is compatible.
+//! * Any change to the request that will be sent to the provider (method, path, query parameters,
+//!   headers or body) is breaking. Changes to the request matching rules only affect the consumer
+//!   tests, so are compatible.
+//! * Adding a field or header to the response is breaking, removing one is compatible. Changing an
+//!   example value is compatible if there is a matching rule for it and the type of the value has
+//!   not changed.
+//! * Relaxing a response matching rule (including adding a rule where values were compared by
+//!   equality) is compatible, tightening or removing one is breaking.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use serde_json::{self, Value};
+use models::{Pact, Interaction, HttpPart, OptionalBody, DetectedContentType};
+use models::matchingrules::{MatchingRules, MatchingRule, RuleList};
+use models::parse_errors::child_path;
+
+/// If a change is compatible with providers verified against the previous version of the pact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The provider will still be able to be verified against the new pact
+    Compatible,
+    /// The provider may fail verification against the new pact
+    Breaking
+}
+
+/// The type of change between the two versions of the pact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeType {
+    /// A new interaction has been added
+    InteractionAdded,
+    /// An interaction has been removed
+    InteractionRemoved,
+    /// A field, header or query parameter has been added
+    FieldAdded,
+    /// A field, header or query parameter has been removed
+    FieldRemoved,
+    /// A value has been changed
+    ValueChanged,
+    /// A matching rule has been added
+    MatchingRuleAdded,
+    /// A matching rule has been removed
+    MatchingRuleRemoved,
+    /// A matching rule has been changed to accept more values
+    MatchingRuleRelaxed,
+    /// A matching rule has been changed to accept fewer values
+    MatchingRuleTightened,
+    /// A matching rule has been changed, but it is not known if it is more or less strict
+    MatchingRuleChanged
+}
+
+/// A change between two versions of a pact
+#[derive(Debug, Clone, PartialEq)]
+pub struct PactChange {
+    /// Description of the interaction that changed
+    pub interaction: String,
+    /// Type of change
+    pub change_type: ChangeType,
+    /// Path to the item in the interaction that changed (i.e. `response.body.user.id`)
+    pub path: String,
+    /// If the change is breaking
+    pub compatibility: Compatibility,
+    /// Description of the change
+    pub description: String
+}
+
+impl PactChange {
+    /// If this change is breaking
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+
+    /// Converts this change to a `Value` struct
+    pub fn to_json(&self) -> Value {
+        json!({
+            s!("interaction"): self.interaction.clone(),
+            s!("type"): format!("{:?}", self.change_type),
+            s!("path"): self.path.clone(),
+            s!("breaking"): self.is_breaking(),
+            s!("description"): self.description.clone()
+        })
+    }
+}
+
+impl Display for PactChange {
+    fn fmt(&self, f: &mut Formatter) -> ::std::fmt::Result {
+        let compatibility = match self.compatibility {
+            Compatibility::Compatible => "compatible",
+            Compatibility::Breaking => "BREAKING"
+        };
+        if self.path.is_empty() {
+            write!(f, "[{}] '{}': {}", compatibility, self.interaction, self.description)
+        } else {
+            write!(f, "[{}] '{}' {}: {}", compatibility, self.interaction, self.path, self.description)
+        }
+    }
+}
+
+/// Returns true if any of the changes are breaking
+pub fn has_breaking_changes(changes: &Vec<PactChange>) -> bool {
+    changes.iter().any(|change| change.is_breaking())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    Request,
+    Response
+}
+
+struct DiffContext<'a> {
+    interaction: String,
+    part: Part,
+    rules: &'a MatchingRules,
+    changes: Vec<PactChange>
+}
+
+impl <'a> DiffContext<'a> {
+    fn add<P: Into<String>, D: Into<String>>(&mut self, change_type: ChangeType, path: P, compatibility: Compatibility, description: D) {
+        self.changes.push(PactChange {
+            interaction: self.interaction.clone(),
+            change_type,
+            path: path.into(),
+            compatibility,
+            description: description.into()
+        });
+    }
+
+    /// Changes to what is sent to the provider are always breaking, while for the response it
+    /// depends on the change
+    fn compatibility(&self, response_compatibility: Compatibility) -> Compatibility {
+        match self.part {
+            Part::Request => Compatibility::Breaking,
+            Part::Response => response_compatibility
+        }
+    }
+}
+
+fn same_interaction(interaction: &Interaction, other: &Interaction) -> bool {
+    interaction.description == other.description && interaction.provider_states == other.provider_states
+}
+
+/// Compares the new version of the pact to the old one, and returns all the changes
+pub fn diff_pacts(old: &Pact, new: &Pact) -> Vec<PactChange> {
+    let mut changes = vec![];
+    for old_interaction in &old.interactions {
+        match new.interactions.iter().find(|i| same_interaction(old_interaction, i)) {
+            Some(new_interaction) => changes.extend(diff_interactions(old_interaction, new_interaction)),
+            None => changes.push(PactChange {
+                interaction: old_interaction.description.clone(),
+                change_type: ChangeType::InteractionRemoved,
+                path: s!(""),
+                compatibility: Compatibility::Compatible,
+                description: s!("Interaction has been removed")
+            })
+        }
+    }
+    for new_interaction in &new.interactions {
+        if !old.interactions.iter().any(|i| same_interaction(new_interaction, i)) {
+            changes.push(PactChange {
+                interaction: new_interaction.description.clone(),
+                change_type: ChangeType::InteractionAdded,
+                path: s!(""),
+                compatibility: Compatibility::Breaking,
+                description: s!("Interaction has been added")
+            });
+        }
+    }
+    changes
+}
+
+/// Compares the new version of an interaction to the old one, and returns all the changes
+pub fn diff_interactions(old: &Interaction, new: &Interaction) -> Vec<PactChange> {
+    let mut request_context = DiffContext {
+        interaction: new.description.clone(),
+        part: Part::Request,
+        rules: &new.request.matching_rules,
+        changes: vec![]
+    };
+    if old.request.method.to_uppercase() != new.request.method.to_uppercase() {
+        request_context.add(ChangeType::ValueChanged, "request.method", Compatibility::Breaking,
+            format!("Request method changed from {} to {}", old.request.method, new.request.method));
+    }
+    if old.request.path != new.request.path {
+        request_context.add(ChangeType::ValueChanged, "request.path", Compatibility::Breaking,
+            format!("Request path changed from '{}' to '{}'", old.request.path, new.request.path));
+    }
+    diff_multi_values(&mut request_context, "request.query", "query", "Query parameter",
+        &old.request.query.clone().unwrap_or_default(), &new.request.query.clone().unwrap_or_default());
+    diff_multi_values(&mut request_context, "request.headers", "header", "Header",
+        &lowercase_keys(&old.request.headers), &lowercase_keys(&new.request.headers));
+    diff_bodies(&mut request_context, "request.body", &old.request, &new.request);
+    diff_matching_rules(&mut request_context, "request.matchingRules", &old.request.matching_rules, &new.request.matching_rules);
+
+    let mut response_context = DiffContext {
+        interaction: new.description.clone(),
+        part: Part::Response,
+        rules: &new.response.matching_rules,
+        changes: request_context.changes
+    };
+    if old.response.status != new.response.status {
+        let compatibility = if response_context.rules.matcher_is_defined("status", &vec![]) {
+            Compatibility::Compatible
+        } else {
+            Compatibility::Breaking
+        };
+        response_context.add(ChangeType::ValueChanged, "response.status", compatibility,
+            format!("Response status changed from {} to {}", old.response.status, new.response.status));
+    }
+    diff_multi_values(&mut response_context, "response.headers", "header", "Header",
+        &lowercase_keys(&old.response.headers), &lowercase_keys(&new.response.headers));
+    diff_bodies(&mut response_context, "response.body", &old.response, &new.response);
+    diff_matching_rules(&mut response_context, "response.matchingRules", &old.response.matching_rules, &new.response.matching_rules);
+
+    response_context.changes
+}
+
+fn lowercase_keys(headers: &Option<HashMap<String, Vec<String>>>) -> HashMap<String, Vec<String>> {
+    headers.clone().unwrap_or_default().into_iter()
+        .map(|(key, values)| (key.to_lowercase(), values))
+        .collect()
+}
+
+fn diff_multi_values(context: &mut DiffContext, path: &str, category: &str, name: &str,
+                     old: &HashMap<String, Vec<String>>, new: &HashMap<String, Vec<String>>) {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        let key_path = child_path(path, key);
+        match (old.get(key), new.get(key)) {
+            (Some(_), None) => {
+                let compatibility = context.compatibility(Compatibility::Compatible);
+                context.add(ChangeType::FieldRemoved, key_path, compatibility, format!("{} '{}' has been removed", name, key));
+            },
+            (None, Some(_)) => {
+                let compatibility = context.compatibility(Compatibility::Breaking);
+                context.add(ChangeType::FieldAdded, key_path, compatibility, format!("{} '{}' has been added", name, key));
+            },
+            (Some(old_values), Some(new_values)) => if old_values != new_values {
+                let matched = context.rules.matcher_is_defined(category, &vec![s!("$"), key.clone()]) ||
+                    context.rules.matcher_is_defined(category, &vec![key.clone()]);
+                let compatibility = context.compatibility(if matched { Compatibility::Compatible } else { Compatibility::Breaking });
+                context.add(ChangeType::ValueChanged, key_path, compatibility,
+                    format!("{} '{}' changed from {:?} to {:?}", name, key, old_values, new_values));
+            },
+            (None, None) => ()
+        }
+    }
+}
+
+fn json_body<T: HttpPart>(part: &T) -> Option<Value> {
+    match part.content_type_enum() {
+        DetectedContentType::Json => serde_json::from_slice(&part.body().value()).ok(),
+        _ => None
+    }
+}
+
+fn diff_bodies<T: HttpPart>(context: &mut DiffContext, path: &str, old: &T, new: &T) {
+    match (old.body(), new.body()) {
+        (&OptionalBody::Present(_, _), &OptionalBody::Present(_, _)) => match (json_body(old), json_body(new)) {
+            (Some(ref old_json), Some(ref new_json)) => diff_json(context, path, &vec![s!("$")], old_json, new_json),
+            _ => if old.body().value() != new.body().value() {
+                let compatibility = context.compatibility(Compatibility::Breaking);
+                context.add(ChangeType::ValueChanged, path, compatibility, "Body contents have changed");
+            }
+        },
+        (&OptionalBody::Present(_, _), _) => {
+            let compatibility = context.compatibility(Compatibility::Compatible);
+            context.add(ChangeType::FieldRemoved, path, compatibility, "Body has been removed");
+        },
+        (_, &OptionalBody::Present(_, _)) => {
+            let compatibility = context.compatibility(Compatibility::Breaking);
+            context.add(ChangeType::FieldAdded, path, compatibility, "Body has been added");
+        },
+        _ => ()
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match *value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object"
+    }
+}
+
+fn diff_json(context: &mut DiffContext, path: &str, json_path: &Vec<String>, old: &Value, new: &Value) {
+    match (old, new) {
+        (&Value::Object(ref old_map), &Value::Object(ref new_map)) => {
+            let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+            for key in keys {
+                let key_path = child_path(path, key);
+                let mut key_json_path = json_path.clone();
+                key_json_path.push(key.clone());
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(old_value), Some(new_value)) => diff_json(context, &key_path, &key_json_path, old_value, new_value),
+                    (Some(_), None) => {
+                        let compatibility = context.compatibility(Compatibility::Compatible);
+                        context.add(ChangeType::FieldRemoved, key_path, compatibility, format!("Field '{}' has been removed", key));
+                    },
+                    (None, Some(_)) => {
+                        let compatibility = context.compatibility(Compatibility::Breaking);
+                        context.add(ChangeType::FieldAdded, key_path, compatibility, format!("Field '{}' has been added", key));
+                    },
+                    (None, None) => ()
+                }
+            }
+        },
+        (&Value::Array(ref old_list), &Value::Array(ref new_list)) => {
+            for index in 0..old_list.len().max(new_list.len()) {
+                let index_path = format!("{}[{}]", path, index);
+                let mut index_json_path = json_path.clone();
+                index_json_path.push(index.to_string());
+                match (old_list.get(index), new_list.get(index)) {
+                    (Some(old_value), Some(new_value)) => diff_json(context, &index_path, &index_json_path, old_value, new_value),
+                    (Some(_), None) => {
+                        let compatibility = context.compatibility(Compatibility::Compatible);
+                        context.add(ChangeType::FieldRemoved, index_path, compatibility, format!("Item {} has been removed", index));
+                    },
+                    (None, Some(_)) => {
+                        let matched = context.rules.matcher_is_defined("body", json_path);
+                        let compatibility = context.compatibility(if matched { Compatibility::Compatible } else { Compatibility::Breaking });
+                        context.add(ChangeType::FieldAdded, index_path, compatibility, format!("Item {} has been added", index));
+                    },
+                    (None, None) => ()
+                }
+            }
+        },
+        _ => if old != new {
+            let matched = context.rules.matcher_is_defined("body", json_path) && json_type(old) == json_type(new);
+            let compatibility = context.compatibility(if matched { Compatibility::Compatible } else { Compatibility::Breaking });
+            context.add(ChangeType::ValueChanged, path, compatibility, format!("Value changed from {} to {}", old, new));
+        }
+    }
+}
+
+fn diff_matching_rules(context: &mut DiffContext, path: &str, old: &MatchingRules, new: &MatchingRules) {
+    let categories: BTreeSet<String> = old.categories().into_iter().chain(new.categories().into_iter()).collect();
+    for category in categories {
+        let old_rules = old.rules_for_category(&category).map(|c| c.rules).unwrap_or_default();
+        let new_rules = new.rules_for_category(&category).map(|c| c.rules).unwrap_or_default();
+        let keys: BTreeSet<&String> = old_rules.keys().chain(new_rules.keys()).collect();
+        let category_path = child_path(path, &category);
+        for key in keys {
+            let rule_path = if key.is_empty() { category_path.clone() } else { child_path(&category_path, key) };
+            let (change_type, description) = match (old_rules.get(key), new_rules.get(key)) {
+                (Some(_), None) => (ChangeType::MatchingRuleRemoved, s!("Matching rule has been removed")),
+                (None, Some(rules)) => (ChangeType::MatchingRuleAdded, format!("Matching rule {:?} has been added", rules.rules)),
+                (Some(old_list), Some(new_list)) => if old_list != new_list {
+                    (compare_rule_lists(old_list, new_list),
+                        format!("Matching rule changed from {:?} to {:?}", old_list.rules, new_list.rules))
+                } else {
+                    continue
+                },
+                (None, None) => continue
+            };
+            let compatibility = match context.part {
+                Part::Request => Compatibility::Compatible,
+                Part::Response => match change_type {
+                    ChangeType::MatchingRuleAdded | ChangeType::MatchingRuleRelaxed => Compatibility::Compatible,
+                    _ => Compatibility::Breaking
+                }
+            };
+            context.add(change_type, rule_path, compatibility, description);
+        }
+    }
+}
+
+/// How strict a matching rule is. Equality is the most strict, while type matching is the least.
+fn rule_strictness(rule: &MatchingRule) -> u8 {
+    match *rule {
+        MatchingRule::Equality => 4,
+        MatchingRule::Regex(_) | MatchingRule::Include(_) | MatchingRule::Timestamp(_) |
+        MatchingRule::Time(_) | MatchingRule::Date(_) => 3,
+        MatchingRule::Number | MatchingRule::Integer | MatchingRule::Decimal | MatchingRule::Null => 2,
+        MatchingRule::Type | MatchingRule::MinType(_) | MatchingRule::MaxType(_) | MatchingRule::MinMaxType(_, _) => 1
+    }
+}
+
+fn type_bounds(rule: &MatchingRule) -> Option<(usize, usize)> {
+    match *rule {
+        MatchingRule::Type => Some((0, usize::max_value())),
+        MatchingRule::MinType(min) => Some((min, usize::max_value())),
+        MatchingRule::MaxType(max) => Some((0, max)),
+        MatchingRule::MinMaxType(min, max) => Some((min, max)),
+        _ => None
+    }
+}
+
+fn compare_rule_lists(old: &RuleList, new: &RuleList) -> ChangeType {
+    if old.rules.len() != 1 || new.rules.len() != 1 {
+        return ChangeType::MatchingRuleChanged
+    }
+    let old_rule = &old.rules[0];
+    let new_rule = &new.rules[0];
+    let old_strictness = rule_strictness(old_rule);
+    let new_strictness = rule_strictness(new_rule);
+    if new_strictness > old_strictness {
+        ChangeType::MatchingRuleTightened
+    } else if new_strictness < old_strictness {
+        ChangeType::MatchingRuleRelaxed
+    } else {
+        match (type_bounds(old_rule), type_bounds(new_rule)) {
+            (Some((old_min, old_max)), Some((new_min, new_max))) => if new_min >= old_min && new_max <= old_max {
+                ChangeType::MatchingRuleTightened
+            } else if new_min <= old_min && new_max >= old_max {
+                ChangeType::MatchingRuleRelaxed
+            } else {
+                ChangeType::MatchingRuleChanged
+            },
+            _ => ChangeType::MatchingRuleChanged
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use models::{Request, Response};
+    use models::provider_states::ProviderState;
+    use models::matchingrules::*;
+
+    fn interaction(description: &str, request: Request, response: Response) -> Interaction {
+        Interaction {
+            description: s!(description),
+            provider_states: vec![ProviderState::default(&s!("user exists"))],
+            request,
+            response,
+            .. Interaction::default()
+        }
+    }
+
+    fn pact(interactions: Vec<Interaction>) -> Pact {
+        Pact { interactions, .. Pact::default() }
+    }
+
+    fn json_response(body: &str, matching_rules: MatchingRules) -> Response {
+        Response {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+            body: OptionalBody::Present(body.into(), None),
+            matching_rules,
+            .. Response::default()
+        }
+    }
+
+    fn summary(changes: &Vec<PactChange>) -> Vec<(ChangeType, String, Compatibility)> {
+        changes.iter().map(|c| (c.change_type, c.path.clone(), c.compatibility)).collect()
+    }
+
+    #[test]
+    fn diff_pacts_with_added_and_removed_interactions() {
+        let old = pact(vec![interaction("a", Request::default(), Response::default()),
+                            interaction("b", Request::default(), Response::default())]);
+        let new = pact(vec![interaction("b", Request::default(), Response::default()),
+                            interaction("c", Request::default(), Response::default())]);
+        let changes = diff_pacts(&old, &new);
+        expect!(summary(&changes)).to(be_equal_to(vec![
+            (ChangeType::InteractionRemoved, s!(""), Compatibility::Compatible),
+            (ChangeType::InteractionAdded, s!(""), Compatibility::Breaking)
+        ]));
+        expect!(changes[1].interaction.clone()).to(be_equal_to(s!("c")));
+        expect!(has_breaking_changes(&changes)).to(be_true());
+    }
+
+    #[test]
+    fn diff_pacts_with_no_changes() {
+        let old = pact(vec![interaction("a", Request::default(), json_response(r#"{"id": 1}"#, MatchingRules::default()))]);
+        let changes = diff_pacts(&old, &old.clone());
+        expect!(changes.iter()).to(be_empty());
+        expect!(has_breaking_changes(&changes)).to(be_false());
+    }
+
+    #[test]
+    fn request_changes_are_breaking() {
+        let old_request = Request { query: Some(hashmap!{ s!("page") => vec![s!("1")] }), .. Request::default() };
+        let new_request = Request {
+            method: s!("POST"),
+            headers: Some(hashmap!{ s!("X-Token") => vec![s!("1234")] }),
+            matching_rules: matchingrules!{ "header" => { "X-Token" => [ MatchingRule::Regex(s!("\\d+")) ] } },
+            .. Request::default()
+        };
+        let changes = diff_interactions(&interaction("a", old_request, Response::default()),
+            &interaction("a", new_request, Response::default()));
+        expect!(summary(&changes)).to(be_equal_to(vec![
+            (ChangeType::ValueChanged, s!("request.method"), Compatibility::Breaking),
+            (ChangeType::FieldRemoved, s!("request.query.page"), Compatibility::Breaking),
+            (ChangeType::FieldAdded, s!("request.headers.x-token"), Compatibility::Breaking),
+            (ChangeType::MatchingRuleAdded, s!("request.matchingRules.header.X-Token"), Compatibility::Compatible)
+        ]));
+    }
+
+    #[test]
+    fn response_body_changes() {
+        let old = json_response(r#"{"id": 1, "name": "Fred", "age": 30, "tags": ["a"]}"#,
+            matchingrules!{ "body" => { "$.id" => [ MatchingRule::Type ] } });
+        let new = json_response(r#"{"id": 2, "name": "Fred", "age": 31, "email": "fred@x.com", "tags": ["a", "b"]}"#,
+            matchingrules!{ "body" => { "$.id" => [ MatchingRule::Type ], "$.tags" => [ MatchingRule::MinType(1) ] } });
+        let changes = diff_interactions(&interaction("a", Request::default(), old),
+            &interaction("a", Request::default(), new));
+        expect!(summary(&changes)).to(be_equal_to(vec![
+            (ChangeType::ValueChanged, s!("response.body.age"), Compatibility::Breaking),
+            (ChangeType::FieldAdded, s!("response.body.email"), Compatibility::Breaking),
+            (ChangeType::ValueChanged, s!("response.body.id"), Compatibility::Compatible),
+            (ChangeType::FieldAdded, s!("response.body.tags[1]"), Compatibility::Compatible),
+            (ChangeType::MatchingRuleAdded, s!("response.matchingRules.body['$.tags']"), Compatibility::Compatible)
+        ]));
+    }
+
+    #[test]
+    fn response_matching_rule_changes() {
+        let old = json_response(r#"{"a": 1, "b": 1, "c": 1, "d": "x"}"#, matchingrules!{
+            "body" => {
+                "$.a" => [ MatchingRule::MinType(1) ],
+                "$.b" => [ MatchingRule::Regex(s!("\\d+")) ],
+                "$.c" => [ MatchingRule::Integer ],
+                "$.d" => [ MatchingRule::Regex(s!("\\w+")) ]
+            }
+        });
+        let new = json_response(r#"{"a": 1, "b": 1, "c": 1, "d": "x"}"#, matchingrules!{
+            "body" => {
+                "$.a" => [ MatchingRule::MinType(2) ],
+                "$.b" => [ MatchingRule::Type ],
+                "$.d" => [ MatchingRule::Regex(s!("[a-z]+")) ]
+            }
+        });
+        let changes = diff_interactions(&interaction("a", Request::default(), old),
+            &interaction("a", Request::default(), new));
+        expect!(summary(&changes)).to(be_equal_to(vec![
+            (ChangeType::MatchingRuleTightened, s!("response.matchingRules.body['$.a']"), Compatibility::Breaking),
+            (ChangeType::MatchingRuleRelaxed, s!("response.matchingRules.body['$.b']"), Compatibility::Compatible),
+            (ChangeType::MatchingRuleRemoved, s!("response.matchingRules.body['$.c']"), Compatibility::Breaking),
+            (ChangeType::MatchingRuleChanged, s!("response.matchingRules.body['$.d']"), Compatibility::Breaking)
+        ]));
+    }
+
+    #[test]
+    fn response_header_and_status_changes() {
+        let old = Response { headers: Some(hashmap!{ s!("X-Old") => vec![s!("1")] }), .. Response::default() };
+        let new = Response { status: 201, headers: Some(hashmap!{ s!("X-New") => vec![s!("1")] }), .. Response::default() };
+        let changes = diff_interactions(&interaction("a", Request::default(), old),
+            &interaction("a", Request::default(), new));
+        expect!(summary(&changes)).to(be_equal_to(vec![
+            (ChangeType::ValueChanged, s!("response.status"), Compatibility::Breaking),
+            (ChangeType::FieldAdded, s!("response.headers.x-new"), Compatibility::Breaking),
+            (ChangeType::FieldRemoved, s!("response.headers.x-old"), Compatibility::Compatible)
+        ]));
+        expect!(changes[0].to_string()).to(be_equal_to(s!("[BREAKING] 'a' response.status: Response status changed from 200 to 201")));
+    }
+}
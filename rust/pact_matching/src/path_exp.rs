@@ -6,7 +6,9 @@ pub enum PathToken {
     Field(String),
     Index(usize),
     Star,
-    StarIndex
+    StarIndex,
+    /// `..` - matches any number (including none) of path elements
+    RecursiveDescent
 }
 
 fn peek<I>(chars: &mut Peekable<I>) -> Option<(usize, char)> where I: Iterator<Item = (usize, char)> {
@@ -61,33 +63,33 @@ fn path_identifier<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path
     }
 }
 
-// string_path -> [^']+
-fn string_path<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &String, index: usize) -> Result<(), String>
+// string_path -> ('([^'\\]|\\.)*' | "([^"\\]|\\.)*")
+// A backslash escapes the next character, so quotes and backslashes can be used in keys
+fn string_path<I>(quote: char, chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &String, index: usize) -> Result<(), String>
     where I: Iterator<Item = (usize, char)>  {
     let mut id = String::new();
-    let mut next_char = peek(chars);
-    if next_char.is_some() {
-        chars.next();
-        let mut ch = next_char.unwrap();
-        next_char = peek(chars);
-        while ch.1 != '\'' && next_char.is_some() {
-            id.push(ch.1);
-            chars.next();
-            ch = next_char.unwrap();
-            next_char = peek(chars);
-        }
-        if ch.1 == '\'' {
-            if id.is_empty() {
-                Err(format!("Empty strings are not allowed in path expression \"{}\" at index {}", path, ch.0))
+    let mut last_index = index;
+    loop {
+        match chars.next() {
+            Some((i, '\\')) => match chars.next() {
+                Some((i, ch)) => {
+                    id.push(ch);
+                    last_index = i;
+                },
+                None => return Err(format!("Unterminated string in path expression \"{}\" at index {}", path, i))
+            },
+            Some((i, ch)) if ch == quote => return if id.is_empty() {
+                Err(format!("Empty strings are not allowed in path expression \"{}\" at index {}", path, i))
             } else {
                 tokens.push(PathToken::Field(id));
                 Ok(())
-            }
-        } else {
-            Err(format!("Unterminated string in path expression \"{}\" at index {}", path, ch.0))
+            },
+            Some((i, ch)) => {
+                id.push(ch);
+                last_index = i;
+            },
+            None => return Err(format!("Unterminated string in path expression \"{}\" at index {}", path, last_index))
         }
-    } else {
-        Err(format!("Unterminated string in path expression \"{}\" at index {}", path, index))
     }
 }
 
@@ -126,9 +128,9 @@ fn bracket_path<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &
     let mut ch = peek(chars);
     match ch {
         Some(c) => {
-            if c.1 == '\'' {
+            if c.1 == '\'' || c.1 == '"' {
               chars.next();
-              string_path(chars, tokens, path, c.0)?
+              string_path(c.1, chars, tokens, path, c.0)?
             } else if c.1.is_numeric() {
               index_path(chars, tokens, path)?
             } else if c.1 == '*' {
@@ -159,13 +161,28 @@ fn bracket_path<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &
     }
 }
 
-// path_exp -> (dot-path | bracket-path)*
+// recursive_descent -> . (path_identifier | bracket-path)
+fn recursive_descent<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &String, index: usize) -> Result<(), String>
+    where I: Iterator<Item = (usize, char)>  {
+    chars.next();
+    tokens.push(PathToken::RecursiveDescent);
+    match chars.peek() {
+        Some(&(i, '[')) => {
+            chars.next();
+            bracket_path(chars, tokens, path, i)
+        },
+        _ => path_identifier(chars, tokens, path, index + 1)
+    }
+}
+
+// path_exp -> (dot-path | recursive_descent | bracket-path)*
 fn path_exp<I>(chars: &mut Peekable<I>, tokens: &mut Vec<PathToken>, path: &String) -> Result<(), String>
     where I: Iterator<Item = (usize, char)> {
     let mut next_char = chars.next();
     while next_char.is_some() {
         let ch = next_char.unwrap();
         match ch.1 {
+            '.' if peek(chars).map(|c| c.1) == Some('.') => recursive_descent(chars, tokens, path, ch.0)?,
             '.' => path_identifier(chars, tokens, path, ch.0)?,
             '[' => bracket_path(chars, tokens, path, ch.0)?,
             _ => return Err(format!("Expected a \".\" or \"[\" instead of \"{}\" in path expression \"{}\" at index {}",
@@ -268,6 +285,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_path_exp_handles_namespace_prefixes_in_brackets() {
+        expect!(parse_path_exp(s!("$['soap:Envelope']['soap:Body']"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::Field(s!("soap:Envelope")),
+                               PathToken::Field(s!("soap:Body"))])
+        );
+        expect!(parse_path_exp(s!("$.soap:Envelope"))).to(
+            be_err().value(s!("\":\" is not allowed in an identifier in path expression \"$.soap:Envelope\" at index 6")));
+    }
+
     #[test]
     fn parse_path_exp_with_star_instead_of_identifiers() {
         expect!(parse_path_exp(s!("$.*"))).to(
@@ -310,6 +337,35 @@ mod tests {
             be_err().value(s!("Expected a \".\" or \"[\" instead of \"b\" in path expression \"$[\'test\']b.c\" at index 9")));
     }
 
+    #[test]
+    fn parse_path_exp_with_escaped_strings() {
+        expect!(parse_path_exp(s!("$['it\\'s']"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::Field(s!("it's"))]));
+        expect!(parse_path_exp(s!("$[\"a.b\"]['c\\\\d']"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::Field(s!("a.b")), PathToken::Field(s!("c\\d"))]));
+        expect!(parse_path_exp(s!("$['abc\\"))).to(
+            be_err().value(s!("Unterminated string in path expression \"$['abc\\\" at index 6")));
+        expect!(parse_path_exp(s!("$[\"abc']"))).to(
+            be_err().value(s!("Unterminated string in path expression \"$[\"abc']\" at index 7")));
+    }
+
+    #[test]
+    fn parse_path_exp_with_recursive_descent() {
+        expect!(parse_path_exp(s!("$..id"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::RecursiveDescent, PathToken::Field(s!("id"))]));
+        expect!(parse_path_exp(s!("$.data..items[*]..id"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::Field(s!("data")), PathToken::RecursiveDescent,
+            PathToken::Field(s!("items")), PathToken::StarIndex, PathToken::RecursiveDescent, PathToken::Field(s!("id"))]));
+        expect!(parse_path_exp(s!("$..['a.b']"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::RecursiveDescent, PathToken::Field(s!("a.b"))]));
+        expect!(parse_path_exp(s!("$..*"))).to(
+            be_ok().value(vec![PathToken::Root, PathToken::RecursiveDescent, PathToken::Star]));
+        expect!(parse_path_exp(s!("$.."))).to(
+            be_err().value(s!("Expected a path after \".\" in path expression \"$..\" at index 2")));
+        expect!(parse_path_exp(s!("$...id"))).to(
+            be_err().value(s!("Expected either a \"*\" or path identifier in path expression \"$...id\" at index 3")));
+    }
+
     #[test]
     fn parse_path_exp_with_invalid_bracket_index_notation() {
         expect!(parse_path_exp(s!("$[dhghh]"))).to(
@@ -0,0 +1,190 @@
+//! The `plan` module provides matching plans for expected requests. A plan is compiled once, which
+//! parses the path expressions of the matching rules and checks their regular expressions, and
+//! can then be run against any number of actual requests. This is useful when the same expected
+//! request is compared many times, like the mock server does with the interactions of its pact.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use models::Request;
+use models::matchingrules::{MatchingRules, MatchingRule, CompiledCategory, RuleResolver, is_path_category};
+use matchers::compiled_regex;
+use super::{Mismatch, MatchingConfig, compare_requests};
+
+/// Compiled plan for matching actual requests against an expected request
+#[derive(Debug, Clone)]
+pub struct RequestMatchingPlan {
+    expected: Request,
+    /// Rules of the categories matched by path, with their path expressions parsed
+    categories: HashMap<String, CompiledCategory>
+}
+
+/// Resolves the matching rules of the expected request using the path expressions parsed by the plan
+struct PlanRules<'a> {
+    rules: &'a MatchingRules,
+    categories: &'a HashMap<String, CompiledCategory>
+}
+
+impl<'a> RuleResolver for PlanRules<'a> {
+    fn matching_rules(&self) -> &MatchingRules {
+        self.rules
+    }
+
+    fn compiled_category(&self, category: &str) -> Option<Cow<CompiledCategory>> {
+        self.categories.get(category).map(Cow::Borrowed)
+    }
+}
+
+impl RequestMatchingPlan {
+
+    /// Compiles the plan for the expected request. Regular expressions that are not valid are
+    /// logged here, and reported as mismatches when the plan is run.
+    pub fn compile(expected: &Request) -> RequestMatchingPlan {
+        let categories = expected.matching_rules.rules.iter()
+            .filter(|&(name, _)| is_path_category(name))
+            .map(|(name, category)| (name.clone(), CompiledCategory::new(category)))
+            .collect();
+        let regexes = expected.matching_rules.rules.values()
+            .flat_map(|category| category.rules.values())
+            .flat_map(|rulelist| rulelist.rules.iter())
+            .filter_map(|rule| match *rule {
+                MatchingRule::Regex(ref regex) => Some(regex),
+                _ => None
+            });
+        for regex in regexes {
+            if let Err(err) = compiled_regex(regex) {
+                warn!("'{}' is not a valid regular expression - {}", regex, err);
+            }
+        }
+        RequestMatchingPlan { expected: expected.clone(), categories }
+    }
+
+    /// The expected request of the plan
+    pub fn expected(&self) -> &Request {
+        &self.expected
+    }
+
+    fn rules(&self) -> PlanRules {
+        PlanRules { rules: &self.expected.matching_rules, categories: &self.categories }
+    }
+
+    /// Matches the actual request against the expected one. This returns the same mismatches as
+    /// `match_request`.
+    pub fn match_request(&self, actual: &Request) -> Vec<Mismatch> {
+        self.match_request_with_config(actual, &MatchingConfig::default())
+    }
+
+    /// Matches the actual request against the expected one, reporting no more mismatches than the
+    /// matching configuration allows. This returns the same mismatches as `match_request_with_config`.
+    pub fn match_request_with_config(&self, actual: &Request, matching_config: &MatchingConfig) -> Vec<Mismatch> {
+        compare_requests(&self.expected, actual, &self.rules(), matching_config)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use models::OptionalBody;
+    use models::matchingrules::RuleList;
+    use super::super::match_request;
+
+    fn request() -> Request {
+        Request {
+            method: s!("POST"),
+            path: s!("/orders/1234"),
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+            body: OptionalBody::Present(r#"{"items": [{"id": 1, "sku": "A-100"}, {"id": 2, "sku": "B-200"}]}"#.into(), None),
+            matching_rules: matchingrules!{
+                "path" => { "" => [ MatchingRule::Regex(s!("/orders/\\d+")) ] },
+                "body" => {
+                    "$.items" => [ MatchingRule::MinType(1) ],
+                    "$.items[*].id" => [ MatchingRule::Integer ],
+                    "$.items[*].sku" => [ MatchingRule::Regex(s!("[A-Z]-\\d{3}")) ]
+                }
+            },
+            .. Request::default()
+        }
+    }
+
+    #[test]
+    fn plan_resolves_the_same_matchers_as_the_matching_rules() {
+        let matchers = matchingrules!{
+            "body" => {
+                "$.a" => [ MatchingRule::Regex(s!("1")) ],
+                "$.a.*" => [ MatchingRule::Regex(s!("2")) ],
+                "$..b" => [ MatchingRule::Regex(s!("3")) ],
+                "$.a.b" => [ MatchingRule::Regex(s!("4")) ]
+            },
+            "header" => {
+                "X-Id" => [ MatchingRule::Integer ]
+            },
+            "path" => {
+                "" => [ MatchingRule::Regex(s!("/\\d+")) ]
+            }
+        };
+        let plan = RequestMatchingPlan::compile(&Request { matching_rules: matchers.clone(), .. request() });
+        expect!(plan.expected().matching_rules.clone()).to(be_equal_to(matchers.clone()));
+        let rules = plan.rules();
+
+        let paths = vec![vec![s!("$")], vec![s!("$"), s!("a")], vec![s!("$"), s!("a"), s!("b")],
+            vec![s!("$"), s!("a"), s!("c")], vec![s!("$"), s!("c"), s!("b")]];
+        for path in paths {
+            expect!(rules.matcher_is_defined("body", &path)).to(be_equal_to(matchers.matcher_is_defined("body", &path)));
+            expect!(rules.wildcard_matcher_is_defined("body", &path)).to(
+                be_equal_to(matchers.wildcard_matcher_is_defined("body", &path)));
+            expect!(rules.resolve_matchers("body", &path)).to(be_equal_to(matchers.resolve_matchers("body", &path)));
+            expect!(rules.resolve_body_matchers_with_weights(&path)).to(
+                be_equal_to(matchers.resolve_body_matchers_with_weights(&path)));
+            expect!(rules.resolve_body_matchers_for_exact_path(&path)).to(
+                be_equal_to(matchers.resolve_body_matchers_for_exact_path(&path)));
+        }
+        expect!(rules.resolve_body_matchers_by_path(&vec![s!("$"), s!("a"), s!("b")])).to(
+            be_some().value(RuleList::new(MatchingRule::Regex(s!("4")))));
+        expect!(rules.matcher_is_defined("header", &vec![s!("X-Id")])).to(be_true());
+        expect!(rules.matcher_is_defined("path", &vec![])).to(be_true());
+    }
+
+    #[test]
+    fn plan_returns_the_same_mismatches_as_match_request() {
+        let plan = RequestMatchingPlan::compile(&request());
+        let matching = Request {
+            path: s!("/orders/5678"),
+            body: OptionalBody::Present(r#"{"items": [{"id": 10, "sku": "C-300"}]}"#.into(), None),
+            .. request()
+        };
+        let mismatched = Request {
+            method: s!("PUT"),
+            path: s!("/orders/abc"),
+            body: OptionalBody::Present(r#"{"items": [{"id": "10", "sku": "c300"}, {"id": 11, "sku": "D-400"}]}"#.into(), None),
+            .. request()
+        };
+
+        expect!(plan.match_request(&matching).iter()).to(be_empty());
+        expect!(plan.match_request(&matching)).to(be_equal_to(match_request(request(), matching.clone())));
+        let mismatches = plan.match_request(&mismatched);
+        expect!(mismatches.len()).to(be_equal_to(4));
+        expect!(mismatches).to(be_equal_to(match_request(request(), mismatched)));
+    }
+
+    #[test]
+    fn plan_honours_the_matching_config() {
+        let plan = RequestMatchingPlan::compile(&request());
+        let actual = Request { method: s!("PUT"), path: s!("/orders/abc"), .. request() };
+        expect!(plan.match_request_with_config(&actual, &MatchingConfig::fail_fast())).to(be_equal_to(vec![
+            Mismatch::MethodMismatch { expected: s!("POST"), actual: s!("PUT") }
+        ]));
+    }
+
+    #[test]
+    fn compile_keeps_invalid_regular_expressions_as_mismatches() {
+        let expected = Request {
+            matching_rules: matchingrules!{ "path" => { "" => [ MatchingRule::Regex(s!("[a-")) ] } },
+            .. request()
+        };
+        let plan = RequestMatchingPlan::compile(&expected);
+        let mismatches = plan.match_request(&request());
+        expect!(mismatches.len()).to(be_equal_to(1));
+        expect!(mismatches[0].mismatch_type()).to(be_equal_to(s!("PathMismatch")));
+    }
+}
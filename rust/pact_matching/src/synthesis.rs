@@ -0,0 +1,332 @@
+//! The `synthesis` module provides functions to generate random example data for a request or
+//! response that still conforms to its matching rules. This is useful for fuzzing a provider, or
+//! for populating a demo environment with varied data.
+//!
+//! Values are generated from the body matching rules defined for each path:
+//!
+//! * `regex` - a random string matching the regular expression.
+//! * `date`, `time` and `timestamp` - a random date and time formatted with the pattern.
+//! * `type`, `min`, `max` - arrays are resized to a random length within the bounds, using the
+//!   example items as templates for the new items. Arrays without any example items are left as
+//!   they are, unless the rules require a minimum number of items, which is an error as there are
+//!   no items to use as templates. Other values are replaced with a random value of the same type.
+//! * `integer`, `decimal` and `number` - a random number of the same kind.
+//! * `include` - a random string containing the value.
+//!
+//! Values without a matching rule (or with an `equality` or `null` rule) are left as they are.
+//! Only JSON bodies are supported.
+
+use serde_json::{self, Value};
+use rand::prelude::*;
+use rand::distributions::Alphanumeric;
+use chrono::{Duration, Local};
+use nom::types::CompleteStr;
+use regex_syntax;
+use rand_regex;
+use models::{HttpPart, OptionalBody};
+use models::matchingrules::{MatchingRules, MatchingRule};
+use time_utils::{parse_pattern, to_chrono_pattern};
+
+/// Maximum number of items that will be added to an array with only a minimum size
+const ARRAY_SIZE_VARIANCE: usize = 3;
+
+/// Generates `count` random variants of the body of the request or response. Each variant
+/// conforms to the matching rules of the part. Parts without a body (or with an empty body) will
+/// return the body unchanged. Returns an error if the body is not JSON, can not be parsed, or has
+/// an empty array where the matching rules require a minimum number of items.
+pub fn synthesize_bodies(part: &dyn HttpPart, count: usize) -> Result<Vec<OptionalBody>, String> {
+    match part.body() {
+        &OptionalBody::Present(ref body, ref content_type) => {
+            let part_content_type = part.content_type();
+            if !part_content_type.starts_with("application/json") {
+                return Err(format!("Can not synthesize a body with content type '{}', only JSON bodies are supported",
+                    part_content_type));
+            }
+            let json: Value = serde_json::from_slice(body)
+                .map_err(|err| format!("Failed to parse the JSON body - {}", err))?;
+            (0..count).map(|_| {
+                synthesize_value(&vec![s!("$")], &json, part.matching_rules())
+                    .map(|value| OptionalBody::Present(value.to_string().into_bytes(), content_type.clone()))
+            }).collect()
+        },
+        body => Ok(vec![body.clone(); count])
+    }
+}
+
+/// Generates a random value from the example that conforms to the matching rules defined for
+/// the path (and any child paths). Returns an error if an array in the example has no items, but
+/// the matching rules require a minimum number of items.
+pub fn synthesize_value(path: &Vec<String>, example: &Value, matching_rules: &MatchingRules) -> Result<Value, String> {
+    let rules = matching_rules.resolve_body_matchers_for_exact_path(path)
+        .map(|rule_list| rule_list.rules)
+        .unwrap_or_default();
+    match example {
+        &Value::Object(ref map) => map.iter().map(|(key, value)| {
+            let mut p = path.clone();
+            p.push(key.clone());
+            synthesize_value(&p, value, matching_rules).map(|value| (key.clone(), value))
+        }).collect::<Result<_, String>>().map(Value::Object),
+        &Value::Array(ref items) if items.is_empty() => match min_array_size(&rules) {
+            0 => Ok(example.clone()),
+            min => Err(format!("Can not synthesize the array at {}, as the matching rules require at least {} item(s) \
+                but the example has no items to use as templates", path.join("."), min))
+        },
+        &Value::Array(ref items) => {
+            let size = array_size(items.len(), &rules);
+            (0..size).map(|index| {
+                let mut p = path.clone();
+                p.push(index.to_string());
+                synthesize_value(&p, &items[index % items.len()], matching_rules)
+            }).collect::<Result<_, String>>().map(Value::Array)
+        },
+        _ => Ok(rules.iter()
+            .filter_map(|rule| synthesize_from_rule(example, rule))
+            .next()
+            .unwrap_or_else(|| example.clone()))
+    }
+}
+
+/// The minimum number of items the first array size rule requires
+fn min_array_size(rules: &Vec<MatchingRule>) -> usize {
+    rules.iter().filter_map(|rule| match rule {
+        &MatchingRule::MinType(min) | &MatchingRule::MinMaxType(min, _) => Some(min),
+        &MatchingRule::MaxType(_) => Some(0),
+        _ => None
+    }).next().unwrap_or(0)
+}
+
+fn array_size(example_size: usize, rules: &Vec<MatchingRule>) -> usize {
+    let mut rnd = thread_rng();
+    rules.iter().filter_map(|rule| match rule {
+        &MatchingRule::MinType(min) => Some(rnd.gen_range(min, min + ARRAY_SIZE_VARIANCE + 1)),
+        &MatchingRule::MaxType(max) => Some(rnd.gen_range(max.min(1), max + 1)),
+        &MatchingRule::MinMaxType(min, max) => Some(rnd.gen_range(min, max.max(min) + 1)),
+        _ => None
+    }).next().unwrap_or(example_size)
+}
+
+fn synthesize_from_rule(example: &Value, rule: &MatchingRule) -> Option<Value> {
+    match rule {
+        &MatchingRule::Regex(ref regex) => random_string_from_regex(regex).map(|value| match example {
+            &Value::Number(_) | &Value::Bool(_) => serde_json::from_str(&value).unwrap_or(Value::String(value)),
+            _ => Value::String(value)
+        }),
+        &MatchingRule::Date(ref format) | &MatchingRule::Time(ref format) |
+        &MatchingRule::Timestamp(ref format) => random_datetime(format).map(Value::String),
+        &MatchingRule::Type | &MatchingRule::MinType(_) | &MatchingRule::MaxType(_) |
+        &MatchingRule::MinMaxType(_, _) => Some(random_value_of_same_type(example)),
+        &MatchingRule::Integer => Some(as_type_of(example, json!(thread_rng().gen_range(0, 10000)))),
+        &MatchingRule::Decimal => Some(as_type_of(example, random_decimal())),
+        &MatchingRule::Number => match example {
+            &Value::Number(ref n) if n.is_f64() => Some(random_decimal()),
+            _ => Some(as_type_of(example, json!(thread_rng().gen_range(0, 10000))))
+        },
+        &MatchingRule::Include(ref value) => Some(Value::String(format!("{}{}{}",
+            random_string(thread_rng().gen_range(0, 5)), value, random_string(thread_rng().gen_range(0, 5))))),
+        &MatchingRule::Equality | &MatchingRule::Null => None
+    }
+}
+
+fn random_string(size: usize) -> String {
+    thread_rng().sample_iter(&Alphanumeric).take(size).collect()
+}
+
+fn random_decimal() -> Value {
+    json!((thread_rng().gen_range(0.0, 10000.0) * 100.0_f64).round() / 100.0)
+}
+
+/// Generated strings always match the whole regular expression, so the `^` and `$` anchors are
+/// removed (rand_regex can not generate values for regular expressions with anchors)
+fn strip_anchors(regex: &str) -> &str {
+    let regex = if regex.starts_with('^') { &regex[1..] } else { regex };
+    if regex.ends_with('$') {
+        let without_anchor = &regex[..regex.len() - 1];
+        let backslashes = without_anchor.chars().rev().take_while(|ch| *ch == '\\').count();
+        if backslashes % 2 == 0 {
+            return without_anchor
+        }
+    }
+    regex
+}
+
+fn random_string_from_regex(regex: &str) -> Option<String> {
+    let mut parser = regex_syntax::ParserBuilder::new().unicode(false).build();
+    match parser.parse(strip_anchors(regex)) {
+        Ok(hir) => match rand_regex::Regex::with_hir(hir, 20) {
+            Ok(gen) => Some(thread_rng().sample(gen)),
+            Err(err) => {
+                warn!("Can not generate a value from the regular expression '{}' - {}", regex, err);
+                None
+            }
+        },
+        Err(err) => {
+            warn!("'{}' is not a valid regular expression - {}", regex, err);
+            None
+        }
+    }
+}
+
+/// Formats a random date and time within the last ten years using the pattern
+fn random_datetime(format: &str) -> Option<String> {
+    match parse_pattern(CompleteStr(format)) {
+        Ok(tokens) => {
+            let datetime = Local::now() - Duration::seconds(thread_rng().gen_range(0, 10 * 365 * 24 * 60 * 60));
+            Some(datetime.format(&to_chrono_pattern(&tokens.1)).to_string())
+        },
+        Err(err) => {
+            warn!("Date/time format {} is not valid - {:?}", format, err);
+            None
+        }
+    }
+}
+
+/// Converts the number to a string if the example is a string
+fn as_type_of(example: &Value, number: Value) -> Value {
+    match example {
+        &Value::String(_) => Value::String(number.to_string()),
+        _ => number
+    }
+}
+
+fn random_value_of_same_type(example: &Value) -> Value {
+    let mut rnd = thread_rng();
+    match example {
+        &Value::String(ref s) => Value::String(random_string(s.len().max(1))),
+        &Value::Number(ref n) => if let Some(i) = n.as_u64() {
+            json!(rnd.gen_range(0, i.saturating_mul(2).max(100)))
+        } else if let Some(i) = n.as_i64() {
+            json!(-rnd.gen_range(0, i.saturating_abs().saturating_mul(2).max(100)))
+        } else {
+            random_decimal()
+        },
+        &Value::Bool(_) => Value::Bool(rnd.gen()),
+        _ => example.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use models::{Request, Response};
+    use models::matchingrules::RuleLogic;
+    use time_utils::validate_datetime;
+    use match_response;
+    use onig::Regex;
+
+    fn parse_body(body: &OptionalBody) -> Value {
+        serde_json::from_slice(&body.value()).unwrap()
+    }
+
+    fn response() -> Response {
+        Response {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+            body: OptionalBody::Present(json!({
+                "id": 100,
+                "name": "Fred",
+                "code": "AB-1234",
+                "dob": "1980-05-20",
+                "active": true,
+                "price": 10.5,
+                "status": "ACTIVE",
+                "items": [ { "sku": "X-001", "quantity": 1 } ]
+            }).to_string().into_bytes(), None),
+            matching_rules: matchingrules!{
+                "body" => {
+                    "$.id" => [ MatchingRule::Integer ],
+                    "$.name" => [ MatchingRule::Type ],
+                    "$.code" => [ MatchingRule::Regex(s!("^[A-Z]{2}-\\d{4}$")) ],
+                    "$.dob" => [ MatchingRule::Date(s!("yyyy-MM-dd")) ],
+                    "$.active" => [ MatchingRule::Type ],
+                    "$.price" => [ MatchingRule::Decimal ],
+                    "$.items" => [ MatchingRule::MinMaxType(1, 5) ],
+                    "$.items[*].sku" => [ MatchingRule::Regex(s!("^X-\\d{3}$")) ]
+                }
+            },
+            .. Response::default()
+        }
+    }
+
+    #[test]
+    fn synthesizes_variants_that_match_the_rules() {
+        let response = response();
+        let variants = synthesize_bodies(&response, 20).unwrap();
+        expect!(variants.len()).to(be_equal_to(20));
+        let mut codes = vec![];
+        let mut skus = vec![];
+        for body in variants {
+            let variant = Response { body: body.clone(), .. response.clone() };
+            expect!(match_response(response.clone(), variant).iter()).to(be_empty());
+
+            let json = parse_body(&body);
+            expect!(json["status"].clone()).to(be_equal_to(json!("ACTIVE")));
+            expect!(Regex::new("^[A-Z]{2}-\\d{4}$").unwrap().is_match(json["code"].as_str().unwrap())).to(be_true());
+            expect!(validate_datetime(&json["dob"].as_str().unwrap().to_string(), &s!("yyyy-MM-dd"))).to(be_ok());
+            expect!(json["dob"].clone()).to_not(be_equal_to(json!("1980-05-20")));
+            let items = json["items"].as_array().unwrap();
+            expect!(items.len() >= 1 && items.len() <= 5).to(be_true());
+            for item in items {
+                expect!(Regex::new("^X-\\d{3}$").unwrap().is_match(item["sku"].as_str().unwrap())).to(be_true());
+                expect!(item["quantity"].clone()).to(be_equal_to(json!(1)));
+            }
+            codes.push(json["code"].clone());
+            skus.extend(items.iter().map(|item| item["sku"].clone()));
+        }
+        expect!(codes.iter().any(|code| code != &json!("AB-1234"))).to(be_true());
+        expect!(skus.iter().any(|sku| sku != &json!("X-001"))).to(be_true());
+    }
+
+    #[test]
+    fn strip_anchors_test() {
+        expect!(strip_anchors("^[A-Z]{2}-\\d{4}$")).to(be_equal_to("[A-Z]{2}-\\d{4}"));
+        expect!(strip_anchors("\\d+")).to(be_equal_to("\\d+"));
+        expect!(strip_anchors("^\\$\\d+\\$")).to(be_equal_to("\\$\\d+\\$"));
+        expect!(strip_anchors("\\\\$")).to(be_equal_to("\\\\"));
+        expect!(random_string_from_regex("^[A-Z]{2}-\\d{4}$")).to(be_some());
+    }
+
+    #[test]
+    fn array_size_uses_the_min_and_max_rules() {
+        for _ in 0..20 {
+            let size = array_size(1, &vec![MatchingRule::MinType(2)]);
+            expect!(size >= 2 && size <= 2 + ARRAY_SIZE_VARIANCE).to(be_true());
+            expect!(array_size(1, &vec![MatchingRule::MaxType(3)])).to(be_less_or_equal_to(3));
+            expect!(array_size(4, &vec![MatchingRule::Type])).to(be_equal_to(4));
+        }
+    }
+
+    #[test]
+    fn keeps_arrays_without_example_items() {
+        let rules = matchingrules!{ "body" => { "$.items" => [ MatchingRule::MaxType(2) ] } };
+        expect!(synthesize_value(&vec![s!("$")], &json!({ "items": [] }), &rules)).to(be_ok().value(json!({ "items": [] })));
+    }
+
+    #[test]
+    fn returns_an_error_for_arrays_without_example_items_that_require_a_minimum_size() {
+        let rules = matchingrules!{ "body" => { "$.items" => [ MatchingRule::MinType(2) ] } };
+        expect!(synthesize_value(&vec![s!("$")], &json!({ "items": [] }), &rules)).to(be_err());
+        let request = Request {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+            body: OptionalBody::Present("{\"items\": []}".into(), None),
+            matching_rules: matchingrules!{ "body" => { "$.items" => [ MatchingRule::MinMaxType(1, 3) ] } },
+            .. Request::default()
+        };
+        expect!(synthesize_bodies(&request, 2)).to(be_err());
+    }
+
+    #[test]
+    fn returns_bodies_without_content_unchanged() {
+        let request = Request::default();
+        expect!(synthesize_bodies(&request, 2)).to(be_ok().value(vec![OptionalBody::Missing, OptionalBody::Missing]));
+    }
+
+    #[test]
+    fn returns_an_error_for_bodies_that_are_not_json() {
+        let request = Request {
+            headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/plain")] }),
+            body: OptionalBody::Present("some text".into(), None),
+            .. Request::default()
+        };
+        expect!(synthesize_bodies(&request, 2)).to(be_err());
+    }
+}
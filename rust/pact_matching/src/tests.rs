@@ -345,7 +345,7 @@ fn body_does_not_match_if_different_content_types() {
     let mut mismatches = vec![];
     let expected = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
-        body: OptionalBody::Present(vec![]), .. Request::default() };
+        body: OptionalBody::Present(vec![], None), .. Request::default() };
     let actual = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("text/plain")] }),
         body: OptionalBody::Missing, .. Request::default() };
@@ -363,7 +363,7 @@ fn body_matches_if_expected_is_missing() {
         body: OptionalBody::Missing, .. Request::default() };
     let actual = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
-        body: OptionalBody::Present("{}".into()), .. Request::default() };
+        body: OptionalBody::Present("{}".into(), None), .. Request::default() };
     match_body(&expected, &actual, DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{});
     expect!(mismatches.iter()).to(be_empty());
 }
@@ -373,14 +373,34 @@ fn body_matches_with_extended_mime_types() {
     let mut mismatches = vec![];
     let expected = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/thrift+json")] }),
-        body: OptionalBody::Present(r#"{"test":true}"#.into()), .. Request::default() };
+        body: OptionalBody::Present(r#"{"test":true}"#.into(), None), .. Request::default() };
     let actual = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/thrift+json")] }),
-        body: OptionalBody::Present(r#"{"test": true}"#.into()), .. Request::default() };
+        body: OptionalBody::Present(r#"{"test": true}"#.into(), None), .. Request::default() };
     match_body(&expected, &actual, DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{});
     expect!(mismatches.iter()).to(be_empty());
 }
 
+#[test]
+fn json_body_with_a_graphql_query_attribute_is_compared_as_graphql() {
+    let request = |body: &str| Request { method: s!("POST"), path: s!("/"), query: None,
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+        body: OptionalBody::Present(body.into(), None), .. Request::default() };
+
+    let mut mismatches = vec![];
+    match_body(&request(r#"{"query": "{ user { name email } }", "page": 1}"#),
+        &request(r#"{"query": "{user{email,name}}", "page": 1}"#),
+        DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{});
+    expect!(mismatches.iter()).to(be_empty());
+
+    let mut mismatches = vec![];
+    match_body(&request(r#"{"query": "name:fred", "page": 1}"#), &request(r#"{"query": "name: fred", "page": 1}"#),
+        DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{});
+    expect!(mismatches.iter()).to(have_count(1));
+    expect!(mismatches[0].clone()).to(be_equal_to(Mismatch::BodyMismatch { path: s!("$.query"),
+        expected: Some("\"name:fred\"".into()), actual: Some("\"name: fred\"".into()), mismatch: s!(""), details: None }));
+}
+
 #[test]
 fn partial_equal_for_method_mismatch() {
     let mismatch = Mismatch::MethodMismatch { expected: s!("get"), actual: s!("post") };
@@ -396,7 +416,7 @@ fn partial_equal_for_method_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
@@ -414,7 +434,7 @@ fn partial_equal_for_path_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
@@ -432,7 +452,7 @@ fn partial_equal_for_status_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
@@ -450,7 +470,7 @@ fn partial_equal_for_body_type_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
@@ -472,7 +492,7 @@ fn partial_equal_for_query_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("")}));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
@@ -494,17 +514,17 @@ fn partial_equal_for_header_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("")}));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!("") }));
-    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+    expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), details: None }));
 }
 
 #[test]
 fn partial_equal_for_body_mismatch() {
-    let mismatch = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-    let mismatch2 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-    let mismatch3 = Mismatch::BodyMismatch { path: s!("key2"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-    let mismatch4 = Mismatch::BodyMismatch { path: s!("key"), expected: None, actual: Some("v2".into()), mismatch: s!("") };
-    let mismatch5 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: None, mismatch: s!("") };
-    let mismatch6 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("did not match") };
+    let mismatch = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), details: None };
+    let mismatch2 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), details: None };
+    let mismatch3 = Mismatch::BodyMismatch { path: s!("key2"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), details: None };
+    let mismatch4 = Mismatch::BodyMismatch { path: s!("key"), expected: None, actual: Some("v2".into()), mismatch: s!(""), details: None };
+    let mismatch5 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: None, mismatch: s!(""), details: None };
+    let mismatch6 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("did not match"), details: None };
     expect!(&mismatch).to(be_equal_to(&mismatch));
     expect!(&mismatch).to(be_equal_to(&mismatch2));
     expect!(&mismatch).to(be_equal_to(&mismatch6));
@@ -519,6 +539,48 @@ fn partial_equal_for_body_mismatch() {
     expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!("get"), actual: s!("post"), mismatch: s!("") }));
 }
 
+#[test]
+fn body_mismatch_to_json_includes_the_details() {
+    let mismatch = Mismatch::BodyMismatch { path: s!("$.items.0.id"), expected: Some("100".into()),
+        actual: Some("\"abc\"".into()), mismatch: s!("Expected 'abc' to be an integer value"),
+        details: Some(MismatchDetails::new(MismatchKind::RuleMismatch(MatchingRule::Integer),
+            &[s!("$"), s!("items"), s!("0"), s!("id")], json!(100), json!("abc"))) };
+    expect!(mismatch.to_json()["details"].clone()).to(be_equal_to(json!({
+        "kind": "RuleMismatch",
+        "rule": { "match": "integer" },
+        "pointer": "/items/0/id",
+        "expected": 100,
+        "actual": "abc"
+    })));
+
+    let mismatch = Mismatch::BodyMismatch { path: s!("/"), expected: Some("body".into()), actual: None,
+        mismatch: s!("Expected body 'body' but was missing"), details: None };
+    expect!(mismatch.to_json().get("details")).to(be_none());
+}
+
+#[test]
+fn ansi_description_includes_a_diff_of_multiline_text_bodies() {
+    let mut mismatches = vec![];
+    match_text(&"line 1\nline 2\nline 3".into(), &"line 1\nline two\nline 3".into(), &mut mismatches,
+        &MatchingRules::default());
+    expect!(mismatches.iter()).to(have_count(1));
+    let description = mismatches[0].ansi_description();
+    expect!(description.contains(&Red.paint("-line 2").to_string())).to(be_true());
+    expect!(description.contains(&Green.paint("+line two").to_string())).to(be_true());
+
+    let mismatch = Mismatch::BodyMismatch { path: s!("$.a"), expected: Some("1".into()), actual: Some("2".into()),
+        mismatch: s!("Expected '1' to be equal to '2'"), details: None };
+    expect!(mismatch.ansi_description().lines().count()).to(be_equal_to(1));
+}
+
+#[test]
+fn json_pointer_test() {
+    expect!(json_pointer(&[])).to(be_equal_to(s!("")));
+    expect!(json_pointer(&[s!("$")])).to(be_equal_to(s!("")));
+    expect!(json_pointer(&[s!("$"), s!("a"), s!("0")])).to(be_equal_to(s!("/a/0")));
+    expect!(json_pointer(&[s!("$"), s!("a/b"), s!("c~d")])).to(be_equal_to(s!("/a~1b/c~0d")));
+}
+
 #[test]
 #[ignore]
 fn strip_whitespace_quickcheck() {
@@ -647,7 +709,7 @@ fn matching_text_body_be_true_when_bodies_are_equal() {
   let mut mismatches = vec![];
   let expected: Vec<u8> = "body value".as_bytes().into();
   let actual: Vec<u8> = "body value".as_bytes().into();
-  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{});
+  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &MatchingConfig::default(), &mut mismatches, &matchingrules!{});
   expect!(mismatches.iter()).to(be_empty());
 }
 
@@ -656,11 +718,11 @@ fn matching_text_body_be_false_when_bodies_are_not_equal() {
   let mut mismatches = vec![];
   let expected: Vec<u8> = "expected body value".as_bytes().into();
   let actual: Vec<u8> = "actual body value".as_bytes().into();
-  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{});
+  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &MatchingConfig::default(), &mut mismatches, &matchingrules!{});
   expect!(mismatches.iter()).to_not(be_empty());
   assert_eq!(mismatches[0], Mismatch::BodyMismatch { path: s!("$"),
       expected: Some(expected), actual: Some(actual),
-      mismatch: s!("") });
+      mismatch: s!(""), details: None });
 }
 
 #[test]
@@ -668,17 +730,99 @@ fn matching_text_body_must_use_defined_matcher() {
   let mut mismatches = vec![];
   let expected: Vec<u8> = "expected body value".as_bytes().into();
   let actual: Vec<u8> = "actualbodyvalue".as_bytes().into();
-  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &MatchingConfig::default(), &mut mismatches, &matchingrules!{
     "body" => {
       "$" => [ MatchingRule::Regex(s!("\\w+")) ]
     }
   });
   expect!(mismatches.iter()).to(be_empty());
 
-  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+  compare_bodies(s!("text/plain"), &expected, &actual, DiffConfig::AllowUnexpectedKeys, &MatchingConfig::default(), &mut mismatches, &matchingrules!{
     "body" => {
       "$" => [ MatchingRule::Regex(s!("\\d+")) ]
     }
   });
   expect!(mismatches.iter()).to_not(be_empty());
 }
+
+fn json_request(method: &str, path: &str, body: &str) -> Request {
+  Request { method: s!(method), path: s!(path), query: None,
+    headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+    body: OptionalBody::Present(body.into(), None), .. Request::default() }
+}
+
+#[test]
+fn match_request_with_config_summarises_the_mismatches_past_the_limit() {
+  let expected = json_request("GET", "/", "[1, 2, 3, 4, 5]");
+  let actual = json_request("POST", "/", "[6, 7, 8, 9, 10]");
+  let mismatches = match_request_with_config(expected.clone(), actual.clone(),
+    &MatchingConfig::with_max_mismatches(3));
+  expect!(mismatches.len()).to(be_equal_to(4));
+  expect!(mismatches[0].mismatch_type()).to(be_equal_to(s!("MethodMismatch")));
+  expect!(mismatches[3].clone()).to(be_equal_to(Mismatch::TruncatedMismatches { limit: 3, count: 1 }));
+  expect!(match_request(expected, actual).len()).to(be_equal_to(6));
+}
+
+#[test]
+fn match_request_with_config_stops_at_the_first_mismatch_when_failing_fast() {
+  let expected = json_request("GET", "/", "[1, 2, 3, 4, 5]");
+  let actual = json_request("POST", "/other", "[6, 7, 8, 9, 10]");
+  let mismatches = match_request_with_config(expected, actual, &MatchingConfig::fail_fast());
+  expect!(mismatches).to(be_equal_to(vec![
+    Mismatch::MethodMismatch { expected: s!("GET"), actual: s!("POST") }
+  ]));
+}
+
+#[test]
+fn match_json_with_config_stops_comparing_once_the_limit_is_reached() {
+  let expected = serde_json::to_vec(&json!((0..1000).map(|_| "a").collect::<Vec<&str>>())).unwrap();
+  let actual = serde_json::to_vec(&json!((0..1000).collect::<Vec<usize>>())).unwrap();
+  let mut mismatches = vec![];
+  json::match_json_with_config(&expected, &actual, DiffConfig::AllowUnexpectedKeys,
+    &MatchingConfig::with_max_mismatches(10), &mut mismatches, &matchingrules!{});
+  expect!(mismatches.len()).to(be_equal_to(11));
+}
+
+#[test]
+fn compare_bodies_stops_comparing_every_body_type_once_the_limit_is_reached() {
+  let bodies = vec![
+    ("application/json", "[1, 2, 3]", "[4, 5, 6]"),
+    ("application/xml", "<a><b>1</b><b>2</b><b>3</b></a>", "<a><b>4</b><b>5</b><b>6</b></a>"),
+    ("application/yaml", "- 1\n- 2\n- 3", "- 4\n- 5\n- 6"),
+    ("text/csv", "a\n1\n2\n3", "a\n4\n5\n6"),
+    ("application/graphql", r#"{"query": "{ a }", "variables": {"x": 1, "y": 2, "z": 3}}"#,
+      r#"{"query": "{ a }", "variables": {"x": 4, "y": 5, "z": 6}}"#)
+  ];
+  for (content_type, expected, actual) in bodies {
+    let mut mismatches = vec![];
+    compare_bodies(s!(content_type), &expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys,
+      &MatchingConfig::default(), &mut mismatches, &matchingrules!{});
+    expect!(mismatches.len()).to(be_equal_to(3));
+
+    let mut mismatches = vec![];
+    compare_bodies(s!(content_type), &expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys,
+      &MatchingConfig::fail_fast(), &mut mismatches, &matchingrules!{});
+    expect!(mismatches.len()).to(be_equal_to(1));
+  }
+}
+
+#[test]
+fn matching_config_truncate_test() {
+  let mismatches = vec![
+    Mismatch::StatusMismatch { expected: 200, actual: 500 },
+    Mismatch::MethodMismatch { expected: s!("GET"), actual: s!("POST") },
+    Mismatch::PathMismatch { expected: s!("/"), actual: s!("/a"), mismatch: s!("") }
+  ];
+  expect!(MatchingConfig::default().truncate(mismatches.clone())).to(be_equal_to(mismatches.clone()));
+  expect!(MatchingConfig::with_max_mismatches(3).truncate(mismatches.clone())).to(be_equal_to(mismatches.clone()));
+  expect!(MatchingConfig::with_max_mismatches(1).truncate(mismatches.clone())).to(be_equal_to(vec![
+    Mismatch::StatusMismatch { expected: 200, actual: 500 },
+    Mismatch::TruncatedMismatches { limit: 1, count: 2 }
+  ]));
+  expect!(Mismatch::TruncatedMismatches { limit: 1, count: 2 }.to_json()).to(be_equal_to(json!({
+    "type": "TruncatedMismatches",
+    "limit": 1,
+    "count": 2,
+    "mismatch": "Only the first 1 mismatches were reported, at least 2 more were not"
+  })));
+}
@@ -0,0 +1,506 @@
+//! The `validation` module provides semantic validation (linting) of pact files. Where the strict
+//! parsing functions check that the pact file has the correct structure, these checks look for
+//! problems that will result in the pact not behaving as expected when it is verified, like regexes
+//! that do not match their own example values, or generators for values that do not exist.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use serde_json::{self, Value};
+use onig::Regex;
+use models::{Pact, Interaction, HttpPart, PactSpecification, DetectedContentType};
+use models::matchingrules::{MatchingRule, RuleList};
+use models::generators::GeneratorCategory;
+use models::parse_errors::{self, PactParseError, child_path};
+use path_exp::{parse_path_exp, PathToken};
+
+/// Severity of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informational finding, the pact will work as expected
+    Info,
+    /// Something that is likely to be a mistake, but the pact can still be verified
+    Warning,
+    /// The pact is invalid, or will not be able to be verified
+    Error
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> ::std::fmt::Result {
+        match *self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARN"),
+            Severity::Error => write!(f, "ERROR")
+        }
+    }
+}
+
+/// The check that resulted in a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationCheck {
+    /// The pact file could not be parsed
+    InvalidPact,
+    /// Two interactions have the same description and provider states
+    DuplicateInteraction,
+    /// A regex matching rule does not match the example value
+    RegexDoesNotMatchExample,
+    /// The example for a minimum type matcher has fewer items than the minimum
+    MinTypeNotSatisfied,
+    /// The example for a maximum type matcher has more items than the maximum
+    MaxTypeExceeded,
+    /// A generator has been defined for a value that does not exist
+    GeneratorPathNotFound,
+    /// The specification version of the pact file is unknown
+    UnknownSpecVersion,
+    /// A body is present, but there is no content type for it
+    MissingContentType
+}
+
+impl Display for ValidationCheck {
+    fn fmt(&self, f: &mut Formatter) -> ::std::fmt::Result {
+        let name = match *self {
+            ValidationCheck::InvalidPact => "invalid-pact",
+            ValidationCheck::DuplicateInteraction => "duplicate-interaction",
+            ValidationCheck::RegexDoesNotMatchExample => "regex-does-not-match-example",
+            ValidationCheck::MinTypeNotSatisfied => "min-type-not-satisfied",
+            ValidationCheck::MaxTypeExceeded => "max-type-exceeded",
+            ValidationCheck::GeneratorPathNotFound => "generator-path-not-found",
+            ValidationCheck::UnknownSpecVersion => "unknown-spec-version",
+            ValidationCheck::MissingContentType => "missing-content-type"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Finding from validating a pact file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationFinding {
+    /// Severity of the finding
+    pub severity: Severity,
+    /// The check that resulted in this finding
+    pub check: ValidationCheck,
+    /// JSON path in the pact file of the item the finding is for
+    pub path: String,
+    /// Description of the finding
+    pub message: String
+}
+
+impl ValidationFinding {
+    fn new<P: Into<String>, M: Into<String>>(severity: Severity, check: ValidationCheck, path: P, message: M) -> ValidationFinding {
+        ValidationFinding { severity, check, path: path.into(), message: message.into() }
+    }
+
+    /// Converts this finding to a `Value` struct
+    pub fn to_json(&self) -> Value {
+        json!({
+            s!("severity"): self.severity.to_string(),
+            s!("check"): self.check.to_string(),
+            s!("path"): self.path.clone(),
+            s!("message"): self.message.clone()
+        })
+    }
+}
+
+impl Display for ValidationFinding {
+    fn fmt(&self, f: &mut Formatter) -> ::std::fmt::Result {
+        write!(f, "{} [{}] {}: {}", self.severity, self.check, self.path, self.message)
+    }
+}
+
+impl <'a> From<&'a PactParseError> for ValidationFinding {
+    fn from(error: &'a PactParseError) -> ValidationFinding {
+        ValidationFinding::new(Severity::Error, ValidationCheck::InvalidPact, error.path.clone(),
+            match error.value {
+                Some(ref value) => format!("{} (found {})", error.problem, value),
+                None => error.problem.clone()
+            })
+    }
+}
+
+/// Validates the pact, returning all the findings
+pub fn validate_pact(pact: &Pact) -> Vec<ValidationFinding> {
+    let mut findings = vec![];
+
+    if pact.specification_version == PactSpecification::Unknown {
+        let version = pact.metadata.get("pactSpecification")
+            .or_else(|| pact.metadata.get("pact-specification"))
+            .and_then(|spec| spec.get("version"))
+            .cloned()
+            .unwrap_or_default();
+        findings.push(ValidationFinding::new(Severity::Warning, ValidationCheck::UnknownSpecVersion,
+            "$.metadata.pactSpecification.version",
+            format!("Specification version '{}' is not known, the pact will be treated as a V3 pact", version)));
+    }
+
+    for (index, interaction) in pact.interactions.iter().enumerate() {
+        let path = format!("$.interactions[{}]", index);
+        if let Some(other) = pact.interactions[..index].iter().position(|other| is_duplicate(interaction, other)) {
+            findings.push(ValidationFinding::new(Severity::Error, ValidationCheck::DuplicateInteraction, path.clone(),
+                format!("Interaction '{}' has the same description and provider states as $.interactions[{}]",
+                    interaction.description, other)));
+        }
+        validate_http_part(&child_path(&path, "request"), "request", &interaction.request, &mut findings);
+        validate_http_part(&child_path(&path, "response"), "response", &interaction.response, &mut findings);
+        validate_request_rules(&child_path(&path, "request"), interaction, &mut findings);
+    }
+
+    findings
+}
+
+/// Validates the pact JSON, returning all the findings. Any problems with the structure of the
+/// pact file are returned as errors, followed by the findings from validating the loaded pact.
+pub fn validate_pact_json(file: &String, json: &Value) -> Vec<ValidationFinding> {
+    let mut findings: Vec<ValidationFinding> = parse_errors::validate_pact(json).iter()
+        .map(ValidationFinding::from)
+        .collect();
+    if json.is_object() {
+        for finding in validate_pact(&Pact::from_json(file, json)) {
+            if !findings.iter().any(|f| f.path == finding.path) {
+                findings.push(finding);
+            }
+        }
+    }
+    findings
+}
+
+/// Reads the pact file and validates it, returning all the findings
+pub fn validate_pact_file(file: &Path) -> io::Result<Vec<ValidationFinding>> {
+    let mut f = File::open(file)?;
+    let json: Value = serde_json::from_reader(&mut f)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse Pact JSON - {}", err)))?;
+    Ok(validate_pact_json(&format!("{:?}", file), &json))
+}
+
+fn is_duplicate(interaction: &Interaction, other: &Interaction) -> bool {
+    interaction.description == other.description && interaction.provider_states == other.provider_states
+}
+
+fn validate_http_part<T: HttpPart>(path: &str, name: &str, part: &T, findings: &mut Vec<ValidationFinding>) {
+    if part.body().is_present() && !part.has_header(&s!("Content-Type")) && part.body().content_type().is_none() {
+        findings.push(ValidationFinding::new(Severity::Warning, ValidationCheck::MissingContentType,
+            child_path(path, "headers"),
+            format!("The {} has a body but no Content-Type header, the content type will be detected from the body ({})",
+                name, part.detect_content_type())));
+    }
+
+    let body = match part.content_type_enum() {
+        DetectedContentType::Json => serde_json::from_slice::<Value>(&part.body().value()).ok(),
+        _ => None
+    };
+
+    if let Some(category) = part.matching_rules().rules_for_category(&s!("body")) {
+        if let Some(ref body) = body {
+            for (key, rules) in &category.rules {
+                if let Ok(path_exp) = parse_path_exp(key.clone()) {
+                    let rule_path = child_path(&child_path(&child_path(path, "matchingRules"), "body"), key);
+                    let values = select_values(&path_exp, body);
+                    validate_rules(&rule_path, rules, &values, findings);
+                }
+            }
+        }
+    }
+
+    if let Some(category) = part.matching_rules().rules_for_category(&s!("header")) {
+        for (key, rules) in &category.rules {
+            let rule_path = child_path(&child_path(&child_path(path, "matchingRules"), "header"), key);
+            let values: Vec<(String, Value)> = part.headers().iter()
+                .flat_map(|headers| headers.iter())
+                .filter(|&(name, _)| name.to_lowercase() == key.to_lowercase())
+                .flat_map(|(name, values)| values.iter()
+                    .map(move |value| (child_path("$.headers", name), Value::String(value.clone()))))
+                .collect();
+            validate_rules(&rule_path, rules, &values, findings);
+        }
+    }
+
+    if let Some(generators) = part.generators().categories.get(&GeneratorCategory::BODY) {
+        if let Some(ref body) = body {
+            for key in generators.keys() {
+                let found = match parse_path_exp(key.clone()) {
+                    Ok(ref path_exp) => path_exp.len() == 1 || !select_values(path_exp, body).is_empty(),
+                    Err(_) => true
+                };
+                if !found {
+                    findings.push(ValidationFinding::new(Severity::Warning, ValidationCheck::GeneratorPathNotFound,
+                        child_path(&child_path(&child_path(path, "generators"), "body"), key),
+                        format!("There is no value in the {} body for the generator path '{}'", name, key)));
+                }
+            }
+        }
+    }
+
+    if let Some(generators) = part.generators().categories.get(&GeneratorCategory::HEADER) {
+        for key in generators.keys() {
+            if !part.has_header(key) {
+                findings.push(ValidationFinding::new(Severity::Warning, ValidationCheck::GeneratorPathNotFound,
+                    child_path(&child_path(&child_path(path, "generators"), "header"), key),
+                    format!("There is no '{}' header in the {} for the generator", key, name)));
+            }
+        }
+    }
+}
+
+fn validate_request_rules(path: &str, interaction: &Interaction, findings: &mut Vec<ValidationFinding>) {
+    let request = &interaction.request;
+    let query = request.query.clone().unwrap_or_default();
+
+    if let Some(category) = request.matching_rules.rules_for_category(&s!("query")) {
+        for (key, rules) in &category.rules {
+            let rule_path = child_path(&child_path(&child_path(path, "matchingRules"), "query"), key);
+            let values: Vec<(String, Value)> = query.get(key).iter()
+                .flat_map(|values| values.iter())
+                .map(|value| (child_path("$.query", key), Value::String(value.clone())))
+                .collect();
+            validate_rules(&rule_path, rules, &values, findings);
+        }
+    }
+
+    if let Some(category) = request.matching_rules.rules_for_category(&s!("path")) {
+        for rules in category.rules.values() {
+            let rule_path = child_path(&child_path(path, "matchingRules"), "path");
+            validate_rules(&rule_path, rules, &vec![(s!("$.path"), Value::String(request.path.clone()))], findings);
+        }
+    }
+
+    if let Some(generators) = request.generators.categories.get(&GeneratorCategory::QUERY) {
+        for key in generators.keys() {
+            if !query.contains_key(key) {
+                findings.push(ValidationFinding::new(Severity::Warning, ValidationCheck::GeneratorPathNotFound,
+                    child_path(&child_path(&child_path(path, "generators"), "query"), key),
+                    format!("There is no '{}' query parameter in the request for the generator", key)));
+            }
+        }
+    }
+}
+
+fn validate_rules(rule_path: &str, rules: &RuleList, values: &Vec<(String, Value)>, findings: &mut Vec<ValidationFinding>) {
+    for rule in &rules.rules {
+        for &(ref value_path, ref value) in values {
+            match *rule {
+                MatchingRule::Regex(ref regex) => if let Some(example) = primitive_value(value) {
+                    if let Ok(re) = Regex::new(regex) {
+                        if !re.is_match(&example) {
+                            findings.push(ValidationFinding::new(Severity::Error, ValidationCheck::RegexDoesNotMatchExample,
+                                rule_path, format!("Example value '{}' at {} does not match the regex '{}'", example, value_path, regex)));
+                        }
+                    }
+                },
+                MatchingRule::MinType(min) | MatchingRule::MinMaxType(min, _) => if let Value::Array(ref items) = *value {
+                    if items.len() < min {
+                        findings.push(ValidationFinding::new(Severity::Error, ValidationCheck::MinTypeNotSatisfied,
+                            rule_path, format!("Example at {} has {} item(s), but the minimum is {}", value_path, items.len(), min)));
+                    }
+                },
+                _ => ()
+            }
+            match *rule {
+                MatchingRule::MaxType(max) | MatchingRule::MinMaxType(_, max) => if let Value::Array(ref items) = *value {
+                    if items.len() > max {
+                        findings.push(ValidationFinding::new(Severity::Error, ValidationCheck::MaxTypeExceeded,
+                            rule_path, format!("Example at {} has {} item(s), but the maximum is {}", value_path, items.len(), max)));
+                    }
+                },
+                _ => ()
+            }
+        }
+    }
+}
+
+fn primitive_value(value: &Value) -> Option<String> {
+    match *value {
+        Value::String(ref s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None
+    }
+}
+
+/// Returns all the values (and their paths) in the JSON document that the path expression matches
+fn select_values(path_exp: &Vec<PathToken>, value: &Value) -> Vec<(String, Value)> {
+    let mut values = vec![];
+    select_values_from(&path_exp[..], s!("$"), value, &mut values);
+    values
+}
+
+fn select_values_from(tokens: &[PathToken], path: String, value: &Value, values: &mut Vec<(String, Value)>) {
+    match tokens.split_first() {
+        None => values.push((path, value.clone())),
+        Some((token, remaining)) => match *token {
+            PathToken::Root => select_values_from(remaining, path, value, values),
+            PathToken::Field(ref name) => if let Some(child) = value.get(name) {
+                select_values_from(remaining, child_path(&path, name), child, values);
+            },
+            PathToken::Index(index) => if let Some(child) = value.as_array().and_then(|items| items.get(index)) {
+                select_values_from(remaining, format!("{}[{}]", path, index), child, values);
+            },
+            PathToken::Star => if let Value::Object(ref map) = *value {
+                for (key, child) in map {
+                    select_values_from(remaining, child_path(&path, key), child, values);
+                }
+            },
+            PathToken::StarIndex => if let Value::Array(ref items) = *value {
+                for (index, child) in items.iter().enumerate() {
+                    select_values_from(remaining, format!("{}[{}]", path, index), child, values);
+                }
+            },
+            PathToken::RecursiveDescent => {
+                select_values_from(remaining, path.clone(), value, values);
+                match *value {
+                    Value::Object(ref map) => for (key, child) in map {
+                        select_values_from(tokens, child_path(&path, key), child, values);
+                    },
+                    Value::Array(ref items) => for (index, child) in items.iter().enumerate() {
+                        select_values_from(tokens, format!("{}[{}]", path, index), child, values);
+                    },
+                    _ => ()
+                }
+            }
+        }
+    }
+}
+
+/// Returns the number of findings for each severity
+pub fn count_by_severity(findings: &Vec<ValidationFinding>) -> HashMap<Severity, usize> {
+    findings.iter().fold(HashMap::new(), |mut counts, finding| {
+        *counts.entry(finding.severity).or_insert(0) += 1;
+        counts
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+
+    fn pact_json(interactions: Value) -> Value {
+        json!({
+            "consumer": { "name": "consumer" },
+            "provider": { "name": "provider" },
+            "interactions": interactions,
+            "metadata": { "pactSpecification": { "version": "3.0.0" } }
+        })
+    }
+
+    #[test]
+    fn validate_pact_with_no_problems() {
+        let json = pact_json(json!([{
+            "description": "get users",
+            "request": { "method": "GET", "path": "/users/100", "query": { "page": ["1"] },
+              "matchingRules": {
+                "path": { "matchers": [{ "match": "regex", "regex": "/users/\\d+" }] },
+                "query": { "page": { "matchers": [{ "match": "regex", "regex": "\\d+" }] } }
+              }
+            },
+            "response": {
+              "status": 200,
+              "headers": { "Content-Type": "application/json" },
+              "body": { "users": [{ "id": 100 }] },
+              "matchingRules": { "body": {
+                "$.users": { "matchers": [{ "match": "type", "min": 1 }] },
+                "$.users[*].id": { "matchers": [{ "match": "regex", "regex": "\\d+" }] }
+              } },
+              "generators": { "body": { "$.users[*].id": { "type": "RandomInt" } } }
+            }
+        }]));
+        expect!(validate_pact_json(&s!("pact.json"), &json).iter()).to(be_empty());
+    }
+
+    #[test]
+    fn validate_pact_finds_duplicate_interactions() {
+        let json = pact_json(json!([
+            { "description": "get users", "providerStates": [{ "name": "users exist" }], "request": { "path": "/" }, "response": {} },
+            { "description": "get users", "providerStates": [{ "name": "no users" }], "request": { "path": "/" }, "response": {} },
+            { "description": "get users", "providerStates": [{ "name": "users exist" }], "request": { "path": "/users" }, "response": {} }
+        ]));
+        expect!(validate_pact_json(&s!("pact.json"), &json)).to(be_equal_to(vec![
+            ValidationFinding::new(Severity::Error, ValidationCheck::DuplicateInteraction, "$.interactions[2]",
+                "Interaction 'get users' has the same description and provider states as $.interactions[0]")
+        ]));
+    }
+
+    #[test]
+    fn validate_pact_finds_examples_that_do_not_match_the_rules() {
+        let json = pact_json(json!([{
+            "description": "get users",
+            "request": { "path": "/users/abc", "headers": { "X-Id": "abc" }, "matchingRules": {
+              "path": { "matchers": [{ "match": "regex", "regex": "/users/\\d+" }] },
+              "header": { "x-id": { "matchers": [{ "match": "regex", "regex": "\\d+" }] } }
+            } },
+            "response": {
+              "headers": { "Content-Type": "application/json" },
+              "body": { "users": [{ "id": "100" }, { "id": "x" }], "tags": [] },
+              "matchingRules": { "body": {
+                "$.users": { "matchers": [{ "match": "type", "min": 1, "max": 1 }] },
+                "$.tags": { "matchers": [{ "match": "type", "min": 1 }] },
+                "$.users[*].id": { "matchers": [{ "match": "regex", "regex": "^\\d+$" }] }
+              } }
+            }
+        }]));
+        let findings = validate_pact_json(&s!("pact.json"), &json);
+        let mut messages = findings.iter().map(|f| f.to_string()).collect::<Vec<String>>();
+        messages.sort();
+        expect!(messages).to(be_equal_to(vec![
+            s!("ERROR [max-type-exceeded] $.interactions[0].response.matchingRules.body['$.users']: Example at $.users has 2 item(s), but the maximum is 1"),
+            s!("ERROR [min-type-not-satisfied] $.interactions[0].response.matchingRules.body['$.tags']: Example at $.tags has 0 item(s), but the minimum is 1"),
+            s!("ERROR [regex-does-not-match-example] $.interactions[0].request.matchingRules.header.x-id: Example value 'abc' at $.headers.X-Id does not match the regex '\\d+'"),
+            s!("ERROR [regex-does-not-match-example] $.interactions[0].request.matchingRules.path: Example value '/users/abc' at $.path does not match the regex '/users/\\d+'"),
+            s!("ERROR [regex-does-not-match-example] $.interactions[0].response.matchingRules.body['$.users[*].id']: Example value 'x' at $.users[1].id does not match the regex '^\\d+$'")
+        ]));
+    }
+
+    #[test]
+    fn validate_pact_finds_generators_for_missing_values() {
+        let json = pact_json(json!([{
+            "description": "create user",
+            "request": { "method": "POST", "path": "/users", "query": { "a": ["1"] },
+              "headers": { "Content-Type": "application/json" }, "body": { "name": "Fred" },
+              "generators": {
+                "query": { "b": { "type": "RandomInt" } },
+                "header": { "Content-Type": { "type": "Uuid" } },
+                "body": { "$.id": { "type": "Uuid" } }
+              }
+            },
+            "response": { "status": 201 }
+        }]));
+        let findings = validate_pact_json(&s!("pact.json"), &json);
+        let mut paths = findings.iter().map(|f| (f.check, f.path.clone())).collect::<Vec<(ValidationCheck, String)>>();
+        paths.sort_by(|a, b| a.1.cmp(&b.1));
+        expect!(paths).to(be_equal_to(vec![
+            (ValidationCheck::GeneratorPathNotFound, s!("$.interactions[0].request.generators.body['$.id']")),
+            (ValidationCheck::GeneratorPathNotFound, s!("$.interactions[0].request.generators.query.b"))
+        ]));
+    }
+
+    #[test]
+    fn validate_pact_finds_unknown_spec_versions_and_missing_content_types() {
+        let json = json!({
+            "consumer": { "name": "consumer" },
+            "provider": { "name": "provider" },
+            "interactions": [{
+              "description": "get users",
+              "request": { "path": "/" },
+              "response": { "body": "some text" }
+            }],
+            "metadata": { "pactSpecification": { "version": "4.0.0" } }
+        });
+        let pact = Pact::from_json(&s!("pact.json"), &json);
+        expect!(validate_pact(&pact)).to(be_equal_to(vec![
+            ValidationFinding::new(Severity::Warning, ValidationCheck::UnknownSpecVersion, "$.metadata.pactSpecification.version",
+                "Specification version '4.0.0' is not known, the pact will be treated as a V3 pact"),
+            ValidationFinding::new(Severity::Warning, ValidationCheck::MissingContentType, "$.interactions[0].response.headers",
+                "The response has a body but no Content-Type header, the content type will be detected from the body (text/plain)")
+        ]));
+    }
+
+    #[test]
+    fn validate_pact_json_includes_the_parse_errors() {
+        let json = pact_json(json!([{ "description": "get users", "request": { "path": "/" }, "response": { "status": "OK" } }]));
+        let findings = validate_pact_json(&s!("pact.json"), &json);
+        expect!(findings).to(be_equal_to(vec![
+            ValidationFinding::new(Severity::Error, ValidationCheck::InvalidPact, "$.interactions[0].response.status",
+                "Expected a HTTP status code between 100 and 599 (found \"OK\")")
+        ]));
+        expect!(count_by_severity(&findings).get(&Severity::Error)).to(be_some().value(&1));
+    }
+}
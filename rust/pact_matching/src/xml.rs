@@ -1,15 +1,25 @@
-use super::Mismatch;
-use super::DiffConfig;
+use super::{Mismatch, MismatchDetails, MismatchKind};
+use super::{DiffConfig, MatchingConfig};
 use sxd_document::dom::*;
+use sxd_document::QName;
 use std::collections::btree_map::BTreeMap;
 use itertools::Itertools;
 use models::matchingrules::*;
 use matchers::*;
-use onig::Regex;
 use models::xml_utils::parse_bytes;
+use serde_json::Value;
+use json::{parse_error_details, rule_mismatch_details};
 
+/// Matches the expected XML to the actual, and populates the mismatches vector with any differences
 pub fn match_xml(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
     mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    match_xml_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the expected XML to the actual, and populates the mismatches vector with any differences.
+/// Stops comparing the bodies once the mismatch limit of the matching configuration is reached.
+pub fn match_xml_with_config(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
     let expected_result = parse_bytes(expected);
     let actual_result = parse_bytes(actual);
 
@@ -18,7 +28,8 @@ pub fn match_xml(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
             Err(e) => {
                 mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
                     actual: Some(actual.clone().into()),
-                    mismatch: format!("Failed to parse the expected body: '{:?}'", e)});
+                    mismatch: format!("Failed to parse the expected body: '{:?}'", e),
+                    details: Some(parse_error_details(expected, actual))});
             },
             _ => ()
         }
@@ -26,7 +37,8 @@ pub fn match_xml(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
             Err(e) => {
                 mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
                     actual: Some(actual.clone().into()),
-                    mismatch: format!("Failed to parse the actual body: '{:?}'", e)});
+                    mismatch: format!("Failed to parse the actual body: '{:?}'", e),
+                    details: Some(parse_error_details(expected, actual))});
             },
             _ => ()
         }
@@ -38,7 +50,7 @@ pub fn match_xml(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
         let actual_root = actual_package.as_document().root();
         let actual_root_node = actual_root.children().iter().cloned().find(|n| n.element().is_some());
         compare_element(&vec![s!("$")], &expected_root_node.unwrap().element().unwrap(),
-            &actual_root_node.unwrap().element().unwrap(), config, mismatches, matchers);
+            &actual_root_node.unwrap().element().unwrap(), config, matching_config, mismatches, matchers);
     }
 }
 
@@ -46,12 +58,12 @@ impl<'a> Matches<Element<'a>> for Element<'a> {
     fn matches(&self, actual: &Element, matcher: &MatchingRule) -> Result<(), String> {
         let result = match *matcher {
           MatchingRule::Regex(ref regex) => {
-            match Regex::new(regex) {
+            match compiled_regex(regex) {
               Ok(re) => {
                 if re.is_match(actual.name().local_part()) {
                   Ok(())
                 } else {
-                  Err(format!("Expected '{}' to match '{}'", actual.name().local_part(), regex))
+                  Err(format!("Expected '{}' to match '{}'", element_name(actual), regex))
                 }
               },
               Err(err) => Err(format!("'{}' is not a valid regular expression - {}", regex, err))
@@ -60,23 +72,23 @@ impl<'a> Matches<Element<'a>> for Element<'a> {
           MatchingRule::Type => if self.name() == actual.name() {
              Ok(())
           } else {
-             Err(format!("Expected '{}' to be the same type as '{}'", self.name().local_part(),
-                  actual.name().local_part()))
+             let (expected_name, actual_name) = describe_names(self, actual);
+             Err(format!("Expected '{}' to be the same type as '{}'", expected_name, actual_name))
           },
           MatchingRule::MinType(min) => if actual.children().len() < min {
-             Err(format!("Expected '{}' to have at least {} children", actual.name().local_part(), min))
+             Err(format!("Expected '{}' to have at least {} children", element_name(actual), min))
           } else {
              Ok(())
           },
           MatchingRule::MaxType(max) => if actual.children().len() > max {
-             Err(format!("Expected '{}' to have at most {} children", actual.name().local_part(), max))
+             Err(format!("Expected '{}' to have at most {} children", element_name(actual), max))
           } else {
              Ok(())
           },
           MatchingRule::MinMaxType(min, max) => if actual.children().len() < min {
-            Err(format!("Expected '{}' to have at least {} children", actual.name().local_part(), min))
+            Err(format!("Expected '{}' to have at least {} children", element_name(actual), min))
           } else if actual.children().len() > max {
-            Err(format!("Expected '{}' to have at most {} children", actual.name().local_part(), max))
+            Err(format!("Expected '{}' to have at most {} children", element_name(actual), max))
           } else {
             Ok(())
           },
@@ -84,8 +96,8 @@ impl<'a> Matches<Element<'a>> for Element<'a> {
              if self.name() == actual.name() {
                  Ok(())
              } else {
-                  Err(format!("Expected '{}' to be equal to '{}'", self.name().local_part(),
-                      actual.name().local_part()))
+                 let (expected_name, actual_name) = describe_names(self, actual);
+                 Err(format!("Expected '{}' to be equal to '{}'", expected_name, actual_name))
              }
           },
           _ => Err(format!("Unable to match {:?} using {:?}", self, matcher))
@@ -95,9 +107,99 @@ impl<'a> Matches<Element<'a>> for Element<'a> {
     }
 }
 
+impl<'a> Matches<Vec<Element<'a>>> for Vec<Element<'a>> {
+    fn matches(&self, actual: &Vec<Element>, matcher: &MatchingRule) -> Result<(), String> {
+        let name = self.first().map(|element| element_name(element)).unwrap_or_default();
+        let result = match *matcher {
+          MatchingRule::Type => Ok(()),
+          MatchingRule::MinType(min) => if actual.len() < min {
+             Err(format!("Expected at least {} '{}' element(s) but received {}", min, name, actual.len()))
+          } else {
+             Ok(())
+          },
+          MatchingRule::MaxType(max) => if actual.len() > max {
+             Err(format!("Expected at most {} '{}' element(s) but received {}", max, name, actual.len()))
+          } else {
+             Ok(())
+          },
+          MatchingRule::MinMaxType(min, max) => if actual.len() < min {
+            Err(format!("Expected at least {} '{}' element(s) but received {}", min, name, actual.len()))
+          } else if actual.len() > max {
+            Err(format!("Expected at most {} '{}' element(s) but received {}", max, name, actual.len()))
+          } else {
+            Ok(())
+          },
+          // only the type rules constrain the list of siblings
+          _ => Ok(())
+        };
+        debug!("Comparing '{:?}' to '{:?}' using {:?} -> {:?}", self, actual, matcher, result);
+        result
+    }
+}
+
+/// Formats the name with the prefix it was declared with (i.e. `ns:name`)
+fn qualified_name(name: QName, prefix: Option<&str>) -> String {
+    match prefix {
+        Some(prefix) => format!("{}:{}", prefix, name.local_part()),
+        None => s!(name.local_part())
+    }
+}
+
+/// Formats the name using the namespace URI instead of the prefix (i.e. `{urn:ns}name`), which
+/// is how names are compared
+fn resolved_name(name: QName) -> String {
+    match name.namespace_uri() {
+        Some(uri) => format!("{{{}}}{}", uri, name.local_part()),
+        None => s!(name.local_part())
+    }
+}
+
+fn element_name(element: &Element) -> String {
+    qualified_name(element.name(), element.preferred_prefix())
+}
+
+fn attribute_name(attribute: &Attribute) -> String {
+    qualified_name(attribute.name(), attribute.preferred_prefix())
+}
+
+/// Names for mismatch messages. If the qualified names look the same but are bound to different
+/// namespaces, the namespace URIs are used so the difference is visible.
+fn describe_names(expected: &Element, actual: &Element) -> (String, String) {
+    let expected_name = element_name(expected);
+    let actual_name = element_name(actual);
+    if expected_name == actual_name {
+        (resolved_name(expected.name()), resolved_name(actual.name()))
+    } else {
+        (expected_name, actual_name)
+    }
+}
+
+/// Name of the actual element to use in a matching path. Namespaces are mapped back to the
+/// prefixes declared in the expected document, so rules written against the expected body apply
+/// regardless of the prefixes used by the actual one.
+fn element_path_name(expected: &Element, actual: &Element) -> String {
+    let name = actual.name();
+    let prefix = match name.namespace_uri() {
+        Some(uri) => if expected.name().namespace_uri() == Some(uri) {
+            expected.preferred_prefix()
+        } else if expected.recursive_default_namespace_uri() == Some(uri) {
+            None
+        } else {
+            expected.prefix_for_namespace_uri(uri, None).or(actual.preferred_prefix())
+        },
+        None => None
+    };
+    qualified_name(name, prefix)
+}
+
+/// Names with a namespace prefix are written in bracket notation (i.e. `$['ns:foo']['@ns:id']`), as
+/// `:` is not allowed in an identifier in a path expression. This keeps mismatch paths usable as
+/// matching rule paths.
 fn path_to_string(path: &Vec<String>) -> String {
     path.iter().enumerate().map(|(i, p)| {
-        if i > 0 && !p.starts_with("[") {
+        if i > 0 && p.contains(':') {
+            format!("['{}']", p)
+        } else if i > 0 && !p.starts_with("[") {
             s!(".") + p
         } else {
             p.clone()
@@ -106,132 +208,297 @@ fn path_to_string(path: &Vec<String>) -> String {
 }
 
 fn compare_element(path: &Vec<String>, expected: &Element, actual: &Element, config: DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    if matching_config.limit_reached(mismatches.len()) {
+        return;
+    }
     let matcher_result = if matchers.matcher_is_defined("body", &path) {
       debug!("calling match_values");
-      match_values("body", path, matchers.clone(), expected, actual)
+      match_values_with_rules("body", path, matchers, expected, actual)
     } else {
-      expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![err])
+      expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![(Some(MatchingRule::Equality), err)])
     };
     debug!("Comparing '{:?}' to '{:?}' at path '{}' -> {:?}", expected, actual, path_to_string(path), matcher_result);
     match matcher_result {
-        Err(messages) => {
-          for message in messages {
+        Err(failures) => {
+          for (rule, message) in failures {
             mismatches.push(Mismatch::BodyMismatch {
               path: path_to_string(path),
-              expected: Some(expected.name().local_part().into()),
-              actual: Some(actual.name().local_part().into()),
-              mismatch: message.clone()
+              expected: Some(element_name(expected).into()),
+              actual: Some(element_name(actual).into()),
+              mismatch: message.clone(),
+              details: Some(rule_mismatch_details(rule, path, &json!(element_name(expected)),
+                &json!(element_name(actual))))
             })
           }
         },
         Ok(_) => {
             let mut new_path = path.to_vec();
-            new_path.push(s!(actual.name().local_part()));
-            compare_attributes(&new_path, expected, actual, config.clone(), mismatches, matchers);
-            compare_children(&new_path, expected, actual, config.clone(), mismatches, matchers);
-            compare_text(&new_path, expected, actual, mismatches, matchers);
+            new_path.push(element_path_name(expected, actual));
+            compare_attributes(&new_path, expected, actual, config.clone(), matching_config, mismatches, matchers);
+            compare_children(&new_path, expected, actual, config.clone(), matching_config, mismatches, matchers);
+            if !matching_config.limit_reached(mismatches.len()) {
+                compare_text(&new_path, expected, actual, mismatches, matchers);
+            }
         }
     }
 }
 
+/// Attributes keyed by their namespace resolved name, with the qualified name and value
+fn attributes<'a>(element: &Element<'a>) -> BTreeMap<String, (Attribute<'a>, String)> {
+    element.attributes().iter()
+        .map(|attr| (resolved_name(attr.name()), (attr.clone(), s!(attr.value())))).collect()
+}
+
+fn desc_attributes(attributes: &BTreeMap<String, (Attribute, String)>) -> BTreeMap<String, String> {
+    attributes.values().map(|&(ref attr, ref value)| (attribute_name(attr), value.clone())).collect()
+}
+
 fn compare_attributes(path: &Vec<String>, expected: &Element, actual: &Element, config: DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
-    let expected_attributes: BTreeMap<String, String> = expected.attributes()
-        .iter().map(|attr| (s!(attr.name().local_part()), s!(attr.value()))).collect();
-    let actual_attributes: BTreeMap<String, String> = actual.attributes()
-        .iter().map(|attr| (s!(attr.name().local_part()), s!(attr.value()))).collect();
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let expected_attributes = attributes(expected);
+    let actual_attributes = attributes(actual);
+    let expected_desc = desc_attributes(&expected_attributes);
+    let actual_desc = desc_attributes(&actual_attributes);
     if expected_attributes.is_empty() && !actual_attributes.is_empty() && config == DiffConfig::NoUnexpectedKeys {
       mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
-          expected: Some(format!("{:?}", expected_attributes).into()),
-          actual: Some(format!("{:?}", actual_attributes).into()),
-          mismatch: format!("Did not expect any attributes but received {:?}", actual_attributes)});
+          expected: Some(format!("{:?}", expected_desc).into()),
+          actual: Some(format!("{:?}", actual_desc).into()),
+          mismatch: format!("Did not expect any attributes but received {:?}", actual_desc),
+          details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, path, json!(expected_desc),
+            json!(actual_desc)))});
     } else {
         match config {
             DiffConfig::AllowUnexpectedKeys if expected_attributes.len() > actual_attributes.len() => {
                 mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
-                    expected: Some(format!("{:?}", expected_attributes).into()),
-                    actual: Some(format!("{:?}", actual_attributes).into()),
+                    expected: Some(format!("{:?}", expected_desc).into()),
+                    actual: Some(format!("{:?}", actual_desc).into()),
                     mismatch: format!("Expected at least {} attribute(s) but received {} attribute(s)",
-                    expected_attributes.len(), actual_attributes.len())});
+                    expected_attributes.len(), actual_attributes.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path, json!(expected_desc),
+                      json!(actual_desc)))});
             },
             DiffConfig::NoUnexpectedKeys if expected_attributes.len() != actual_attributes.len() => {
                 mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
-                    expected: Some(format!("{:?}", expected_attributes).into()),
-                    actual: Some(format!("{:?}", actual_attributes).into()),
+                    expected: Some(format!("{:?}", expected_desc).into()),
+                    actual: Some(format!("{:?}", actual_desc).into()),
                     mismatch: format!("Expected {} attribute(s) but received {} attribute(s)",
-                    expected_attributes.len(), actual_attributes.len())});
+                    expected_attributes.len(), actual_attributes.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path, json!(expected_desc),
+                      json!(actual_desc)))});
             },
             _ => ()
         }
 
-        for (key, value) in expected_attributes.iter() {
-            if actual_attributes.contains_key(key) {
-                let mut p = path.to_vec();
-                p.push(s!("@") + key);
-                compare_value(&p, value, &actual_attributes[key], mismatches, matchers);
-            } else {
-                mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
-                    expected: Some(format!("{:?}", expected_attributes).into()),
-                    actual: Some(format!("{:?}", actual_attributes).into()),
-                    mismatch: format!("Expected attribute '{}'='{}' but was missing", key, value)});
+        for (key, &(ref attr, ref value)) in expected_attributes.iter() {
+            if matching_config.limit_reached(mismatches.len()) {
+                break;
+            }
+            match actual_attributes.get(key) {
+                Some(&(_, ref actual_value)) => {
+                    compare_value(&attribute_path(path, attr), value, actual_value, mismatches, matchers);
+                },
+                None => mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
+                    expected: Some(format!("{:?}", expected_desc).into()),
+                    actual: Some(format!("{:?}", actual_desc).into()),
+                    mismatch: format!("Expected attribute '{}'='{}' but was missing", attribute_name(attr), value),
+                    details: Some(MismatchDetails::new(MismatchKind::MissingValue,
+                      &attribute_path(path, attr), json!(value), Value::Null))})
             }
         }
     }
 }
 
+fn attribute_path(path: &Vec<String>, attribute: &Attribute) -> Vec<String> {
+    let mut p = path.to_vec();
+    p.push(s!("@") + &attribute_name(attribute));
+    p
+}
+
 fn children<'a>(element: &Element<'a>) -> Vec<ChildOfElement<'a>> {
     element.children().iter().cloned().filter(|child| child.element().is_some()).collect()
 }
 
+fn names<'a>(children: &Vec<ChildOfElement<'a>>) -> Vec<String> {
+    children.iter().map(|child| element_name(&child.element().unwrap())).collect()
+}
+
 fn desc_children<'a>(children: &Vec<ChildOfElement<'a>>) -> String {
-    children.iter().map(|child| child.element().unwrap().name().local_part()).join(", ")
+    names(children).join(", ")
+}
+
+/// Pretty-prints the XML document in a canonical form for display. Each element is on its own line
+/// indented by two spaces, with its attributes sorted by their namespace resolved name. Text is
+/// trimmed, and comments and processing instructions are dropped.
+pub(crate) fn canonicalize_xml(body: &Vec<u8>) -> Result<String, String> {
+    let package = parse_bytes(body)?;
+    let document = package.as_document();
+    let mut lines = vec![];
+    for child in document.root().children() {
+        if let Some(element) = child.element() {
+            canonical_element(&element, 0, &mut lines);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+fn canonical_element(element: &Element, depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let name = element_name(element);
+    let attributes = attributes(element).values()
+        .map(|&(ref attr, ref value)| format!(" {}=\"{}\"", attribute_name(attr), escape_xml(value)))
+        .collect::<String>();
+    if children(element).is_empty() {
+        let text = element.children().iter().filter_map(|child| child.text()).map(|text| s!(text.text()))
+            .collect::<String>();
+        if text.trim().is_empty() {
+            lines.push(format!("{}<{}{}/>", indent, name, attributes));
+        } else {
+            lines.push(format!("{}<{}{}>{}</{}>", indent, name, attributes, escape_xml(text.trim()), name));
+        }
+    } else {
+        lines.push(format!("{}<{}{}>", indent, name, attributes));
+        for child in element.children() {
+            match child {
+                ChildOfElement::Element(ref child) => canonical_element(child, depth + 1, lines),
+                ChildOfElement::Text(ref text) if !text.text().trim().is_empty() =>
+                    lines.push(format!("{}  {}", indent, escape_xml(text.text().trim()))),
+                _ => ()
+            }
+        }
+        lines.push(format!("{}</{}>", indent, name));
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// If there is a type rule defined on the path to a child element, the first expected child with
+/// that name is used as a template for all the actual siblings with the same name
+fn is_template_rule(rules: &RuleList) -> bool {
+    rules.rules.iter().any(|rule| match *rule {
+        MatchingRule::Type | MatchingRule::MinType(_) | MatchingRule::MaxType(_) |
+        MatchingRule::MinMaxType(_, _) => true,
+        _ => false
+    })
 }
 
 fn compare_children(path: &Vec<String>, expected: &Element, actual: &Element, config: DiffConfig,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
     let mut expected_children = children(expected);
-    let actual_children = children(actual);
+    let mut actual_children = children(actual);
+    // index of each expected child in the expected element, used for the matching path
+    let mut expected_indices = (0..expected_children.len()).collect::<Vec<usize>>();
     if matchers.matcher_is_defined("body", &path) {
         if !expected_children.is_empty() {
             let expected_example = expected_children[0].clone();
             expected_children.resize(actual_children.len(), expected_example);
+            expected_indices = (0..expected_children.len()).collect();
         }
     } else {
+        let templates = expected_children.iter()
+            .map(|child| child.element().unwrap())
+            .unique_by(|child| resolved_name(child.name()))
+            .filter(|child| {
+                let mut p = path.to_vec();
+                p.push(element_name(child));
+                matchers.resolve_body_matchers_for_exact_path(&p).map(|rules| is_template_rule(&rules))
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<Element>>();
+        for template in templates.iter() {
+            let siblings = actual_children.iter().enumerate()
+                .map(|(i, child)| (i, child.element().unwrap()))
+                .filter(|&(_, ref child)| child.name() == template.name())
+                .collect::<Vec<(usize, Element)>>();
+            compare_siblings(path, template, &siblings, config.clone(), matching_config, mismatches, matchers);
+        }
+        let is_templated = |child: &ChildOfElement| templates.iter().any(|template| template.name() == child.element().unwrap().name());
+        expected_indices.retain(|&i| !is_templated(&expected_children[i]));
+        expected_children.retain(|child| !is_templated(child));
+        actual_children.retain(|child| !is_templated(child));
+
         if expected_children.is_empty() && !actual_children.is_empty() && config == DiffConfig::NoUnexpectedKeys {
           mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
               expected: Some(desc_children(&expected_children).into()),
               actual: Some(desc_children(&actual_children).into()),
-              mismatch: format!("Expected an empty List but received [{}]", desc_children(&actual_children))});
+              mismatch: format!("Expected an empty List but received [{}]", desc_children(&actual_children)),
+              details: Some(MismatchDetails::new(MismatchKind::UnexpectedValue, path,
+                json!(names(&expected_children)), json!(names(&actual_children))))});
         } else if expected_children.len() != actual_children.len() {
             if config == DiffConfig::AllowUnexpectedKeys && expected_children.len() > actual_children.len() {
                 mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
                     expected: Some(desc_children(&expected_children).into()),
                     actual: Some(desc_children(&actual_children).into()),
                     mismatch: format!("Expected a List with at least {} element(s) but received {} element(s)",
-                        expected_children.len(), actual_children.len())});
+                        expected_children.len(), actual_children.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path,
+                      json!(names(&expected_children)), json!(names(&actual_children))))});
 
             } else if config == DiffConfig::NoUnexpectedKeys {
                 mismatches.push(Mismatch::BodyMismatch { path: path_to_string(path),
                     expected: Some(desc_children(&expected_children).into()),
                     actual: Some(desc_children(&actual_children).into()),
                     mismatch: format!("Expected a List with {} element(s) but received {} element(s)",
-                        expected_children.len(), actual_children.len())});
+                        expected_children.len(), actual_children.len()),
+                    details: Some(MismatchDetails::new(MismatchKind::LengthMismatch, path,
+                      json!(names(&expected_children)), json!(names(&actual_children))))});
             }
         }
     }
 
-    for ((i, exp), act) in expected_children.iter().enumerate().zip(actual_children.iter()) {
+    for ((i, exp), act) in expected_indices.iter().zip(expected_children.iter()).zip(actual_children.iter()) {
+        if matching_config.limit_reached(mismatches.len()) {
+            break;
+        }
         let expected = exp.element().unwrap();
         let mut p = path.to_vec();
         p.push(format!("{}", i));
         compare_element(&p, &expected, &act.element().unwrap(),
-            config.clone(), mismatches, matchers);
+            config.clone(), matching_config, mismatches, matchers);
+    }
+}
+
+/// Compares all the actual sibling elements against the template element. The number of siblings
+/// is checked against the rule defined for the template (i.e. `$.items.item`), and each sibling
+/// is compared at its index in the actual element.
+fn compare_siblings(path: &Vec<String>, template: &Element, siblings: &Vec<(usize, Element)>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let mut template_path = path.to_vec();
+    template_path.push(element_name(template));
+    let actual = siblings.iter().map(|&(_, ref child)| child.clone()).collect::<Vec<Element>>();
+    let matcher_result = match_values_with_rules("body", &template_path, matchers, &vec![template.clone()], &actual);
+    debug!("Comparing '{:?}' to '{:?}' at path '{}' -> {:?}", template, actual, path_to_string(&template_path), matcher_result);
+    match matcher_result {
+        Err(failures) => {
+          let actual_names = actual.iter().map(|child| element_name(child)).collect::<Vec<String>>();
+          for (rule, message) in failures {
+            mismatches.push(Mismatch::BodyMismatch {
+              path: path_to_string(&template_path),
+              expected: Some(element_name(template).into()),
+              actual: Some(actual_names.join(", ").into()),
+              mismatch: message.clone(),
+              details: Some(rule_mismatch_details(rule, &template_path, &json!([element_name(template)]),
+                &json!(actual_names)))
+            })
+          }
+        },
+        Ok(_) => ()
+    }
+
+    for &(i, ref sibling) in siblings {
+        if matching_config.limit_reached(mismatches.len()) {
+            break;
+        }
+        let mut p = path.to_vec();
+        p.push(format!("{}", i));
+        compare_element(&p, template, sibling, config.clone(), matching_config, mismatches, matchers);
     }
 }
 
 fn compare_text(path: &Vec<String>, expected: &Element, actual: &Element,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
     let expected_text = s!(expected.children().iter().cloned()
         .filter(|child| child.text().is_some())
         .map(|child| child.text().unwrap().text())
@@ -243,20 +510,21 @@ fn compare_text(path: &Vec<String>, expected: &Element, actual: &Element,
     let mut p = path.to_vec();
     p.push(s!("#text"));
     let matcher_result = if matchers.matcher_is_defined("body", &p) {
-      match_values("body", &p, matchers.clone(), &expected_text, &actual_text)
+      match_values_with_rules("body", &p, matchers, &expected_text, &actual_text)
     } else {
-      expected_text.matches(&actual_text, &MatchingRule::Equality).map_err(|err| vec![err])
+      expected_text.matches(&actual_text, &MatchingRule::Equality).map_err(|err| vec![(Some(MatchingRule::Equality), err)])
     };
     debug!("Comparing text '{}' to '{}' at path '{}' -> {:?}", expected_text, actual_text,
         path_to_string(path), matcher_result);
     match matcher_result {
-        Err(messages) => {
-          for message in messages {
+        Err(failures) => {
+          for (rule, message) in failures {
             mismatches.push(Mismatch::BodyMismatch {
               path: path_to_string(path) + ".#text",
               expected: Some(expected_text.clone().into()),
               actual: Some(actual_text.clone().into()),
-              mismatch: message.clone()
+              mismatch: message.clone(),
+              details: Some(rule_mismatch_details(rule, &p, &json!(expected_text), &json!(actual_text)))
             })
           }
         },
@@ -265,21 +533,22 @@ fn compare_text(path: &Vec<String>, expected: &Element, actual: &Element,
 }
 
 fn compare_value(path: &Vec<String>, expected: &String, actual: &String,
-    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
     let matcher_result = if matchers.matcher_is_defined("body", &path) {
-      match_values("body", path, matchers.clone(), expected, actual)
+      match_values_with_rules("body", path, matchers, expected, actual)
     } else {
-      expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![err])
+      expected.matches(actual, &MatchingRule::Equality).map_err(|err| vec![(Some(MatchingRule::Equality), err)])
     };
     debug!("Comparing '{}' to '{}' at path '{}' -> {:?}", expected, actual, path_to_string(path), matcher_result);
     match matcher_result {
-        Err(messages) => {
-          for message in messages {
+        Err(failures) => {
+          for (rule, message) in failures {
             mismatches.push(Mismatch::BodyMismatch {
               path: path_to_string(path),
               expected: Some(expected.clone().into()),
               actual: Some(actual.clone().into()),
-              mismatch: message.clone()
+              mismatch: message.clone(),
+              details: Some(rule_mismatch_details(rule, path, &json!(expected), &json!(actual)))
             })
           }
         },
@@ -304,7 +573,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(2));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(vec![]),
-            actual: Some(vec![]), mismatch: s!("")}));
+            actual: Some(vec![]), mismatch: s!(""), details: None}));
     }
 
     #[test]
@@ -316,7 +585,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("")}));
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
     }
 
     #[test]
@@ -328,12 +597,12 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
-            actual: Some(actual.into()), mismatch: s!("")}));
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
     }
 
     fn mismatch_message(mismatch: &Mismatch) -> String {
         match mismatch {
-            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m } => m.clone(),
+            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m, .. } => m.clone(),
             _ => s!("")
         }
     }
@@ -375,7 +644,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some("foo".into()),
-            actual: Some("bar".into()), mismatch: s!("")}));
+            actual: Some("bar".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'foo' to be equal to 'bar'")));
     }
 
@@ -406,15 +675,15 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.blah"),
             expected: Some("{\"a\": \"c\", \"c\": \"b\"}".into()),
-            actual: Some("{\"a\": \"b\"}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\": \"b\"}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected at least 2 attribute(s) but received 1 attribute(s)")));
         let mismatch = mismatches[1].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.blah.@a"), expected: Some("c".into()),
-            actual: Some("b".into()), mismatch: s!("")}));
+            actual: Some("b".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'c' to be equal to 'b'")));
         let mismatch = mismatches[2].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.blah"), expected: Some("{\"a\": \"c\", \"c\": \"b\"}".into()),
-            actual: Some("{\"a\": \"b\"}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\": \"b\"}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected attribute \'c\'=\'b\' but was missing")));
     }
 
@@ -431,7 +700,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.blah"), expected: Some("{}".into()),
-            actual: Some("{\"a\": \"b\", \"c\": \"d\"}".into()), mismatch: s!("")}));
+            actual: Some("{\"a\": \"b\", \"c\": \"d\"}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Did not expect any attributes but received {\"a\": \"b\", \"c\": \"d\"}")));
     }
 
@@ -461,7 +730,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some("{\"something\": \"100\"}".into()),
-            actual: Some("{\"something\": \"100\", \"somethingElse\": \"101\"}".into()), mismatch: s!("")}));
+            actual: Some("{\"something\": \"100\", \"somethingElse\": \"101\"}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 1 attribute(s) but received 2 attribute(s)")));
     }
 
@@ -478,7 +747,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some("{\"something\": \"100\", \"somethingElse\": \"100\"}".into()),
-            actual: Some("{\"something\": \"100\", \"somethingDifferent\": \"100\"}".into()), mismatch: s!("")}));
+            actual: Some("{\"something\": \"100\", \"somethingDifferent\": \"100\"}".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected attribute \'somethingElse\'=\'100\' but was missing")));
     }
 
@@ -495,7 +764,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.@somethingElse"), expected: Some("100".into()),
-            actual: Some("101".into()), mismatch: s!("")}));
+            actual: Some("101".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected \'100\' to be equal to \'101\'")));
 
         mismatches.clear();
@@ -520,7 +789,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some(vec![]),
-            actual: Some("bar".into()), mismatch: s!("")}));
+            actual: Some("bar".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected an empty List but received [bar]")));
     }
 
@@ -563,7 +832,7 @@ mod tests {
         expect!(mismatches.iter()).to(have_count(1));
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some("bar".into()),
-            actual: Some(vec![]), mismatch: s!("")}));
+            actual: Some(vec![]), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a List with 1 element(s) but received 0 element(s)")));
     }
 
@@ -581,7 +850,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"),
             expected: Some("one, two, three, four".into()),
-            actual: Some("one, two, three".into()), mismatch: s!("")}));
+            actual: Some("one, two, three".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected a List with 4 element(s) but received 3 element(s)")));
     }
 
@@ -601,7 +870,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.2"),
             expected: Some("three".into()),
-            actual: Some("four".into()), mismatch: s!("")}));
+            actual: Some("four".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'three' to be equal to 'four'")));
 
         mismatches.clear();
@@ -614,7 +883,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.2"),
             expected: Some("three".into()),
-            actual: Some("four".into()), mismatch: s!("")}));
+            actual: Some("four".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'three' to be the same type as 'four'")));
     }
 
@@ -632,12 +901,12 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.0"),
             expected: Some("one".into()),
-            actual: Some("two".into()), mismatch: s!("")}));
+            actual: Some("two".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'one' to be equal to 'two'")));
         let mismatch = mismatches[1].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.1"),
             expected: Some("two".into()),
-            actual: Some("one".into()), mismatch: s!("")}));
+            actual: Some("one".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'two' to be equal to 'one'")));
     }
 
@@ -682,7 +951,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.#text"),
             expected: Some("hello world".into()),
-            actual: Some("hello mars".into()), mismatch: s!("")}));
+            actual: Some("hello mars".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'hello world' to be equal to 'hello mars'")));
 
         mismatches.clear();
@@ -708,7 +977,7 @@ mod tests {
         let mismatch = mismatches[0].clone();
         expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.#text"),
             expected: Some("helloworld".into()),
-            actual: Some("hellomars".into()), mismatch: s!("")}));
+            actual: Some("hellomars".into()), mismatch: s!(""), details: None}));
         expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected 'helloworld' to be equal to 'hellomars'")));
 
         mismatches.clear();
@@ -737,4 +1006,148 @@ mod tests {
         expect!(mismatches.iter()).to(be_empty());
     }
 
+
+    #[test]
+    fn match_xml_with_the_same_namespace_bound_to_different_prefixes() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ns1:foo xmlns:ns1="urn:ns" ns1:id="1"><ns1:bar>baz</ns1:bar></ns1:foo>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <soap:foo xmlns:soap="urn:ns" soap:id="1"><soap:bar>baz</soap:bar></soap:foo>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <foo xmlns="urn:ns" xmlns:a="urn:ns" a:id="1"><bar>baz</bar></foo>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn match_xml_with_different_namespaces() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ns1:foo xmlns:ns1="urn:ns1"/>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ns1:foo xmlns:ns1="urn:ns2"/>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        let mismatch = mismatches[0].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some("ns1:foo".into()),
+            actual: Some("ns1:foo".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected '{urn:ns1}foo' to be equal to '{urn:ns2}foo'")));
+    }
+
+    #[test]
+    fn match_xml_with_namespaced_attributes() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <foo xmlns:a="urn:a" xmlns:b="urn:b" a:id="1" b:id="2"/>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <foo xmlns:x="urn:a" xmlns:y="urn:c" x:id="1" y:id="2"/>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        let mismatch = mismatches[0].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"),
+            expected: Some("{\"a:id\": \"1\", \"b:id\": \"2\"}".into()),
+            actual: Some("{\"x:id\": \"1\", \"y:id\": \"2\"}".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected attribute 'b:id'='2' but was missing")));
+    }
+
+    #[test]
+    fn match_xml_with_matchers_on_namespaced_paths() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ns:foo xmlns:ns="urn:ns" ns:id="100"><ns:bar>baz</ns:bar></ns:foo>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <soap:foo xmlns:soap="urn:ns" soap:id="101"><soap:bar>qux</soap:bar></soap:foo>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(2));
+        let mismatch = mismatches[0].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$['ns:foo']['@ns:id']"),
+            expected: Some("100".into()), actual: Some("101".into()), mismatch: s!(""), details: None}));
+        let mismatch = mismatches[1].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$['ns:foo'].0['ns:bar'].#text"),
+            expected: Some("baz".into()), actual: Some("qux".into()), mismatch: s!(""), details: None}));
+
+        mismatches.clear();
+        match_xml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$['ns:foo']['@ns:id']" => [ MatchingRule::Regex(s!("\\d+")) ],
+                "$['ns:foo'].*['ns:bar']['#text']" => [ MatchingRule::Type ]
+            }
+        });
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+
+    #[test]
+    fn match_xml_with_a_type_rule_on_repeated_elements() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items><count>2</count><item id="1"/></items>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items><item id="1"/><item id="1"/><count>2</count><item id="1"/></items>
+        "#;
+        let matchers = matchingrules!{
+            "body" => {
+                "$.items.item" => [ MatchingRule::MinType(1) ]
+            }
+        };
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchers);
+        expect!(mismatches.iter()).to(be_empty());
+
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items><item id="1"/><item id="2"/><count>2</count></items>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchers);
+        expect!(mismatches.iter()).to(have_count(1));
+        let mismatch = mismatches[0].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.items.1.item.@id"),
+            expected: Some("1".into()), actual: Some("2".into()), mismatch: s!(""), details: None}));
+    }
+
+    #[test]
+    fn match_xml_enforces_min_and_max_on_repeated_elements() {
+        let mut mismatches = vec![];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items><item/></items>
+        "#;
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items><item/><item/><item/></items>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$.items.item" => [ MatchingRule::MinMaxType(1, 2) ]
+            }
+        });
+        expect!(mismatches.iter()).to(have_count(1));
+        let mismatch = mismatches[0].clone();
+        expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.items.item"),
+            expected: Some("item".into()), actual: Some("item, item, item".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatch)).to(be_equal_to(s!("Expected at most 2 'item' element(s) but received 3")));
+
+        mismatches.clear();
+        let actual = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <items/>
+        "#;
+        match_xml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$.items.item" => [ MatchingRule::MinType(1) ]
+            }
+        });
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected at least 1 'item' element(s) but received 0")));
+    }
+
 }
@@ -0,0 +1,173 @@
+//! The `yaml` module provides functions to compare and display the differences between YAML bodies.
+//! YAML documents are parsed into the same tree as JSON documents, so they are compared using the
+//! JSON matching rules and path expressions.
+
+use serde_json::{self, Value};
+use serde_yaml;
+use super::Mismatch;
+use super::{DiffConfig, MatchingConfig};
+use difference::*;
+use ansi_term::Colour::*;
+use models::matchingrules::*;
+use json::{compare, walk_json, parse_error_details};
+
+/// Converts the JSON value to YAML, without the leading document marker
+fn to_yaml(value: &Value) -> String {
+    match serde_yaml::to_string(value) {
+        Ok(yaml) => s!(yaml.trim_start_matches("---").trim()),
+        Err(_) => value.to_string()
+    }
+}
+
+/// Converts the mismatch values (which the JSON comparison stores as JSON) to YAML
+fn yaml_value(value: Option<Vec<u8>>) -> Option<Vec<u8>> {
+    value.map(|bytes| match serde_json::from_slice::<Value>(&bytes) {
+        Ok(ref json) => to_yaml(json).into(),
+        Err(_) => bytes
+    })
+}
+
+/// Matches the expected YAML to the actual, and populates the mismatches vector with any differences
+pub fn match_yaml(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    mismatches: &mut Vec<super::Mismatch>, matchers: &MatchingRules) {
+    match_yaml_with_config(expected, actual, config, &MatchingConfig::default(), mismatches, matchers)
+}
+
+/// Matches the expected YAML to the actual, and populates the mismatches vector with any differences.
+/// Stops comparing the bodies once the mismatch limit of the matching configuration is reached.
+pub fn match_yaml_with_config(expected: &Vec<u8>, actual: &Vec<u8>, config: DiffConfig,
+    matching_config: &MatchingConfig, mismatches: &mut Vec<super::Mismatch>, matchers: &dyn RuleResolver) {
+    let expected_yaml = serde_yaml::from_slice::<Value>(expected);
+    let actual_yaml = serde_yaml::from_slice::<Value>(actual);
+
+    if expected_yaml.is_err() || actual_yaml.is_err() {
+        match expected_yaml {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
+                    actual: Some(actual.clone().into()),
+                    mismatch: format!("Failed to parse the expected body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+        match actual_yaml {
+            Err(e) => {
+                mismatches.push(Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.clone().into()),
+                    actual: Some(actual.clone().into()),
+                    mismatch: format!("Failed to parse the actual body: '{}'", e),
+                    details: Some(parse_error_details(expected, actual))});
+            },
+            _ => ()
+        }
+    } else {
+        // compare into the mismatches vector, so any mismatches already found count towards the limit
+        let start = mismatches.len();
+        compare(&vec![s!("$")], &expected_yaml.unwrap(), &actual_yaml.unwrap(), &config,
+            matching_config, mismatches, matchers);
+        for mismatch in mismatches[start..].iter_mut() {
+            if let Mismatch::BodyMismatch { ref mut expected, ref mut actual, .. } = *mismatch {
+                *expected = yaml_value(expected.take());
+                *actual = yaml_value(actual.take());
+            }
+        }
+    }
+}
+
+/// Returns a diff of the expected versus the actual YAML bodies, focusing on a particular path
+pub fn display_diff(expected: &String, actual: &String, path: &String) -> String {
+    let expected_body = serde_yaml::from_str::<Value>(expected).unwrap_or_default();
+    let actual_body = serde_yaml::from_str::<Value>(actual).unwrap_or_default();
+    let path = path.split('.').skip(2);
+    let expected_fragment = match walk_json(&expected_body, &mut path.clone()) {
+        Some(json) => to_yaml(&json),
+        None => s!("")
+    };
+    let actual_fragment = match walk_json(&actual_body, &mut path.clone()) {
+        Some(json) => to_yaml(&json),
+        None => s!("")
+    };
+    let changeset = Changeset::new(&expected_fragment, &actual_fragment, "\n");
+    let mut output = String::new();
+    for change in changeset.diffs {
+        match change {
+            Difference::Same(ref x) if x.is_empty() => (),
+            Difference::Same(ref x) => output.push_str(&format!(" {}\n", x)),
+            Difference::Add(ref x) => output.push_str(&Green.paint(format!("+{}\n", x)).to_string()),
+            Difference::Rem(ref x) => output.push_str(&Red.paint(format!("-{}\n", x)).to_string())
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expectest::prelude::*;
+    use Mismatch;
+    use DiffConfig;
+
+    fn mismatch_message(mismatch: &Mismatch) -> String {
+        match mismatch {
+            &Mismatch::BodyMismatch{ path: _, expected: _, actual: _, mismatch: ref m, .. } => m.clone(),
+            _ => s!("")
+        }
+    }
+
+    #[test]
+    fn match_yaml_handles_invalid_yaml() {
+        let mut mismatches = vec![];
+        let expected = "a: [1, 2";
+        let actual = "a: 1";
+        match_yaml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(1));
+        expect!(&mismatches[0]).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$"), expected: Some(expected.into()),
+            actual: Some(actual.into()), mismatch: s!(""), details: None}));
+    }
+
+    #[test]
+    fn match_yaml_with_equal_documents_in_different_styles() {
+        let mut mismatches = vec![];
+        let expected = "name: config\nitems:\n  - a\n  - b\n";
+        let actual = "{ items: [a, b], name: config }";
+        match_yaml(&expected.into(), &actual.into(), DiffConfig::NoUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn match_yaml_with_different_values() {
+        let mut mismatches = vec![];
+        let expected = "name: config\nsettings:\n  retries: 3\n  hosts: [a, b]\n";
+        let actual = "name: other\nsettings:\n  retries: 3\n  hosts: [a]\n";
+        match_yaml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &MatchingRules::default());
+        expect!(mismatches.iter()).to(have_count(3));
+        expect!(&mismatches[0]).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.name"),
+            expected: Some("config".into()), actual: Some("other".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatches[0])).to(be_equal_to(s!("Expected 'config' to be equal to 'other'")));
+        expect!(&mismatches[1]).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.settings.hosts"),
+            expected: Some("- a\n- b".into()), actual: Some("- a".into()), mismatch: s!(""), details: None}));
+        expect!(mismatch_message(&mismatches[2])).to(be_equal_to(s!("Expected a List with 2 elements but received 1 elements")));
+    }
+
+    #[test]
+    fn match_yaml_with_matching_rules() {
+        let mut mismatches = vec![];
+        let expected = "name: config\nitems:\n  - id: 1\n";
+        let actual = "name: prod\nitems:\n  - id: 10\n  - id: 20\n";
+        match_yaml(&expected.into(), &actual.into(), DiffConfig::AllowUnexpectedKeys, &mut mismatches, &matchingrules!{
+            "body" => {
+                "$.name" => [ MatchingRule::Regex(s!("[a-z]+")) ],
+                "$.items" => [ MatchingRule::MinType(1) ]
+            }
+        });
+        expect!(mismatches.iter()).to(be_empty());
+    }
+
+    #[test]
+    fn display_diff_shows_the_differences_in_yaml() {
+        let expected = s!("settings:\n  retries: 3\n  timeout: 10\n");
+        let actual = s!("settings:\n  retries: 5\n  timeout: 10\n");
+        let diff = display_diff(&expected, &actual, &s!("$.body.settings"));
+        expect!(diff).to(be_equal_to(format!("{}{} timeout: 10\n", Red.paint("-retries: 3\n"),
+          Green.paint("+retries: 5\n"))));
+    }
+}
@@ -1,6 +1,6 @@
-use matching::{MatchResult, match_request};
+use matching::{MatchResult, CompiledInteraction, compile_interactions, match_compiled_request};
 
-use pact_matching::models::{Pact, Request, OptionalBody};
+use pact_matching::models::{Pact, Request, OptionalBody, HttpPart};
 use pact_matching::models::matchingrules::*;
 use pact_matching::models::generators::*;
 use pact_matching::models::parse_query_string;
@@ -70,7 +70,7 @@ fn extract_headers(headers: &hyper::HeaderMap) -> Result<Option<HashMap<String,
 fn extract_body(chunk: hyper::Chunk) -> OptionalBody {
     let bytes = chunk.into_bytes();
     if bytes.len() > 0 {
-        OptionalBody::Present(bytes.to_vec())
+        OptionalBody::Present(bytes.to_vec(), None)
     } else {
         OptionalBody::Empty
     }
@@ -135,10 +135,28 @@ fn error_body(request: &Request, error: &String) -> String {
     body.to_string()
 }
 
-fn match_result_to_hyper_response(request: &Request, match_result: MatchResult) -> Result<Response<Body>, InteractionError> {
+/// Context used to generate the responses of the mock server. The details of the mock server are
+/// used by the `MockServerURL` generator, and the base URL can be used in provider state
+/// expressions as `${mockServer.href}`. The values from each request are combined with this
+/// context when the response for that request is generated, and can not override it.
+fn generation_context(addr: &std::net::SocketAddr) -> HashMap<String, serde_json::Value> {
+    hashmap!{
+        "mockServer".to_string() => json!({
+            "href": format!("http://localhost:{}", addr.port()),
+            "port": addr.port()
+        })
+    }
+}
+
+fn match_result_to_hyper_response(request: &Request, match_result: MatchResult,
+    context: &HashMap<String, serde_json::Value>) -> Result<Response<Body>, InteractionError> {
     match match_result {
         MatchResult::RequestMatch(ref interaction) => {
-            let response = pact_matching::generate_response(&interaction.response, &hashmap!{});
+            let mut context = context.clone();
+            for (key, value) in request_generation_context(interaction, request) {
+                context.entry(key).or_insert(value);
+            }
+            let response = pact_matching::generate_response(&interaction.response, &context);
             info!("Request matched, sending response {:?}", response);
             info!("     body: '{}'\n\n", interaction.response.body.str_value());
 
@@ -147,9 +165,14 @@ fn match_result_to_hyper_response(request: &Request, match_result: MatchResult)
             builder.status(response.status);
             builder.header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
             set_hyper_headers(&mut builder, &response.headers)?;
+            if !response.has_header(&"Content-Type".to_string()) {
+                if let Some(content_type) = response.body.content_type() {
+                    builder.header(hyper::header::CONTENT_TYPE, content_type.as_str());
+                }
+            }
 
             builder.body(match response.body {
-                OptionalBody::Present(ref s) => Body::from(s.clone()),
+                OptionalBody::Present(ref s, _) => Body::from(s.clone()),
                 _ => Body::empty()
             })
                 .map_err(|_| InteractionError::ResponseBodyError)
@@ -168,19 +191,21 @@ fn match_result_to_hyper_response(request: &Request, match_result: MatchResult)
 
 fn handle_request(
     req: hyper::Request<Body>,
-    pact: Arc<Pact>,
-    matches: Arc<Mutex<Vec<MatchResult>>>
+    interactions: Arc<Vec<CompiledInteraction>>,
+    matches: Arc<Mutex<Vec<MatchResult>>>,
+    context: Arc<Mutex<HashMap<String, serde_json::Value>>>
 ) -> impl Future<Item = Response<Body>, Error = InteractionError> {
     debug!("Creating pact request from hyper request");
 
     hyper_request_to_pact_request(req)
         .and_then(move |request| {
             info!("Received request {}", request);
-            let match_result = match_request(&request, &pact.interactions);
+            let match_result = match_compiled_request(&request, &interactions);
 
             matches.lock().unwrap().push(match_result.clone());
 
-            match_result_to_hyper_response(&request, match_result)
+            let context = context.lock().unwrap().clone();
+            match_result_to_hyper_response(&request, match_result, &context)
         })
 }
 
@@ -214,20 +239,26 @@ pub fn create_and_bind(
     shutdown: impl Future<Item = (), Error = ()>,
     matches: Arc<Mutex<Vec<MatchResult>>>,
 ) -> Result<(impl Future<Item = (), Error = ()>, std::net::SocketAddr), hyper::Error> {
-    let pact = Arc::new(pact);
+    let interactions = Arc::new(compile_interactions(&pact.interactions));
+    let context = Arc::new(Mutex::new(hashmap!{}));
+    let server_context = context.clone();
 
     let server = Server::try_bind(&addr)?
         .serve(move || {
-            let pact = pact.clone();
+            let interactions = interactions.clone();
             let matches = matches.clone();
+            let context = server_context.clone();
 
             service_fn(move |req| {
-                handle_request(req, pact.clone(), matches.clone())
+                handle_request(req, interactions.clone(), matches.clone(), context.clone())
                     .then(handle_mock_request_error)
             })
         });
 
     let socket_addr = server.local_addr();
+    // The port is only known once the server is bound, but no requests can be received before the
+    // server future is run
+    *context.lock().unwrap() = generation_context(&socket_addr);
 
     let prepared_server = server
         .with_graceful_shutdown(shutdown)
@@ -241,6 +272,7 @@ pub fn create_and_bind(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pact_matching::models::Interaction;
     use tokio::runtime::current_thread::Runtime;
 
     #[test]
@@ -262,4 +294,66 @@ mod tests {
         let all_matches = matches.lock().unwrap().clone();
         assert_eq!(all_matches, vec![]);
     }
+
+    #[test]
+    fn mock_server_url_generator_uses_the_address_of_the_mock_server() {
+        let interaction = Interaction {
+            response: pact_matching::models::Response {
+                headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+                body: OptionalBody::Present("{\"href\": \"http://provider.example.com/orders/1234\"}".into(), None),
+                generators: generators!{
+                    "BODY" => {
+                        "$.href" => Generator::MockServerURL("http://localhost:9000/orders/1".to_string(),
+                            ".*(/orders/\\d+)$".to_string())
+                    }
+                },
+                .. pact_matching::models::Response::default()
+            },
+            .. Interaction::default()
+        };
+        let context = generation_context(&([127, 0, 0, 1], 34567 as u16).into());
+
+        let response = match_result_to_hyper_response(&Request::default(),
+            MatchResult::RequestMatch(interaction), &context).unwrap();
+
+        let body = response.into_body().concat2().wait().unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "{\"href\":\"http://localhost:34567/orders/1234\"}");
+    }
+
+    #[test]
+    fn provider_state_generators_use_the_values_from_the_request() {
+        let interaction = Interaction {
+            request: Request {
+                path: "/orders/1".to_string(),
+                matching_rules: matchingrules!{
+                    "path" => { "" => [ MatchingRule::Regex("/orders/(?<orderId>\\d+)".to_string()) ] }
+                },
+                .. Request::default()
+            },
+            response: pact_matching::models::Response {
+                headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+                body: OptionalBody::Present("{\"id\": 1, \"href\": \"/orders/1\", \"requestId\": \"1\"}".into(), None),
+                generators: generators!{
+                    "BODY" => {
+                        "$.id" => Generator::ProviderStateGenerator("orderId".to_string()),
+                        "$.href" => Generator::ProviderStateGenerator("${mockServer.href}/orders/${orderId}".to_string()),
+                        "$.requestId" => Generator::ProviderStateGenerator("header.x-request-id".to_string())
+                    }
+                },
+                .. pact_matching::models::Response::default()
+            },
+            .. Interaction::default()
+        };
+        let request = Request {
+            path: "/orders/1234".to_string(),
+            headers: Some(hashmap!{ "x-request-id".to_string() => vec!["abc".to_string()] }),
+            .. Request::default()
+        };
+        let context = generation_context(&([127, 0, 0, 1], 34567).into());
+
+        let response = match_result_to_hyper_response(&request, MatchResult::RequestMatch(interaction), &context).unwrap();
+
+        let body = response.into_body().concat2().wait().unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "{\"href\":\"http://localhost:34567/orders/1234\",\"id\":1234,\"requestId\":\"abc\"}");
+    }
 }
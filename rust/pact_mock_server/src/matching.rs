@@ -5,6 +5,7 @@
 
 use pact_matching::models::{Interaction, Request, PactSpecification};
 use pact_matching::Mismatch;
+use pact_matching::plan::RequestMatchingPlan;
 use pact_matching::s;
 use serde_json::json;
 use itertools::Itertools;
@@ -77,17 +78,51 @@ fn method_or_path_mismatch(mismatches: &Vec<Mismatch>) -> bool {
         .any(|mismatch_type| mismatch_type == "MethodMismatch" || mismatch_type == "PathMismatch")
 }
 
+/// Interaction along with the compiled matching plan for its request
+#[derive(Debug, Clone)]
+pub struct CompiledInteraction {
+    /// The interaction
+    pub interaction: Interaction,
+    /// Matching plan for the request of the interaction
+    pub plan: RequestMatchingPlan
+}
+
+///
+/// Compiles the matching plans for the requests of the interactions, so they can be matched against
+/// many requests
+///
+pub fn compile_interactions(interactions: &Vec<Interaction>) -> Vec<CompiledInteraction> {
+    interactions.iter().map(|interaction| CompiledInteraction {
+        interaction: interaction.clone(),
+        plan: RequestMatchingPlan::compile(&interaction.request)
+    }).collect()
+}
+
 ///
 /// Matches a request against a list of interactions
 ///
 pub fn match_request(req: &Request, interactions: &Vec<Interaction>) -> MatchResult {
-    let match_results = interactions
-        .into_iter()
-        .map(|i| (i.clone(), pact_matching::match_request(i.request.clone(), req.clone())))
+    select_match_result(req, interactions.iter()
+        .map(|i| (i, pact_matching::match_request(i.request.clone(), req.clone()))))
+}
+
+///
+/// Matches a request against a list of interactions with compiled matching plans
+///
+pub fn match_compiled_request(req: &Request, interactions: &Vec<CompiledInteraction>) -> MatchResult {
+    select_match_result(req, interactions.iter()
+        .map(|i| (&i.interaction, i.plan.match_request(req))))
+}
+
+fn select_match_result<'a, I>(req: &Request, match_results: I) -> MatchResult
+    where I: Iterator<Item=(&'a Interaction, Vec<Mismatch>)> {
+    let match_results = match_results
         .sorted_by(|i1, i2| {
-            let list1 = i1.1.clone().into_iter().map(|m| m.mismatch_type()).unique().count();
-            let list2 = i2.1.clone().into_iter().map(|m| m.mismatch_type()).unique().count();
-            Ord::cmp(&list1, &list2)
+            let list1 = i1.1.iter().map(|m| m.mismatch_type()).unique().count();
+            let list2 = i2.1.iter().map(|m| m.mismatch_type()).unique().count();
+            // for interactions with the same types of mismatches (i.e. many GraphQL interactions on the
+            // same path), prefer the one with the fewest mismatches
+            Ord::cmp(&(list1, i1.1.len()), &(list2, i2.1.len()))
         });
     match match_results.first() {
         Some(res) => {
@@ -101,4 +136,4 @@ pub fn match_request(req: &Request, interactions: &Vec<Interaction>) -> MatchRes
         },
         None => MatchResult::RequestNotFound(req.clone())
     }
-}
\ No newline at end of file
+}
@@ -6,7 +6,7 @@
 use hyper_server;
 use matching::MatchResult;
 
-use pact_matching::models::{Pact, Interaction, PactSpecification};
+use pact_matching::models::{Pact, Interaction, PactSpecification, MergeStrategy};
 use std::ffi::CString;
 use std::path::PathBuf;
 use std::io;
@@ -29,6 +29,8 @@ pub struct MockServer {
     pub resources: Vec<CString>,
     /// Pact that this mock server is based on
     pub pact: Pact,
+    /// Strategy used to resolve conflicts when merging the pact into an existing pact file
+    pub merge_strategy: MergeStrategy,
     /// Receiver of match results
     matches: Arc<Mutex<Vec<MatchResult>>>,
     /// Shutdown signal
@@ -53,6 +55,7 @@ impl MockServer {
             addr: socket_addr,
             resources: vec![],
             pact: pact,
+            merge_strategy: MergeStrategy::default(),
             matches: matches,
             shutdown_tx: Some(shutdown_tx)
         };
@@ -108,7 +111,8 @@ impl MockServer {
         mismatches.chain(missing).collect()
     }
 
-    /// Mock server writes its pact out to the provided directory
+    /// Mock server writes its pact out to the provided directory. If a pact file already exists,
+    /// the pact is merged into it using the merge strategy of the mock server.
     pub fn write_pact(&self, output_path: &Option<String>) -> io::Result<()> {
         let pact_file_name = self.pact.default_file_name();
         let filename = match *output_path {
@@ -122,12 +126,12 @@ impl MockServer {
 
         info!("Writing pact out to '{}'", filename.display());
 
-        // Lock so that no two threads can read/write pact file at the same time.
-        // TODO: Could use a fs-based lock in case multiple processes are doing
-        // this concurrently?
+        // Lock so that no two threads can read/write pact file at the same time. Writing the pact
+        // file also takes a file lock, for the case where multiple processes are doing this
+        // concurrently.
         let _file_lock = PACT_FILE_MUTEX.lock().unwrap();
 
-        match self.pact.write_pact(filename.as_path(), PactSpecification::V3) {
+        match self.pact.write_pact_with_strategy(filename.as_path(), PactSpecification::V3, self.merge_strategy) {
             Ok(_) => Ok(()),
             Err(err) => {
                 warn!("Failed to write pact to file - {}", err);
@@ -151,6 +155,7 @@ impl Clone for MockServer {
             addr: self.addr,
             resources: vec![],
             pact: self.pact.clone(),
+            merge_strategy: self.merge_strategy,
             matches: self.matches.clone(),
             shutdown_tx: None
         }
@@ -1,9 +1,10 @@
 use expectest::prelude::*;
 use super::*;
-use matching::{MatchResult, match_request};
+use matching::{MatchResult, match_request, compile_interactions, match_compiled_request};
 use pact_matching::models::{Interaction, Request, OptionalBody};
 use pact_matching::Mismatch;
 use pact_matching::models::matchingrules::*;
+use serde_json::json;
 
 #[test]
 fn match_request_returns_a_match_for_identical_requests() {
@@ -65,11 +66,11 @@ fn match_request_returns_request_not_found_if_method_or_path_do_not_match() {
 
 #[test]
 fn match_request_returns_the_most_appropriate_mismatch_for_multiple_requests() {
-    let request = Request { method: s!("GET"), path: s!("/"), body: OptionalBody::Present("This is a body".into()),
+    let request = Request { method: s!("GET"), path: s!("/"), body: OptionalBody::Present("This is a body".into(), None),
       .. Request::default() };
     let request2 = Request { method: s!("GET"), path: s!("/"), query: Some(hashmap!{
         s!("QueryA") => vec![s!("Value A")]
-        }), body: OptionalBody::Present("This is a body".into()),
+        }), body: OptionalBody::Present("This is a body".into(), None),
       .. Request::default() };
     let request3 = Request { method: s!("GET"), path: s!("/"), query: Some(hashmap!{
         s!("QueryA") => vec![s!("Value A")]
@@ -80,7 +81,7 @@ fn match_request_returns_the_most_appropriate_mismatch_for_multiple_requests() {
     let result = match_request(&request3, &interactions);
     expect!(result).to(be_equal_to(MatchResult::RequestMismatch(interaction2,
         vec![Mismatch::BodyMismatch { path: s!("/"), expected: Some("This is a body".into()), actual: None,
-        mismatch: s!("Expected body \'This is a body\' but was missing") }])));
+        mismatch: s!("Expected body \'This is a body\' but was missing"), details: None }])));
 }
 
 #[test]
@@ -92,7 +93,7 @@ fn match_request_supports_v2_matchers() {
                 "a": 100,
                 "b": "one hundred"
             }
-            "#.into()
+            "#.into(), None
         ), .. Request::default() };
     let expected_request = Request { method: s!("GET"), path: s!("/"),
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
@@ -102,7 +103,7 @@ fn match_request_supports_v2_matchers() {
                 "a": 1000,
                 "b": "One Thousand"
             }
-            "#.into()
+            "#.into(), None
         ), matching_rules: matchingrules!{
           "body" => {
             "$.*" => [ MatchingRule::Type ]
@@ -121,14 +122,14 @@ fn match_request_supports_v2_matchers_with_xml() {
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/xml")] }), body: OptionalBody::Present(
             r#"<?xml version="1.0" encoding="UTF-8"?>
             <foo>hello<bar/>world</foo>
-            "#.into()
+            "#.into(), None
         ), .. Request::default() };
     let expected_request = Request { method: s!("GET"), path: s!("/"), query: None,
         headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/xml")] }),
         body: OptionalBody::Present(
             r#"<?xml version="1.0" encoding="UTF-8"?>
             <foo>hello<bar/>mars </foo>
-            "#.into()
+            "#.into(), None
         ), matching_rules: matchingrules!{
           "body" => {
             "$.foo['#text']" => [ MatchingRule::Regex(s!("[a-z]+")) ]
@@ -140,3 +141,74 @@ fn match_request_supports_v2_matchers_with_xml() {
     let result = match_request(&request, &vec![interaction.clone()]);
     expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction)));
 }
+
+#[test]
+fn match_request_selects_the_graphql_interaction_with_the_same_operation() {
+    let graphql_request = |body: &str| Request { method: s!("POST"), path: s!("/graphql"), query: None,
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+        body: OptionalBody::Present(body.into(), None), .. Request::default() };
+    let interaction = Interaction { description: s!("get user"), request: graphql_request(
+        r#"{"query": "query GetUser($id: ID!) { user(id: $id) { name email } }", "operationName": "GetUser", "variables": {"id": "100"}}"#
+      ), .. Interaction::default() };
+    let interaction2 = Interaction { description: s!("get orders"), request: graphql_request(
+        r#"{"query": "query GetOrders { orders { id total } }", "operationName": "GetOrders"}"#
+      ), .. Interaction::default() };
+    let interactions = vec![interaction.clone(), interaction2.clone()];
+
+    let request = graphql_request(r#"{"operationName": "GetOrders", "query": "query GetOrders {\n  orders {\n    total\n    id\n  }\n}\n"}"#);
+    expect!(match_request(&request, &interactions)).to(be_equal_to(MatchResult::RequestMatch(interaction2)));
+
+    let request = graphql_request(r#"{"operationName": "GetUser", "variables": {"id": "200"}, "query": "query GetUser($id: ID!) { user(id: $id) { email name } }"}"#);
+    let result = match_request(&request, &interactions);
+    expect!(result.match_key()).to(be_equal_to(s!("Request-Mismatch")));
+    match result {
+        MatchResult::RequestMismatch(ref mismatched, _) => expect!(mismatched.description.clone()).to(be_equal_to(s!("get user"))),
+        _ => ()
+    }
+}
+
+#[test]
+fn request_mismatch_json_includes_the_details_of_body_mismatches() {
+    let request = Request { method: s!("POST"), path: s!("/orders"),
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+        body: OptionalBody::Present(r#"{"id": "abc", "items": []}"#.into(), None), .. Request::default() };
+    let expected_request = Request { method: s!("POST"), path: s!("/orders"),
+        headers: Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] }),
+        body: OptionalBody::Present(r#"{"id": 100, "items": []}"#.into(), None),
+        matching_rules: matchingrules!{
+          "body" => {
+            "$.id" => [ MatchingRule::Integer ]
+          }
+        },
+      .. Request::default()
+    };
+    let interaction = Interaction { request: expected_request, .. Interaction::default() };
+    let result = match_request(&request, &vec![interaction]);
+    let json = result.to_json();
+    expect!(json["mismatches"][0]["details"].clone()).to(be_equal_to(json!({
+        "kind": "RuleMismatch",
+        "rule": { "match": "integer" },
+        "pointer": "/id",
+        "expected": 100,
+        "actual": "abc"
+    })));
+}
+
+#[test]
+fn match_compiled_request_can_match_many_requests_with_the_same_plans() {
+    let request1 = Request { method: s!("GET"), path: s!("/orders/1"), .. Request::default() };
+    let request2 = Request { method: s!("POST"), path: s!("/orders"), .. Request::default() };
+    let interaction1 = Interaction { description: s!("test1"), request: Request {
+        matching_rules: matchingrules!{ "path" => { "" => [ MatchingRule::Regex(s!("/orders/\\d+")) ] } },
+        .. request1.clone()
+    }, .. Interaction::default() };
+    let interaction2 = Interaction { description: s!("test2"), request: request2.clone(), .. Interaction::default() };
+    let interactions = compile_interactions(&vec![interaction1.clone(), interaction2.clone()]);
+
+    expect!(match_compiled_request(&request1, &interactions)).to(be_equal_to(MatchResult::RequestMatch(interaction1.clone())));
+    expect!(match_compiled_request(&request2, &interactions)).to(be_equal_to(MatchResult::RequestMatch(interaction2)));
+    let request3 = Request { path: s!("/orders/2"), .. request1.clone() };
+    expect!(match_compiled_request(&request3, &interactions)).to(be_equal_to(MatchResult::RequestMatch(interaction1)));
+    let request4 = Request { method: s!("DELETE"), .. request1 };
+    expect!(match_compiled_request(&request4, &interactions)).to(be_equal_to(MatchResult::RequestNotFound(request4.clone())));
+}
@@ -3,55 +3,98 @@ use hyper::Client;
 use hyper::Url;
 use hyper::header::ContentType;
 use hyper::mime::{Mime, TopLevel, SubLevel, Attr, Value};
+use std::io;
 use std::io::prelude::*;
 use std::path::Path;
 use serde_json;
 use pact_matching::models::Pact;
+use pact_matching::models::archive;
 
-pub fn create_mock_server(host: &str, port: u16, matches: &ArgMatches) -> Result<(), i32> {
-    let file = matches.value_of("file").unwrap();
-    info!("Creating mock server from file {}", file);
+fn load_pacts(matches: &ArgMatches) -> Result<Vec<Pact>, String> {
+    let strict = matches.is_present("strict");
+    if matches.is_present("stdin") {
+        info!("Creating mock server from standard input");
+        let pact = if strict {
+            Pact::read_pact_from_reader_strict("<stdin>", io::stdin())
+        } else {
+            Pact::read_pact_from_reader("<stdin>", io::stdin())
+        };
+        pact.map(|pact| vec![pact])
+            .map_err(|err| format!("Failed to load pact from standard input: {}", err))
+    } else if let Some(archive) = matches.value_of("archive") {
+        info!("Creating mock servers from archive {}", archive);
+        let pacts = if strict {
+            archive::read_pacts_from_archive_strict(Path::new(archive))
+        } else {
+            archive::read_pacts_from_archive(Path::new(archive))
+        };
+        let pacts = pacts.map_err(|err| format!("Failed to load pacts from archive '{}': {}", archive, err))?;
+        if pacts.is_empty() {
+            return Err(format!("No pact files were found in archive '{}'", archive));
+        }
+        pacts.into_iter()
+            .map(|(name, pact)| pact.map_err(|err| format!("Failed to load pact file '{}' from archive '{}': {}",
+                name, archive, err)))
+            .collect()
+    } else {
+        let file = matches.value_of("file").unwrap();
+        info!("Creating mock server from file {}", file);
+        let pact = if strict {
+            Pact::read_pact_strict(&Path::new(file))
+        } else {
+            Pact::read_pact(&Path::new(file))
+        };
+        pact.map(|pact| vec![pact])
+            .map_err(|err| format!("Failed to load pact file '{}': {}", file, err))
+    }
+}
 
-    match Pact::read_pact(&Path::new(file)) {
-        Ok(ref pact) => {
-            let client = Client::new();
-            let url = Url::parse(format!("http://{}:{}/", host, port).as_str()).unwrap();
-            let res = client.post(url.clone())
-                .body(&pact.to_json(pact.spec_version()).to_string())
-                .header(ContentType(Mime(TopLevel::Application, SubLevel::Json,
-                             vec![(Attr::Charset, Value::Utf8)])))
-                .send();
+fn start_mock_server(host: &str, port: u16, pact: &Pact, matches: &ArgMatches) -> Result<(), i32> {
+    let client = Client::new();
+    let url = Url::parse(format!("http://{}:{}/", host, port).as_str()).unwrap();
+    let res = client.post(url.clone())
+        .body(&pact.to_json(pact.spec_version()).to_string())
+        .header(ContentType(Mime(TopLevel::Application, SubLevel::Json,
+                     vec![(Attr::Charset, Value::Utf8)])))
+        .send();
 
-            match res {
-                Ok(mut result) => {
-                    let mut body = String::new();
-                    result.read_to_string(&mut body).unwrap();
-                    if result.status.is_success() {
-                        let json_result: Result<serde_json::Value, _> = serde_json::from_str(body.as_str());
-                        match json_result {
-                            Ok(json) => {
-                                let mock_server = json.get("mockServer").unwrap();
-                                let id = mock_server.get("id").unwrap();
-                                let port = mock_server.get("port").unwrap();
-                                println!("Mock server {} started on port {}", id, port);
-                                Ok(())
-                            },
-                            Err(err) => {
-                                error!("Failed to parse JSON: {}\n{}", err, body);
-                                ::display_error(format!("Failed to parse JSON: {}\n{}", err, body), matches);
-                            }
-                        }
-                    } else {
-                        ::display_error(format!("Master mock server returned an error: {}\n{}", result.status, body), matches);
+    match res {
+        Ok(mut result) => {
+            let mut body = String::new();
+            result.read_to_string(&mut body).unwrap();
+            if result.status.is_success() {
+                let json_result: Result<serde_json::Value, _> = serde_json::from_str(body.as_str());
+                match json_result {
+                    Ok(json) => {
+                        let mock_server = json.get("mockServer").unwrap();
+                        let id = mock_server.get("id").unwrap();
+                        let port = mock_server.get("port").unwrap();
+                        println!("Mock server {} started on port {}", id, port);
+                        Ok(())
+                    },
+                    Err(err) => {
+                        error!("Failed to parse JSON: {}\n{}", err, body);
+                        ::display_error(format!("Failed to parse JSON: {}\n{}", err, body), matches);
                     }
-                },
-                Err(err) => {
-                    ::display_error(format!("Failed to connect to the master mock server '{}': {}", url, err), matches);
                 }
+            } else {
+                ::display_error(format!("Master mock server returned an error: {}\n{}", result.status, body), matches);
             }
         },
         Err(err) => {
-            ::display_error(format!("Failed to load pact file '{}': {}", file, err), matches);
+            ::display_error(format!("Failed to connect to the master mock server '{}': {}", url, err), matches);
         }
     }
 }
+
+pub fn create_mock_server(host: &str, port: u16, matches: &ArgMatches) -> Result<(), i32> {
+    match load_pacts(matches) {
+        Ok(pacts) => {
+            for pact in &pacts {
+                start_mock_server(host, port, pact, matches)?;
+            }
+            Ok(())
+        },
+        Err(err) => ::display_error(err, matches)
+    }
+}
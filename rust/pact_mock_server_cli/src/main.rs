@@ -202,8 +202,22 @@ fn handle_command_args() -> Result<(), i32> {
                     .long("file")
                     .takes_value(true)
                     .use_delimiter(false)
-                    .required(true)
+                    .required_unless_one(&["archive", "stdin"])
+                    .conflicts_with_all(&["archive", "stdin"])
                     .help("the pact file to define the mock server"))
+                .arg(Arg::with_name("archive")
+                    .short("a")
+                    .long("archive")
+                    .takes_value(true)
+                    .use_delimiter(false)
+                    .conflicts_with("stdin")
+                    .help("zip or tar(.gz) archive of pact files, a mock server will be created for each pact file"))
+                .arg(Arg::with_name("stdin")
+                    .long("stdin")
+                    .help("read the pact file to define the mock server from standard input"))
+                .arg(Arg::with_name("strict")
+                    .long("strict")
+                    .help("strictly parse the pact files, and fail if there are any problems with them"))
                 .setting(AppSettings::ColoredHelp))
         .subcommand(SubCommand::with_name("verify")
                 .about("Verify the mock server by id or port number, and generate a pact file if all ok")
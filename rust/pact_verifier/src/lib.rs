@@ -39,7 +39,8 @@ use std::fmt::{Display, Formatter};
 use pact_matching::*;
 use pact_matching::models::*;
 use pact_matching::models::provider_states::*;
-use pact_matching::models::http_utils::HttpAuth;
+use pact_matching::models::http_utils::{HttpAuth, FetchOptions};
+use pact_matching::explain::explain_body;
 use ansi_term::*;
 use ansi_term::Colour::*;
 use std::collections::HashMap;
@@ -58,8 +59,15 @@ pub enum PactSource {
     File(String),
     /// Load all the pacts from a Directory
     Dir(String),
+    /// Load all the pacts from a zip or tar(.gz) archive
+    Archive(String),
+    /// Load the pact from standard input
+    Stdin,
     /// Load the pact from a URL
     URL(String, Option<HttpAuth>),
+    /// Load the pact from a URL, using the fetch options to configure the HTTP client (timeouts,
+    /// retries, headers, proxy and TLS)
+    URLWithOptions(String, Option<HttpAuth>, FetchOptions),
     /// Load all pacts with the provider name from the pact broker url
     BrokerUrl(String, String, Option<HttpAuth>, Vec<Link>)
 }
@@ -69,7 +77,10 @@ impl Display for PactSource {
     match self {
       &PactSource::File(ref file) => write!(f, "File({})", file),
       &PactSource::Dir(ref dir) => write!(f, "Dir({})", dir),
+      &PactSource::Archive(ref archive) => write!(f, "Archive({})", archive),
+      &PactSource::Stdin => write!(f, "Stdin"),
       &PactSource::URL(ref url, _) => write!(f, "URL({})", url),
+      &PactSource::URLWithOptions(ref url, _, _) => write!(f, "URL({})", url),
       &PactSource::BrokerUrl(ref provider_name, ref broker_url, _, _) => {
         write!(f, "PactBroker({}, provider_name='{}')", broker_url, provider_name)
       }
@@ -162,7 +173,10 @@ fn provider_client_error_to_string(err: ProviderClientError) -> String {
 
 fn verify_response_from_provider(provider: &ProviderInfo, interaction: &Interaction, runtime: &mut Runtime) -> Result<(), MismatchResult> {
   let ref expected_response = interaction.response;
-  match runtime.block_on(make_provider_request(provider, &pact_matching::generate_request(&interaction.request, &hashmap!{}))) {
+  let context = interaction.provider_states.iter()
+    .flat_map(|state| state.params.clone())
+    .collect::<HashMap<String, Value>>();
+  match runtime.block_on(make_provider_request(provider, &pact_matching::generate_request(&interaction.request, &context))) {
     Ok(ref actual_response) => {
       let mismatches = match_response(expected_response.clone(), actual_response.clone());
       if mismatches.is_empty() {
@@ -205,7 +219,7 @@ fn execute_state_change(provider_state: &ProviderState, provider: &ProviderInfo,
                   json_body_mut.insert(k, v);
                 }
               }
-              state_change_request.body = OptionalBody::Present(json_body.to_string().into());
+              state_change_request.body = OptionalBody::Present(json_body.to_string().into(), None);
               state_change_request.headers = Some(hashmap!{ s!("Content-Type") => vec![s!("application/json")] });
             } else {
               let mut query = hashmap!{ s!("state") => vec![provider_state.name.clone()] };
@@ -274,6 +288,13 @@ fn display_result(status: u16, status_result: ANSIGenericString<str>,
     println!("      has a matching body ({})", body_result);
 }
 
+fn read_archive(path: &Path) -> io::Result<Vec<io::Result<Pact>>> {
+    debug!("Loading pacts from archive {:?}", path);
+    archive::read_pacts_from_archive(path).map(|pacts| pacts.into_iter().map(|(name, pact)| {
+        pact.map_err(|err| io::Error::new(err.kind(), format!("{} in archive {:?}: {}", name, path, err)))
+    }).collect())
+}
+
 fn walkdir(dir: &Path) -> io::Result<Vec<io::Result<Pact>>> {
     let mut pacts = vec![];
     debug!("Scanning {:?}", dir);
@@ -281,9 +302,17 @@ fn walkdir(dir: &Path) -> io::Result<Vec<io::Result<Pact>>> {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            walkdir(&path)?;
-        } else {
+            pacts.extend(walkdir(&path)?);
+        } else if archive::ArchiveType::from_path(&path).is_some() {
+            match read_archive(&path) {
+                Ok(archive_pacts) => pacts.extend(archive_pacts),
+                Err(err) => pacts.push(Err(io::Error::new(err.kind(),
+                    format!("Could not load pacts from archive {:?}: {}", path, err))))
+            }
+        } else if path.extension().map(|ext| ext == "json").unwrap_or(false) {
             pacts.push(Pact::read_pact(&path))
+        } else {
+            debug!("Ignoring {:?} as it is not a pact file", path);
         }
     }
     Ok(pacts)
@@ -293,7 +322,26 @@ fn display_body_mismatch(expected: &Response, actual: &Response, path: &String)
     match expected.content_type_enum() {
         DetectedContentType::Json => println!("{}", pact_matching::json::display_diff(&expected.body.str_value().to_string(),
             &actual.body.str_value().to_string(), path)),
-        _ => ()
+        DetectedContentType::Yaml => println!("{}", pact_matching::yaml::display_diff(&expected.body.str_value().to_string(),
+            &actual.body.str_value().to_string(), path)),
+        DetectedContentType::Xml => println!("{}", pact_matching::diff::display_xml_diff(&expected.body.str_value().to_string(),
+            &actual.body.str_value().to_string())),
+        // the description of text body mismatches already includes the diff
+        DetectedContentType::Text => ()
+    }
+}
+
+fn display_explanation(expected: &Response, actual: &Response) {
+    match explain_body(expected, actual, DiffConfig::AllowUnexpectedKeys) {
+        Ok(ref explanation) if !explanation.entries.is_empty() => {
+            println!("    Explanation of the body comparison:\n");
+            for line in explanation.to_string().lines() {
+                println!("      {}", line);
+            }
+            println!();
+        },
+        Ok(_) => (),
+        Err(err) => println!("    Could not explain the body comparison - {}\n", err)
     }
 }
 
@@ -399,7 +447,10 @@ pub struct VerificationOptions {
   /// Provider version being published
   pub provider_version: Option<String>,
   /// Build URL to associate with the published results
-  pub build_url: Option<String>
+  pub build_url: Option<String>,
+  /// If an explanation of how the matching rules were applied to the response body should be
+  /// displayed for each failure
+  pub explain: bool
 }
 
 /// Verify the provider with the given pact sources
@@ -446,13 +497,20 @@ pub fn verify_provider(provider_info: &ProviderInfo, source: Vec<PactSource>, fi
               let mismatch = mismatches.first().unwrap();
               println!("{}) {}{}", i, description, mismatch.summary());
               for mismatch in mismatches {
-                println!("    {}\n", mismatch.ansi_description());
+                for line in mismatch.ansi_description().lines() {
+                  println!("    {}", line);
+                }
+                println!();
               }
 
               match mismatch {
                 &Mismatch::BodyMismatch{ref path, ..} => display_body_mismatch(expected, actual, path),
                 _ => ()
               }
+
+              if options.explain {
+                display_explanation(expected, actual);
+              }
             }
           }
         }
@@ -479,9 +537,25 @@ fn fetch_pacts(source: &Vec<PactSource>, consumers: &Vec<String>, runtime: &mut
         }).collect(),
         Err(err) => vec![Err(format!("Could not load pacts from directory '{}' - {}", dir, err))]
       },
+      &PactSource::Archive(ref archive) => match read_archive(Path::new(archive)) {
+        Ok(ref pacts) if pacts.is_empty() => vec![Err(format!("No pact files were found in archive '{}'", archive))],
+        Ok(ref pacts) => pacts.iter().map(|p| {
+          match p {
+            &Ok(ref pact) => Ok((pact.clone(), s.clone())),
+            &Err(ref err) => Err(format!("Failed to load pact - {}", err))
+          }
+        }).collect(),
+        Err(err) => vec![Err(format!("Could not load pacts from archive '{}' - {}", archive, err))]
+      },
+      &PactSource::Stdin => vec![Pact::read_pact_from_reader("<stdin>", io::stdin())
+        .map_err(|err| format!("Failed to load pact from standard input - {}", err))
+        .map(|pact| (pact, s.clone()))],
       &PactSource::URL(ref url, ref auth) => vec![Pact::from_url(url, auth)
         .map_err(|err| format!("Failed to load pact '{}' - {}", url, err))
         .map(|pact| (pact, s.clone()))],
+      &PactSource::URLWithOptions(ref url, ref auth, ref options) => vec![Pact::from_url_with_options(url, auth, options)
+        .map_err(|err| format!("Failed to load pact '{}' - {}", url, err))
+        .map(|pact| (pact, s.clone()))],
       &PactSource::BrokerUrl(ref provider_name, ref broker_url, ref auth, _) => {
         let future = pact_broker::fetch_pacts_from_broker(broker_url.clone(), provider_name.clone(), auth.clone());
         match runtime.block_on(future) {
@@ -515,10 +515,20 @@ fn build_payload(result: TestResult, version: String, build_url: Option<String>)
                       "identifier": "$",
                       "description": format!("Expected body type of '{}' but received '{}'", expected, actual)
                     })),
-                    &Mismatch::BodyMismatch { ref path, ref mismatch, .. } => acc.0.push(json!({
+                    &Mismatch::BodyMismatch { ref path, ref mismatch, ref details, .. } => {
+                      let mut json = json!({
+                        "attribute": "body",
+                        "identifier": path,
+                        "description": mismatch
+                      });
+                      if let &Some(ref details) = details {
+                        json["details"] = details.to_json();
+                      }
+                      acc.0.push(json)
+                    },
+                    &Mismatch::TruncatedMismatches { .. } => acc.0.push(json!({
                       "attribute": "body",
-                      "identifier": path,
-                      "description": mismatch
+                      "description": mismatch.description()
                     }))
                   }
                 }
@@ -558,6 +568,8 @@ mod tests {
     use pact_matching::models::{Pact, Consumer, Provider, Interaction, PactSpecification};
     use tokio::runtime::current_thread::Runtime;
   use pact_matching::Mismatch::MethodMismatch;
+  use pact_matching::{MismatchDetails, MismatchKind};
+  use pact_matching::models::matchingrules::MatchingRule;
 
   #[test]
     fn fetch_returns_an_error_if_there_is_no_pact_broker() {
@@ -1007,6 +1019,42 @@ mod tests {
     })));
   }
 
+  #[test]
+  fn test_build_payload_includes_the_details_of_body_mismatches() {
+    let result = TestResult::Failed(vec![
+      ("Description".to_string(), MismatchResult::Mismatches {
+        mismatches: vec![
+          Mismatch::BodyMismatch {
+            path: "$.id".to_string(),
+            expected: Some("100".into()),
+            actual: Some("\"abc\"".into()),
+            mismatch: "Expected 'abc' to be an integer value".to_string(),
+            details: Some(MismatchDetails::new(MismatchKind::RuleMismatch(MatchingRule::Integer),
+              &["$".to_string(), "id".to_string()], json!(100), json!("abc")))
+          }
+        ],
+        expected: Default::default(),
+        actual: Default::default(),
+        interaction_id: Some("1234abc".to_string())
+      })
+    ]);
+    let payload = super::build_payload(result, "1".to_string(), None);
+    expect!(payload["testResults"][0]["mismatches"].clone()).to(be_equal_to(json!([
+      {
+        "attribute": "body",
+        "identifier": "$.id",
+        "description": "Expected 'abc' to be an integer value",
+        "details": {
+          "kind": "RuleMismatch",
+          "rule": { "match": "integer" },
+          "pointer": "/id",
+          "expected": 100,
+          "actual": "abc"
+        }
+      }
+    ])));
+  }
+
   #[test]
   fn test_build_payload_with_failure_with_exception() {
     let result = TestResult::Failed(vec![
@@ -78,7 +78,7 @@ fn create_hyper_request(base_url: &String, request: &Request) -> Result<HyperReq
 
     let hyper_request = builder
         .body(match request.body {
-            OptionalBody::Present(ref s) => Body::from(s.clone()),
+            OptionalBody::Present(ref s, _) => Body::from(s.clone()),
             OptionalBody::Null => {
                 if request.content_type() == "application/json" {
                     Body::from("null")
@@ -124,7 +124,7 @@ pub fn extract_body(hyper_body: Result<hyper::Chunk, HyperError>) -> Result<Opti
         Ok(chunk) => {
             let bytes = chunk.into_bytes();
             if bytes.len() > 0 {
-                Ok(OptionalBody::Present(bytes.to_vec()))
+                Ok(OptionalBody::Present(bytes.to_vec(), None))
             } else {
                 Ok(OptionalBody::Empty)
             }
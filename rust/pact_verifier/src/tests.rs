@@ -1,5 +1,6 @@
 use expectest::prelude::*;
-use super::{FilterInfo, filter_interaction, filter_consumers, execute_state_change, ProviderInfo};
+use super::{FilterInfo, filter_interaction, filter_consumers, execute_state_change, ProviderInfo, VerificationOptions,
+  verify_provider, walkdir};
 use pact_matching::models::*;
 use pact_matching::models::provider_states::*;
 use pact_consumer::prelude::*;
@@ -7,6 +8,7 @@ use env_logger::*;
 use tokio::runtime::current_thread::Runtime;
 use PactSource;
 use std::panic::catch_unwind;
+use std::{env, fs, process};
 use pact_broker::Link;
 
 #[test]
@@ -190,7 +192,8 @@ fn publish_result_does_nothing_if_not_from_broker() {
     let options = super::VerificationOptions {
       publish: true,
       provider_version: None,
-      build_url: None
+      build_url: None,
+      explain: false
     };
     super::publish_result(&vec![], &PactSource::File("/tmp/test".into()), &options,
       &mut Runtime::new().unwrap());
@@ -217,7 +220,8 @@ fn publish_successful_result_to_broker() {
   let options = super::VerificationOptions {
     publish: true,
     provider_version: Some("1".into()),
-    build_url: None
+    build_url: None,
+    explain: false
   };
   let links = vec![
     Link {
@@ -229,3 +233,56 @@ fn publish_successful_result_to_broker() {
   let source = PactSource::BrokerUrl("Test".to_string(), server.url().to_string(), None, links);
   super::publish_result(&vec![], &source, &options, &mut Runtime::new().unwrap());
 }
+
+#[test]
+fn verify_provider_with_a_directory_of_written_pacts() {
+  init().unwrap_or(());
+
+  let provider = PactBuilder::new("Consumer", "Provider")
+    .interaction("get hello", |i| {
+      i.request.path("/hello");
+      i.response.status(200);
+    })
+    .start_mock_server();
+
+  let mut dir = env::temp_dir();
+  dir.push(format!("verify_provider_dir_test_{}", process::id()));
+  let pact = Pact { consumer: Consumer { name: s!("Consumer") }, provider: Provider { name: s!("Provider") },
+    interactions: vec![ Interaction {
+      description: s!("get hello"),
+      request: Request { path: s!("/hello"), .. Request::default() },
+      response: Response { status: 200, .. Response::default() },
+      .. Interaction::default()
+    } ],
+    .. Pact::default() };
+  pact.write_pact(&dir.join(pact.default_file_name()), PactSpecification::V3).unwrap();
+  pact.write_pact(&dir.join(pact.default_file_name()), PactSpecification::V3).unwrap();
+  fs::write(dir.join("README.md"), "Not a pact file").unwrap();
+
+  let provider_info = ProviderInfo { port: provider.url().port().unwrap(), .. ProviderInfo::default() };
+  let options = VerificationOptions { publish: false, provider_version: None, build_url: None, explain: false };
+  let result = verify_provider(&provider_info, vec![PactSource::Dir(dir.to_string_lossy().to_string())],
+    &FilterInfo::None, &vec![], &options, &mut Runtime::new().unwrap());
+  let files = fs::read_dir(&dir).map(|entries| entries.count()).unwrap_or_default();
+  fs::remove_dir_all(&dir).unwrap_or(());
+
+  expect!(result).to(be_true());
+  expect!(files).to(be_equal_to(2));
+}
+
+#[test]
+fn walkdir_keeps_loading_pacts_when_an_archive_can_not_be_read() {
+  let mut dir = env::temp_dir();
+  dir.push(format!("walkdir_archive_test_{}", process::id()));
+  let pact = Pact { consumer: Consumer { name: s!("Consumer") }, provider: Provider { name: s!("Provider") },
+    .. Pact::default() };
+  pact.write_pact(&dir.join(pact.default_file_name()), PactSpecification::V3).unwrap();
+  fs::write(dir.join("pacts.zip"), "not a zip file").unwrap();
+
+  let pacts = walkdir(&dir);
+  fs::remove_dir_all(&dir).unwrap_or(());
+
+  let pacts = pacts.unwrap();
+  expect!(pacts.len()).to(be_equal_to(2));
+  expect!(pacts.iter().filter(|pact| pact.is_ok()).count()).to(be_equal_to(1));
+}
@@ -18,13 +18,16 @@
 //!     pact_verifier_cli [FLAGS] [OPTIONS] --file <file> --dir <dir> --url <url> --broker-url <broker-url> --provider-name <provider-name>
 //!
 //! FLAGS:
+//!         --explain                  Displays how the matching rules were applied to each path of the response body for each failure
 //!         --filter-no-state          Only validate interactions that have no defined provider state
 //!         --help                     Prints help information
+//!         --stdin                    Read a pact file to verify from standard input
 //!         --state-change-as-query    State change request data will be sent as query parameters instead of in the request body
 //!         --state-change-teardown    State change teardown requests are to be made after each interaction
 //!     -v, --version                  Prints version information
 //!
 //! OPTIONS:
+//!     -a, --archive <archive>                          Zip or tar(.gz) archive of pact files to verify (can be repeated)
 //!     -b, --broker-url <broker-url>                    URL of the pact broker to fetch pacts from to verify (requires the provider name parameter)
 //!     -d, --dir <dir>                                  Directory of pact files to verify (can be repeated)
 //!     -f, --file <file>                                Pact file to verify (can be repeated)
@@ -53,9 +56,24 @@
 //! |--------|------|-------------|
 //! | `-f, --file <file>` | File | Loads a pact from the given file |
 //! | `-u, --url <url>` | URL | Loads a pact from a URL resource |
-//! | `-d, --dir <dir>` | Directory | Loads all the pacts from the given directory |
+//! | `-d, --dir <dir>` | Directory | Loads all the pacts from the given directory and its sub-directories, including any zip or tar(.gz) archives |
+//! | `-a, --archive <archive>` | Archive | Loads all the pacts (files with a `.json` extension) from the given zip or tar(.gz) archive |
+//! | `--stdin` | Standard input | Loads a pact from standard input |
 //! | `-b, --broker-url <broker-url>` | Pact Broker | Loads all the pacts for the provider from the pact broker. Requires the `-n, --provider-name <provider-name>` option |
 //!
+//! ### Fetching pacts from URLs
+//!
+//! The following options configure how pacts are fetched with the `-u, --url <url>` option:
+//!
+//! | Option | Description |
+//! |--------|-------------|
+//! | `--header <NAME=VALUE>` | Additional header to send with the request (can be repeated) |
+//! | `--fetch-timeout <seconds>` | Timeout for each request (defaults to 30 seconds) |
+//! | `--fetch-retries <retries>` | Number of times to retry a request that fails to connect, times out or gets a 5xx or 429 response. Retries are made with an exponential backoff starting at one second, up to one minute between retries |
+//! | `--proxy <url>` | Proxy to send the requests through |
+//! | `--ca-bundle <file>` | PEM file of additional CA certificates to trust |
+//! | `--insecure-tls` | Disables TLS certificate validation. Only use this for testing |
+//!
 //! ### Provider Options
 //!
 //! The running provider can be specified with the following options:
@@ -102,6 +120,15 @@
 //!
 //! This option will cause the verifier to also make a tear down request after the main request is made. It will receive a second field in the body or a query parameter named `action` with the value `teardown`.
 //!
+//! ### Explaining failures
+//!
+//! #### `--explain`
+//!
+//! For each failure, this displays how the matching rules were applied to the response body. For every path in the body that
+//! was compared, it lists all the matching rules whose path expression applies to the path along with their weights, the rule
+//! that was selected and the outcome of the comparison. This helps to work out which rule caused a mismatch, or why a body
+//! matched when it was not expected to. Only JSON bodies can be explained.
+//!
 //! ## Example run
 //!
 //! This will verify all the pacts for the `happy_provider` found in the pact broker (running on localhost) against the provider running on localhost port 5050. Only the pacts for the consumers `Consumer` and `Consumer2` will be verified.
@@ -220,7 +247,10 @@ use std::str::FromStr;
 use std::error::Error;
 use regex::Regex;
 use tokio::runtime::current_thread::Runtime;
-use pact_matching::models::http_utils::HttpAuth;
+use pact_matching::models::http_utils::{HttpAuth, FetchOptions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
 fn main() {
     match handle_command_args() {
@@ -238,6 +268,39 @@ fn integer_value(v: String) -> Result<(), String> {
     v.parse::<u16>().map(|_| ()).map_err(|e| format!("'{}' is not a valid port value: {}", v, e) )
 }
 
+fn fetch_timeout_value(v: String) -> Result<(), String> {
+    v.parse::<u64>().map(|_| ()).map_err(|e| format!("'{}' is not a valid timeout in seconds: {}", v, e) )
+}
+
+fn fetch_retries_value(v: String) -> Result<(), String> {
+    v.parse::<u32>().map(|_| ()).map_err(|e| format!("'{}' is not a valid number of retries: {}", v, e) )
+}
+
+fn header_value(v: String) -> Result<(), String> {
+    parse_header(&v).map(|_| ())
+}
+
+fn parse_header(v: &str) -> Result<(String, String), String> {
+    match v.find('=') {
+        Some(index) if index > 0 => Ok((v[..index].trim().to_string(), v[index + 1..].trim().to_string())),
+        _ => Err(format!("'{}' is not a valid header, it must be in the form NAME=VALUE", v))
+    }
+}
+
+fn fetch_options(matches: &ArgMatches) -> FetchOptions {
+    FetchOptions {
+        timeout: matches.value_of("fetch-timeout").map(|v| Duration::from_secs(v.parse::<u64>().unwrap())),
+        retries: matches.value_of("fetch-retries").map(|v| v.parse::<u32>().unwrap()).unwrap_or(0),
+        headers: matches.values_of("header")
+            .map(|values| values.map(|v| parse_header(v).unwrap()).collect())
+            .unwrap_or_else(HashMap::new),
+        proxy: matches.value_of("proxy").map(|v| v.to_string()),
+        ca_bundle: matches.value_of("ca-bundle").map(PathBuf::from),
+        insecure: matches.is_present("insecure-tls"),
+        .. FetchOptions::default()
+    }
+}
+
 fn pact_source(matches: &ArgMatches) -> Vec<PactSource> {
     let mut sources = vec![];
     match matches.values_of("file") {
@@ -248,16 +311,24 @@ fn pact_source(matches: &ArgMatches) -> Vec<PactSource> {
         Some(values) => sources.extend(values.map(|v| PactSource::Dir(s!(v))).collect::<Vec<PactSource>>()),
         None => ()
     };
+    match matches.values_of("archive") {
+        Some(values) => sources.extend(values.map(|v| PactSource::Archive(s!(v))).collect::<Vec<PactSource>>()),
+        None => ()
+    };
+    if matches.is_present("stdin") {
+        sources.push(PactSource::Stdin);
+    }
     match matches.values_of("url") {
       Some(values) => sources.extend(values.map(|v| {
         if matches.is_present("user") {
-          PactSource::URL(s!(v), matches.value_of("user").map(|user| {
+          PactSource::URLWithOptions(s!(v), matches.value_of("user").map(|user| {
             HttpAuth::User(user.to_string(), matches.value_of("password").map(|p| p.to_string()))
-          }))
+          }), fetch_options(matches))
         } else if matches.is_present("token") {
-          PactSource::URL(s!(v), matches.value_of("token").map(|token| HttpAuth::Token(token.to_string())))
+          PactSource::URLWithOptions(s!(v), matches.value_of("token").map(|token| HttpAuth::Token(token.to_string())),
+            fetch_options(matches))
         } else {
-          PactSource::URL(s!(v), None)
+          PactSource::URLWithOptions(s!(v), None, fetch_options(matches))
         }
       }).collect::<Vec<PactSource>>()),
       None => ()
@@ -325,7 +396,7 @@ fn handle_command_args() -> Result<(), i32> {
         .arg(Arg::with_name("file")
             .short("f")
             .long("file")
-            .required_unless_one(&["dir", "url", "broker-url"])
+            .required_unless_one(&["dir", "url", "broker-url", "archive", "stdin"])
             .takes_value(true)
             .use_delimiter(false)
             .multiple(true)
@@ -335,7 +406,7 @@ fn handle_command_args() -> Result<(), i32> {
         .arg(Arg::with_name("dir")
             .short("d")
             .long("dir")
-            .required_unless_one(&["file", "url", "broker-url"])
+            .required_unless_one(&["file", "url", "broker-url", "archive", "stdin"])
             .takes_value(true)
             .use_delimiter(false)
             .multiple(true)
@@ -345,7 +416,7 @@ fn handle_command_args() -> Result<(), i32> {
         .arg(Arg::with_name("url")
             .short("u")
             .long("url")
-            .required_unless_one(&["file", "dir", "broker-url"])
+            .required_unless_one(&["file", "dir", "broker-url", "archive", "stdin"])
             .takes_value(true)
             .use_delimiter(false)
             .multiple(true)
@@ -355,7 +426,7 @@ fn handle_command_args() -> Result<(), i32> {
         .arg(Arg::with_name("broker-url")
             .short("b")
             .long("broker-url")
-            .required_unless_one(&["file", "dir", "url"])
+            .required_unless_one(&["file", "dir", "url", "archive", "stdin"])
             .requires("provider-name")
             .takes_value(true)
             .use_delimiter(false)
@@ -363,6 +434,20 @@ fn handle_command_args() -> Result<(), i32> {
             .number_of_values(1)
             .empty_values(false)
             .help("URL of the pact broker to fetch pacts from to verify (requires the provider name parameter)"))
+        .arg(Arg::with_name("archive")
+            .short("a")
+            .long("archive")
+            .required_unless_one(&["file", "dir", "url", "broker-url", "stdin"])
+            .takes_value(true)
+            .use_delimiter(false)
+            .multiple(true)
+            .number_of_values(1)
+            .empty_values(false)
+            .help("Zip or tar(.gz) archive of pact files to verify (can be repeated)"))
+        .arg(Arg::with_name("stdin")
+            .long("stdin")
+            .required_unless_one(&["file", "dir", "url", "broker-url", "archive"])
+            .help("Read a pact file to verify from standard input"))
         .arg(Arg::with_name("hostname")
             .short("h")
             .long("hostname")
@@ -447,6 +532,40 @@ fn handle_command_args() -> Result<(), i32> {
           .empty_values(false)
           .conflicts_with("user")
           .help("Bearer token to use when fetching pacts from URLS"))
+        .arg(Arg::with_name("header")
+          .long("header")
+          .takes_value(true)
+          .use_delimiter(false)
+          .multiple(true)
+          .number_of_values(1)
+          .empty_values(false)
+          .validator(header_value)
+          .help("Additional header to send when fetching pacts from URLS, in the form NAME=VALUE (can be repeated)"))
+        .arg(Arg::with_name("fetch-timeout")
+          .long("fetch-timeout")
+          .takes_value(true)
+          .use_delimiter(false)
+          .validator(fetch_timeout_value)
+          .help("Timeout in seconds when fetching pacts from URLS (defaults to 30)"))
+        .arg(Arg::with_name("fetch-retries")
+          .long("fetch-retries")
+          .takes_value(true)
+          .use_delimiter(false)
+          .validator(fetch_retries_value)
+          .help("Number of times to retry fetching pacts from URLS if the request fails (defaults to 0)"))
+        .arg(Arg::with_name("proxy")
+          .long("proxy")
+          .takes_value(true)
+          .use_delimiter(false)
+          .help("Proxy to use when fetching pacts from URLS"))
+        .arg(Arg::with_name("ca-bundle")
+          .long("ca-bundle")
+          .takes_value(true)
+          .use_delimiter(false)
+          .help("PEM file of additional CA certificates to trust when fetching pacts from URLS"))
+        .arg(Arg::with_name("insecure-tls")
+          .long("insecure-tls")
+          .help("Disables TLS certificate validation when fetching pacts from URLS"))
         .arg(Arg::with_name("publish")
           .long("publish")
           .requires("broker-url")
@@ -466,6 +585,9 @@ fn handle_command_args() -> Result<(), i32> {
           .number_of_values(1)
           .empty_values(false)
           .help("URL of the build to associate with the published verification results."))
+        .arg(Arg::with_name("explain")
+          .long("explain")
+          .help("Displays how the matching rules were applied to each path of the response body for each failure"))
         ;
 
     let matches = app.get_matches_safe();
@@ -492,7 +614,8 @@ fn handle_command_args() -> Result<(), i32> {
             let options = VerificationOptions {
               publish: matches.is_present("publish"),
               provider_version: matches.value_of("provider-version").map(|v| v.to_string()),
-              build_url: matches.value_of("build-url").map(|v| v.to_string())
+              build_url: matches.value_of("build-url").map(|v| v.to_string()),
+              explain: matches.is_present("explain")
             };
             if verify_provider(&provider, source, &filter,
                                &matches.values_of_lossy("filter-consumer").unwrap_or(vec![]),
@@ -526,7 +649,7 @@ mod test {
 
     use quickcheck::{TestResult, quickcheck};
     use rand::Rng;
-    use super::integer_value;
+    use super::{integer_value, fetch_timeout_value, fetch_retries_value, parse_header};
     use expectest::prelude::*;
 
     #[test]
@@ -548,4 +671,21 @@ mod test {
         expect!(integer_value(s!("1234"))).to(be_ok());
         expect!(integer_value(s!("1234x"))).to(be_err());
     }
+
+    #[test]
+    fn validates_fetch_options() {
+        expect!(fetch_timeout_value(s!("300000"))).to(be_ok());
+        expect!(fetch_timeout_value(s!("5s"))).to(be_err().value(
+            s!("'5s' is not a valid timeout in seconds: invalid digit found in string")));
+        expect!(fetch_retries_value(s!("3"))).to(be_ok());
+        expect!(fetch_retries_value(s!("-1"))).to(be_err());
+    }
+
+    #[test]
+    fn parses_headers() {
+        expect!(parse_header("X-Token=abc=123")).to(be_ok().value((s!("X-Token"), s!("abc=123"))));
+        expect!(parse_header(" X-Token = abc ")).to(be_ok().value((s!("X-Token"), s!("abc"))));
+        expect!(parse_header("X-Token")).to(be_err());
+        expect!(parse_header("=abc")).to(be_err());
+    }
 }